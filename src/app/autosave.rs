@@ -0,0 +1,125 @@
+use crate::app::VcvRackApp;
+use crate::models::patch::Patch;
+use eframe::egui;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+const AUTOSAVE_FILE: &str = "autosave.json";
+/// How often a rack with unsaved changes is written to the autosave file.
+pub const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Checks whether the autosave at `autosave` holds work that is newer than
+/// the patch at `last_save`, which is the case after a crash.
+pub fn autosave_is_newer(autosave: &Path, last_save: Option<&Path>) -> bool {
+    let modified = |path: &Path| fs::metadata(path).and_then(|m| m.modified()).ok();
+    match (modified(autosave), last_save.and_then(modified)) {
+        (Some(autosaved), Some(saved)) => autosaved > saved,
+        (Some(_), None) => true,
+        (None, _) => false,
+    }
+}
+
+/// Patch file the autosave at `path` was made from.
+pub fn autosave_source(path: &Path) -> Option<PathBuf> {
+    Patch::load(path).ok()?.patch.source
+}
+
+impl VcvRackApp {
+    pub fn autosave_path(&self) -> Option<PathBuf> {
+        self.settings_dir.as_ref().map(|dir| dir.join(AUTOSAVE_FILE))
+    }
+
+    /// Autosave left behind by a session that didn't end cleanly and that is
+    /// newer than the patch file it was made from.
+    pub(crate) fn recoverable_autosave(&self) -> Option<PathBuf> {
+        self.autosave_path()
+            .filter(|path| autosave_is_newer(path, autosave_source(path).as_deref()))
+    }
+
+    /// Autosaves when there are unsaved changes and the interval has passed
+    /// since the last autosave. Returns true when the rack was written.
+    pub(crate) fn tick_autosave(&mut self, now: Instant) -> bool {
+        match self.autosave_path() {
+            Some(path) => self.autosave_to(&path, now),
+            None => false,
+        }
+    }
+
+    pub fn autosave_to(&mut self, path: &Path, now: Instant) -> bool {
+        if !self.has_unsaved_changes || now.duration_since(self.last_autosave) < AUTOSAVE_INTERVAL {
+            return false;
+        }
+        self.last_autosave = now;
+        let patch = Patch {
+            source: self.current_file.clone(),
+            ..Patch::new(self.plugin_manager.save_state(), self.patch_metadata.touched())
+        };
+        match patch.save(path) {
+            Ok(()) => true,
+            Err(err) => {
+                println!("Autosave failed: {}", err);
+                false
+            }
+        }
+    }
+
+    /// Removes the autosave once its work is saved or deliberately thrown
+    /// away.
+    pub(crate) fn discard_autosave(&self) {
+        if let Some(path) = self.autosave_path().filter(|path| path.exists()) {
+            if let Err(err) = fs::remove_file(&path) {
+                println!("Failed to remove {}: {}", path.display(), err);
+            }
+        }
+    }
+
+    /// Replaces the rack with the autosave at `path`. The restored rack
+    /// belongs to the patch file it was made from and counts as unsaved.
+    pub fn restore_autosave(&mut self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let loaded = Patch::load(path)?;
+        self.plugin_manager.load_state(loaded.patch.rack, self.blank_plate_plugin_texture.clone());
+        self.patch_metadata = loaded.patch.metadata;
+        self.current_file = loaded.patch.source;
+        self.has_unsaved_changes = true;
+        Ok(())
+    }
+
+    /// Offers to restore the autosave found on startup.
+    pub(crate) fn autosave_restore_dialog(&mut self, ctx: &egui::Context) {
+        let Some(path) = self.autosave_offer.clone() else {
+            return;
+        };
+        let mut restore = None;
+        let modal = egui::Modal::new(egui::Id::new("restore_autosave")).show(ctx, |ui| {
+            ui.set_width(320.0);
+            ui.heading("Restore unsaved work?");
+            ui.label("The last session ended without saving. Do you want to restore the rack from the autosave?");
+            ui.add_space(8.0);
+            ui.horizontal(|ui| {
+                if ui.button("Restore").clicked() {
+                    restore = Some(true);
+                }
+                if ui.button("Discard").clicked() {
+                    restore = Some(false);
+                }
+            });
+        });
+        if modal.should_close() {
+            restore.get_or_insert(false);
+        }
+        match restore {
+            Some(true) => {
+                self.autosave_offer = None;
+                if let Err(err) = self.restore_autosave(&path) {
+                    println!("Failed to restore {}: {}", path.display(), err);
+                }
+            }
+            Some(false) => {
+                self.autosave_offer = None;
+                self.discard_autosave();
+            }
+            None => {}
+        }
+    }
+}
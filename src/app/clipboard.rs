@@ -0,0 +1,96 @@
+use crate::app::VcvRackApp;
+use crate::models::geometry::GridPos;
+use crate::models::module_id::ModuleId;
+use crate::models::selection::Selection;
+use eframe::egui;
+
+impl VcvRackApp {
+    /// The selected modules as clipboard text, or `None` when nothing is selected.
+    pub fn copy_selection(&self) -> Option<String> {
+        let selection = self.plugin_manager.selection()?;
+        match selection.to_json() {
+            Ok(json) => Some(json),
+            Err(err) => {
+                println!("Failed to copy modules: {}", err);
+                None
+            }
+        }
+    }
+
+    /// Copies the selected modules and removes them from the rack.
+    pub fn cut_selection(&mut self) -> Option<String> {
+        let json = self.copy_selection()?;
+        self.plugin_manager.delete_selected_plugins();
+        self.has_unsaved_changes = true;
+        Some(json)
+    }
+
+    /// Places copied modules as close to `at` as they fit. Returns the ids
+    /// of the new plugins, or an error when `text` holds no modules or
+    /// there is no room for them on the rails.
+    pub fn paste(&mut self, text: &str, at: GridPos) -> Result<Vec<ModuleId>, Box<dyn std::error::Error>> {
+        let selection = Selection::from_json(text)?;
+        let ids = self.plugin_manager.insert_selection(&selection, at);
+        if ids.is_empty() {
+            return Err("There is no room for the modules on the rails".into());
+        }
+        self.has_unsaved_changes = true;
+        Ok(ids)
+    }
+
+    /// Places a copy of the selected modules next to them. Returns the ids
+    /// of the copies, none when nothing is selected, or an error when there
+    /// is no room for them on the rails.
+    pub fn duplicate_selection(&mut self) -> Result<Vec<ModuleId>, Box<dyn std::error::Error>> {
+        let Some(selection) = self.plugin_manager.selection() else {
+            return Ok(Vec::new());
+        };
+        let at = self.plugin_manager.get_selected_plugins().iter()
+            .map(|p| p.grid())
+            .min_by_key(|grid| (grid.rail, grid.column))
+            .unwrap_or_default();
+        let ids = self.plugin_manager.insert_selection(&selection, at);
+        if ids.is_empty() {
+            return Err("There is no room for the copies on the rails".into());
+        }
+        self.has_unsaved_changes = true;
+        Ok(ids)
+    }
+
+    /// Handles Ctrl+C, Ctrl+X, Ctrl+V and Ctrl+D. Copy, cut and paste arrive
+    /// as clipboard events, so they go through the system clipboard.
+    pub(crate) fn handle_clipboard_shortcuts(&mut self, ctx: &egui::Context) {
+        if self.pending_action().is_some() || ctx.wants_keyboard_input() {
+            return;
+        }
+        let (events, duplicate) = ctx.input(|i| (
+            i.events.clone(),
+            i.modifiers.ctrl && i.key_pressed(egui::Key::D),
+        ));
+        for event in events {
+            match event {
+                egui::Event::Copy => {
+                    if let Some(json) = self.copy_selection() {
+                        ctx.copy_text(json);
+                    }
+                }
+                egui::Event::Cut => {
+                    if let Some(json) = self.cut_selection() {
+                        ctx.copy_text(json);
+                    }
+                }
+                egui::Event::Paste(text) => {
+                    if let Err(err) = self.paste(&text, self.placement_target()) {
+                        println!("Nothing pasted: {}", err);
+                    }
+                }
+                _ => {}
+            }
+        }
+        if duplicate {
+            if let Err(err) = self.duplicate_selection() {
+                println!("Nothing duplicated: {}", err);
+            }
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use crate::app::VcvRackApp;
+use eframe::egui;
+
+/// Menu entry text, e.g. "Undo Move module", or just "Undo" when the stack is empty.
+pub fn history_label(action: &str, name: Option<&str>) -> String {
+    match name {
+        Some(name) => format!("{} {}", action, name),
+        None => action.to_string(),
+    }
+}
+
+impl VcvRackApp {
+    pub(crate) fn edit_menu(&mut self, ui: &mut egui::Ui) {
+        ui.menu_button("Edit", |ui| {
+            ui.set_min_width(200.0); // Match File and View menu width
+            let history = self.plugin_manager.history();
+            let undo_label = history_label("Undo", history.undo_name());
+            let redo_label = history_label("Redo", history.redo_name());
+            let (can_undo, can_redo) = (history.can_undo(), history.can_redo());
+
+            let undo_shortcut = egui::KeyboardShortcut::new(egui::Modifiers::CTRL, egui::Key::Z);
+            if ui.add_enabled(can_undo, egui::Button::new(undo_label).shortcut_text(ui.ctx().format_shortcut(&undo_shortcut))).clicked() {
+                self.undo();
+                ui.close_menu();
+            }
+            let redo_shortcut = egui::KeyboardShortcut::new(egui::Modifiers::CTRL | egui::Modifiers::SHIFT, egui::Key::Z);
+            if ui.add_enabled(can_redo, egui::Button::new(redo_label).shortcut_text(ui.ctx().format_shortcut(&redo_shortcut))).clicked() {
+                self.redo();
+                ui.close_menu();
+            }
+            ui.separator();
+            let has_selection = !self.plugin_manager.get_selected_plugins().is_empty();
+            let command = |key| ui.ctx().format_shortcut(&egui::KeyboardShortcut::new(egui::Modifiers::CTRL, key));
+            let (cut_shortcut, copy_shortcut, paste_shortcut, duplicate_shortcut) =
+                (command(egui::Key::X), command(egui::Key::C), command(egui::Key::V), command(egui::Key::D));
+            if ui.add_enabled(has_selection, egui::Button::new("Cut").shortcut_text(cut_shortcut)).clicked() {
+                if let Some(json) = self.cut_selection() {
+                    ui.ctx().copy_text(json);
+                }
+                ui.close_menu();
+            }
+            if ui.add_enabled(has_selection, egui::Button::new("Copy").shortcut_text(copy_shortcut)).clicked() {
+                if let Some(json) = self.copy_selection() {
+                    ui.ctx().copy_text(json);
+                }
+                ui.close_menu();
+            }
+            if ui.add(egui::Button::new("Paste").shortcut_text(paste_shortcut)).clicked() {
+                // The clipboard text comes back as a paste event
+                ui.ctx().send_viewport_cmd(egui::ViewportCommand::RequestPaste);
+                ui.close_menu();
+            }
+            if ui.add_enabled(has_selection, egui::Button::new("Duplicate").shortcut_text(duplicate_shortcut)).clicked() {
+                if let Err(err) = self.duplicate_selection() {
+                    println!("Nothing duplicated: {}", err);
+                }
+                ui.close_menu();
+            }
+        });
+    }
+
+    /// Handles Ctrl+Z, Ctrl+Shift+Z and Ctrl+Y.
+    pub(crate) fn handle_history_shortcuts(&mut self, ctx: &egui::Context) {
+        // Text fields undo their own typing
+        if ctx.wants_keyboard_input() {
+            return;
+        }
+        let (undo, redo) = ctx.input(|i| {
+            let z = i.modifiers.ctrl && i.key_pressed(egui::Key::Z);
+            let y = i.modifiers.ctrl && i.key_pressed(egui::Key::Y);
+            (z && !i.modifiers.shift, (z && i.modifiers.shift) || y)
+        });
+        if undo {
+            self.undo();
+        } else if redo {
+            self.redo();
+        }
+    }
+
+    pub fn undo(&mut self) {
+        if self.plugin_manager.undo() {
+            self.has_unsaved_changes = self.plugin_manager.history().is_modified();
+        }
+    }
+
+    pub fn redo(&mut self) {
+        if self.plugin_manager.redo() {
+            self.has_unsaved_changes = self.plugin_manager.history().is_modified();
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use crate::app::VcvRackApp;
+use crate::engine::audio_engine::SAMPLE_RATES;
+use crate::engine::{DeviceBackend, EngineState, NullBackend, WavFileBackend};
+use eframe::egui;
+use rfd::FileDialog;
+
+/// Text shown on the right of the menu bar, e.g. "Running | 48000 Hz | CPU 3.2%".
+pub fn engine_status_text(state: &EngineState) -> String {
+    if state.running {
+        format!("Running | {} Hz | CPU {:.1}%", state.sample_rate, state.cpu_load * 100.0)
+    } else {
+        format!("Stopped | {} Hz", state.sample_rate)
+    }
+}
+
+impl VcvRackApp {
+    pub(crate) fn engine_menu(&mut self, ui: &mut egui::Ui) {
+        ui.menu_button("Engine", |ui| {
+            ui.set_min_width(200.0); // Match File and View menu width
+            let running = self.engine.is_running();
+            if ui.add(egui::Button::new(if running { "Stop engine" } else { "Start engine" })).clicked() {
+                if running {
+                    self.engine.stop();
+                } else if let Err(err) = self.engine.start() {
+                    println!("Failed to start audio engine: {}", err);
+                }
+                ui.close_menu();
+            }
+            ui.separator();
+
+            ui.menu_button("Sample rate", |ui| {
+                for rate in SAMPLE_RATES {
+                    let selected = self.engine.sample_rate() == rate;
+                    if ui.radio(selected, format!("{} Hz", rate)).clicked() {
+                        if let Err(err) = self.engine.set_sample_rate(rate) {
+                            println!("Failed to change sample rate: {}", err);
+                        }
+                        ui.close_menu();
+                    }
+                }
+            });
+
+            ui.menu_button("Output", |ui| {
+                let backend = self.engine.state().backend;
+                if ui.radio(backend == "Audio device", "Default audio device").clicked() {
+                    if let Err(err) = self.engine.set_backend(Box::new(DeviceBackend::new())) {
+                        println!("Failed to switch output: {}", err);
+                    }
+                    ui.close_menu();
+                }
+                if ui.radio(backend == "Null", "None (null output)").clicked() {
+                    if let Err(err) = self.engine.set_backend(Box::new(NullBackend::new())) {
+                        println!("Failed to switch output: {}", err);
+                    }
+                    ui.close_menu();
+                }
+                if ui.radio(backend == "WAV file", "Record to WAV file...").clicked() {
+                    if let Some(path) = FileDialog::new()
+                        .set_file_name("recording.wav")
+                        .add_filter("WAV files", &["wav"])
+                        .save_file()
+                    {
+                        if let Err(err) = self.engine.set_backend(Box::new(WavFileBackend::new(path))) {
+                            println!("Failed to switch output: {}", err);
+                        }
+                    }
+                    ui.close_menu();
+                }
+            });
+        });
+    }
+
+    pub(crate) fn engine_status(&self, ui: &mut egui::Ui) {
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            let state = self.engine.state();
+            let color = if state.running {
+                egui::Color32::from_rgb(120, 200, 120)
+            } else {
+                egui::Color32::GRAY
+            };
+            ui.label(egui::RichText::new(engine_status_text(&state)).color(color));
+        });
+    }
+}
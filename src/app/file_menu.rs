@@ -0,0 +1,227 @@
+use crate::app::unsaved_changes::PendingAction;
+use crate::app::VcvRackApp;
+use crate::models::recent_files::RecentFiles;
+use eframe::egui;
+use rfd::FileDialog;
+use std::path::{Path, PathBuf};
+
+const RECENT_FILES: &str = "recent_files.json";
+
+impl VcvRackApp {
+    fn recent_files_path(&self) -> Option<PathBuf> {
+        self.settings_dir.as_ref().map(|dir| dir.join(RECENT_FILES))
+    }
+
+    /// Recent files of the last session.
+    pub(crate) fn load_recent_files(&self) -> RecentFiles {
+        self.recent_files_path()
+            .filter(|path| path.exists())
+            .and_then(|path| RecentFiles::load(&path).ok())
+            .unwrap_or_default()
+    }
+
+    fn save_recent_files(&self) {
+        if let Some(path) = self.recent_files_path() {
+            if let Err(err) = self.recent_files.save(&path) {
+                println!("Failed to save recent files: {}", err);
+            }
+        }
+    }
+
+    /// Puts `path` at the top of the Recent Files menu.
+    pub(crate) fn add_recent_file(&mut self, path: &Path) {
+        self.recent_files.add(path);
+        self.save_recent_files();
+    }
+
+    fn remove_recent_file(&mut self, path: &Path) {
+        self.recent_files.remove(path);
+        self.save_recent_files();
+    }
+
+    /// Folder the file dialogs start in: the one of the open patch, or the save directory.
+    pub(crate) fn dialog_directory(&self) -> Option<PathBuf> {
+        self.current_file.as_ref()
+            .and_then(|file| file.parent().map(Path::to_path_buf))
+            .or_else(Self::get_save_directory)
+    }
+
+    pub(crate) fn file_menu(&mut self, ui: &mut egui::Ui) {
+        ui.menu_button("File", |ui| {
+            ui.set_min_width(200.0); // Match View menu width
+            let open_shortcut = egui::KeyboardShortcut::new(egui::Modifiers::CTRL, egui::Key::O);
+            if ui.add(egui::Button::new("Open...").shortcut_text(ui.ctx().format_shortcut(&open_shortcut))).clicked() {
+                ui.close_menu();
+                self.request_action(PendingAction::Open);
+            }
+            self.recent_files_menu(ui);
+            if ui.button("Import VCV Rack Patch...").clicked() {
+                ui.close_menu();
+                self.request_action(PendingAction::ImportVcv);
+            }
+            if ui.button("Export VCV Rack Patch...").clicked() {
+                ui.close_menu();
+                self.export_vcv();
+            }
+            ui.separator();
+            let has_selection = !self.plugin_manager.get_selected_plugins().is_empty();
+            if ui.add_enabled(has_selection, egui::Button::new("Save Selection As...")).clicked() {
+                ui.close_menu();
+                self.save_selection_as();
+            }
+            if ui.button("Import Selection...").clicked() {
+                ui.close_menu();
+                self.import_selection();
+            }
+            ui.separator();
+            let save_shortcut = egui::KeyboardShortcut::new(egui::Modifiers::CTRL, egui::Key::S);
+            if ui.add(egui::Button::new("Save").shortcut_text(ui.ctx().format_shortcut(&save_shortcut))).clicked() {
+                ui.close_menu();
+                self.save();
+            }
+            let save_as_shortcut = egui::KeyboardShortcut::new(egui::Modifiers::CTRL | egui::Modifiers::SHIFT, egui::Key::S);
+            if ui.add(egui::Button::new("Save As...").shortcut_text(ui.ctx().format_shortcut(&save_as_shortcut))).clicked() {
+                ui.close_menu();
+                self.save_as();
+            }
+            ui.separator();
+            if ui.button("Exit").clicked() {
+                ui.close_menu();
+                self.request_action(PendingAction::Exit);
+            }
+        });
+    }
+
+    fn recent_files_menu(&mut self, ui: &mut egui::Ui) {
+        ui.menu_button("Recent Files", |ui| {
+            if self.recent_files.files.is_empty() {
+                ui.add_enabled(false, egui::Button::new("No recent files"));
+                return;
+            }
+            for file in self.recent_files.files.clone() {
+                let name = file.file_name().map_or_else(|| file.display().to_string(), |n| n.to_string_lossy().into_owned());
+                if ui.button(name).on_hover_text(file.display().to_string()).clicked() {
+                    ui.close_menu();
+                    self.request_action(PendingAction::OpenFile(file));
+                }
+            }
+            ui.separator();
+            if ui.button("Clear Recent Files").clicked() {
+                self.recent_files.clear();
+                self.save_recent_files();
+                ui.close_menu();
+            }
+        });
+    }
+
+    /// Handles Ctrl+O, Ctrl+S and Ctrl+Shift+S.
+    pub(crate) fn handle_file_shortcuts(&mut self, ctx: &egui::Context) {
+        if self.pending_action().is_some() {
+            return;
+        }
+        let (open, save, save_as) = ctx.input(|i| {
+            let s = i.modifiers.ctrl && i.key_pressed(egui::Key::S);
+            (i.modifiers.ctrl && i.key_pressed(egui::Key::O), s && !i.modifiers.shift, s && i.modifiers.shift)
+        });
+        if open {
+            self.request_action(PendingAction::Open);
+        } else if save {
+            self.save();
+        } else if save_as {
+            self.save_as();
+        }
+    }
+
+    /// Asks for a patch file and opens it.
+    pub fn open(&mut self) {
+        let mut dialog = FileDialog::new().add_filter("Patch files", &["json"]);
+        if let Some(dir) = self.dialog_directory() {
+            dialog = dialog.set_directory(dir);
+        }
+        if let Some(path) = dialog.pick_file() {
+            self.open_file(&path);
+        }
+    }
+
+    /// Opens the patch at `path`. Files that can't be read are dropped from
+    /// the Recent Files menu.
+    pub fn open_file(&mut self, path: &Path) -> bool {
+        match self.load_patch(path) {
+            Ok(_) => {
+                println!("Opened {}", path.display());
+                // The autosave belonged to the rack that was just replaced
+                self.discard_autosave();
+                true
+            }
+            Err(err) => {
+                println!("Failed to open {}: {}", path.display(), err);
+                if !path.exists() {
+                    self.remove_recent_file(path);
+                }
+                false
+            }
+        }
+    }
+
+    pub fn load_report(&self) -> Option<&[String]> {
+        self.load_report.as_deref()
+    }
+
+    /// Lists the module ids that had to change while opening a patch file.
+    pub(crate) fn load_report_window(&mut self, ctx: &egui::Context) {
+        let Some(report) = &self.load_report else {
+            return;
+        };
+        let mut open = true;
+        let mut close = false;
+        egui::Window::new("Patch Repaired")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(true)
+            .show(ctx, |ui| {
+                ui.label("Some modules in the patch had ids that were invalid or used twice. They got new ids:");
+                egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                    for line in report {
+                        ui.label(line);
+                    }
+                });
+                if ui.button("OK").clicked() {
+                    close = true;
+                }
+            });
+        if !open || close {
+            self.load_report = None;
+        }
+    }
+
+    /// Saves to the open patch file, or asks for one when the rack was never saved.
+    pub fn save(&mut self) {
+        match self.current_file.clone() {
+            Some(path) => self.save_file(&path),
+            None => self.save_as(),
+        }
+    }
+
+    /// Asks where to save the patch.
+    pub fn save_as(&mut self) {
+        let file_name = self.current_file.as_ref()
+            .and_then(|file| file.file_name())
+            .map_or_else(|| "New rack.json".to_string(), |n| n.to_string_lossy().into_owned());
+        let mut dialog = FileDialog::new()
+            .set_file_name(file_name)
+            .add_filter("Patch files", &["json"]);
+        if let Some(dir) = self.dialog_directory() {
+            dialog = dialog.set_directory(dir);
+        }
+        if let Some(path) = dialog.save_file() {
+            self.save_file(&path);
+        }
+    }
+
+    fn save_file(&mut self, path: &Path) {
+        match self.save_patch(path) {
+            Ok(()) => println!("Rack state saved to {}", path.display()),
+            Err(err) => println!("Failed to save {}: {}", path.display(), err),
+        }
+    }
+}
@@ -1,2 +1,15 @@
+pub mod autosave;
+pub mod clipboard;
+pub mod edit_menu;
+pub mod engine_menu;
+pub mod file_menu;
+pub mod module_browser;
+pub mod presets;
+pub mod rail_view;
+pub mod selections;
+pub mod unsaved_changes;
+pub mod vcv_export;
+pub mod vcv_import;
 pub mod vcvrack_app;
-pub use vcvrack_app::VcvRackApp;
\ No newline at end of file
+pub mod zoom;
+pub use vcvrack_app::VcvRackApp;
@@ -0,0 +1,142 @@
+use crate::app::VcvRackApp;
+use crate::models::browser::{self, BrowserPrefs, ModuleBrowser};
+use crate::models::module::ModuleDescriptor;
+use crate::models::module_id::ModuleId;
+use crate::modules;
+use eframe::egui;
+use std::path::PathBuf;
+
+const PREFS_FILE: &str = "module_browser.json";
+
+impl VcvRackApp {
+    fn browser_prefs_path(&self) -> Option<PathBuf> {
+        self.settings_dir.as_ref().map(|dir| dir.join(PREFS_FILE))
+    }
+
+    /// Browser with the favourites and recent modules of the last session.
+    pub(crate) fn load_module_browser(&self) -> ModuleBrowser {
+        let prefs = self.browser_prefs_path()
+            .filter(|path| path.exists())
+            .and_then(|path| BrowserPrefs::load(&path).ok())
+            .unwrap_or_default();
+        ModuleBrowser::new(prefs)
+    }
+
+    fn save_browser_prefs(&self) {
+        if let Some(path) = self.browser_prefs_path() {
+            if let Err(err) = self.module_browser.prefs.save(&path) {
+                println!("Failed to save module browser settings: {}", err);
+            }
+        }
+    }
+
+    /// Opens the browser; the chosen module is placed at `pos`.
+    pub fn open_module_browser(&mut self, pos: egui::Pos2) {
+        self.module_browser.reset_search();
+        self.browser_target = Some(pos);
+        self.focus_browser_search = true;
+    }
+
+    pub fn close_module_browser(&mut self) {
+        self.browser_target = None;
+    }
+
+    pub fn is_module_browser_open(&self) -> bool {
+        self.browser_target.is_some()
+    }
+
+    /// Places `descriptor` where the browser was opened and closes it.
+    /// Returns the id of the new plugin, or `None` when the spot is taken.
+    pub fn place_module(&mut self, descriptor: &'static ModuleDescriptor) -> Option<ModuleId> {
+        let pos = self.browser_target.take()?;
+        let texture = self.plugin_manager.panel_texture(descriptor.slug);
+        self.plugin_manager.deselect_all();
+        let id = self.plugin_manager.add_module(descriptor, pos, texture)?;
+        self.has_unsaved_changes = true;
+        self.module_browser.record_used(descriptor.slug);
+        self.save_browser_prefs();
+        Some(id)
+    }
+
+    pub(crate) fn module_browser_window(&mut self, ctx: &egui::Context) {
+        if !self.is_module_browser_open() {
+            return;
+        }
+        let mut open = true;
+        let mut chosen = None;
+        let mut favorites_changed = false;
+
+        egui::Window::new("Module Browser")
+            .open(&mut open)
+            .collapsible(false)
+            .default_size([420.0, 480.0])
+            .show(ctx, |ui| {
+                let browser = &mut self.module_browser;
+                let search = ui.add(egui::TextEdit::singleline(&mut browser.query)
+                    .hint_text("Search modules")
+                    .desired_width(f32::INFINITY));
+                if std::mem::take(&mut self.focus_browser_search) {
+                    search.request_focus();
+                }
+
+                ui.horizontal(|ui| {
+                    ui.toggle_value(&mut browser.favorites_only, "★ Favourites");
+                    egui::ComboBox::from_id_salt("browser_tag")
+                        .selected_text(browser.tag.unwrap_or("All tags"))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut browser.tag, None, "All tags");
+                            for tag in browser::all_tags(modules::MODULES) {
+                                ui.selectable_value(&mut browser.tag, Some(tag), tag);
+                            }
+                        });
+                    egui::ComboBox::from_id_salt("browser_brand")
+                        .selected_text(browser.brand.unwrap_or("All brands"))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut browser.brand, None, "All brands");
+                            for brand in browser::all_brands(modules::MODULES) {
+                                ui.selectable_value(&mut browser.brand, Some(brand), brand);
+                            }
+                        });
+                });
+                ui.separator();
+
+                let results = browser.search(modules::MODULES);
+                // Enter in the search field takes the best match
+                if search.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                    chosen = results.first().copied();
+                }
+
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    if results.is_empty() {
+                        ui.weak("No modules found");
+                    }
+                    for descriptor in results {
+                        ui.horizontal(|ui| {
+                            let star = if browser.is_favorite(descriptor.slug) { "★" } else { "☆" };
+                            if ui.small_button(star).on_hover_text("Favourite").clicked() {
+                                browser.toggle_favorite(descriptor.slug);
+                                favorites_changed = true;
+                            }
+                            let label = format!("{}  ·  {}  ·  {}HP", descriptor.name, descriptor.brand, descriptor.hp);
+                            if ui.selectable_label(false, label).clicked() {
+                                chosen = Some(descriptor);
+                            }
+                            ui.weak(descriptor.tags.join(", "));
+                        });
+                    }
+                });
+            });
+
+        if favorites_changed {
+            self.save_browser_prefs();
+        }
+        if let Some(descriptor) = chosen {
+            if self.place_module(descriptor).is_none() {
+                println!("Cannot place {}: not enough free space on the rail", descriptor.name);
+            }
+        }
+        if !open || ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
+            self.close_module_browser();
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use crate::app::VcvRackApp;
+use crate::models::module_id::ModuleId;
+use crate::models::preset::{list_presets, preset_directory, PRESET_EXTENSION};
+use crate::modules;
+use rfd::FileDialog;
+use std::path::{Path, PathBuf};
+
+impl VcvRackApp {
+    /// Folder with the presets of module type `slug` in the settings folder.
+    pub fn presets_directory(&self, slug: &str) -> Option<PathBuf> {
+        self.settings_dir.as_ref().map(|dir| preset_directory(dir, slug))
+    }
+
+    /// Reads the preset lists of every module type for the context menus.
+    pub(crate) fn refresh_presets(&mut self) {
+        for descriptor in modules::MODULES {
+            let presets = self.presets_directory(descriptor.slug)
+                .map(|dir| list_presets(&dir))
+                .unwrap_or_default();
+            self.plugin_manager.set_presets(descriptor.slug, presets);
+        }
+    }
+
+    /// Asks for a preset name once a module's context menu asked to save one.
+    pub(crate) fn handle_preset_save_request(&mut self) {
+        let Some(id) = self.plugin_manager.take_preset_save_request() else {
+            return;
+        };
+        let Some(slug) = self.plugin_manager.get_plugin(id).map(|p| p.descriptor.slug) else {
+            return;
+        };
+        let mut dialog = FileDialog::new()
+            .set_title("Save preset")
+            .set_file_name(format!("{}.{}", slug, PRESET_EXTENSION))
+            .add_filter("Module presets", &[PRESET_EXTENSION]);
+        if let Some(dir) = self.presets_directory(slug) {
+            std::fs::create_dir_all(&dir).ok();
+            dialog = dialog.set_directory(dir);
+        }
+        if let Some(path) = dialog.save_file() {
+            match self.save_preset(id, &path) {
+                Ok(()) => println!("Preset saved to {}", path.display()),
+                Err(err) => println!("Failed to save preset {}: {}", path.display(), err),
+            }
+        }
+    }
+
+    /// Saves the params of the plugin with `id` as a preset file.
+    pub fn save_preset(&mut self, id: ModuleId, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let preset = self.plugin_manager.preset_of(id).ok_or("No such module")?;
+        preset.save(path)?;
+        self.refresh_presets();
+        Ok(())
+    }
+}
@@ -0,0 +1,81 @@
+use crate::models::geometry::{GRID_UNIT, RAIL_COUNT, RAIL_HEIGHT, RAIL_TILES, RAIL_TILE_HP};
+use eframe::egui;
+use std::ops::Range;
+
+/// Size of one rail tile of `Rail.svg` at 100% zoom.
+pub const RAIL_TILE_SIZE: egui::Vec2 = egui::vec2(RAIL_TILE_HP as f32 * GRID_UNIT, RAIL_HEIGHT);
+/// Rail tiles per row.
+pub const RAIL_COLUMNS: usize = RAIL_TILES as usize;
+/// Rows of rails.
+pub const RAIL_ROWS: usize = RAIL_COUNT as usize;
+
+/// Grid of rail tiles covering the rack. Only the tiles inside the viewport
+/// are ever drawn or hit tested, so the cost of a frame depends on the window
+/// size and not on the size of the rack.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RailLayout {
+    pub origin: egui::Pos2,
+    pub tile_size: egui::Vec2,
+    pub columns: usize,
+    pub rows: usize,
+}
+
+impl Default for RailLayout {
+    fn default() -> Self {
+        Self {
+            origin: egui::Pos2::ZERO,
+            tile_size: RAIL_TILE_SIZE,
+            columns: RAIL_COLUMNS,
+            rows: RAIL_ROWS,
+        }
+    }
+}
+
+impl RailLayout {
+    /// Size of the whole rack.
+    pub fn size(&self) -> egui::Vec2 {
+        egui::vec2(self.tile_size.x * self.columns as f32, self.tile_size.y * self.rows as f32)
+    }
+
+    pub fn tile_rect(&self, column: usize, row: usize) -> egui::Rect {
+        let min = self.origin + egui::vec2(column as f32 * self.tile_size.x, row as f32 * self.tile_size.y);
+        egui::Rect::from_min_size(min, self.tile_size)
+    }
+
+    /// Columns and rows of the tiles that intersect `viewport`.
+    pub fn visible_tiles(&self, viewport: egui::Rect) -> (Range<usize>, Range<usize>) {
+        let span = |min: f32, max: f32, origin: f32, size: f32, count: usize| {
+            let first = ((min - origin) / size).floor().max(0.0) as usize;
+            let last = (((max - origin) / size).ceil().max(0.0) as usize).min(count);
+            first.min(last)..last
+        };
+        (
+            span(viewport.min.x, viewport.max.x, self.origin.x, self.tile_size.x, self.columns),
+            span(viewport.min.y, viewport.max.y, self.origin.y, self.tile_size.y, self.rows),
+        )
+    }
+
+    /// Column and row of the tile under `pos`.
+    pub fn tile_at(&self, pos: egui::Pos2) -> Option<(usize, usize)> {
+        let offset = pos - self.origin;
+        if offset.x < 0.0 || offset.y < 0.0 {
+            return None;
+        }
+        let column = (offset.x / self.tile_size.x) as usize;
+        let row = (offset.y / self.tile_size.y) as usize;
+        (column < self.columns && row < self.rows).then_some((column, row))
+    }
+
+    /// One textured mesh with a quad per visible tile.
+    pub fn mesh(&self, texture: egui::TextureId, viewport: egui::Rect) -> egui::Mesh {
+        let mut mesh = egui::Mesh::with_texture(texture);
+        let uv = egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0));
+        let (columns, rows) = self.visible_tiles(viewport);
+        for row in rows {
+            for column in columns.clone() {
+                mesh.add_rect_with_uv(self.tile_rect(column, row), uv, egui::Color32::WHITE);
+            }
+        }
+        mesh
+    }
+}
@@ -0,0 +1,62 @@
+use crate::app::VcvRackApp;
+use crate::models::geometry::{GridPos, RackGeometry};
+use crate::models::module_id::ModuleId;
+use crate::models::selection::{Selection, SELECTION_EXTENSION};
+use rfd::FileDialog;
+use std::path::Path;
+
+impl VcvRackApp {
+    /// Rack position selections are placed at: the grid cell last under
+    /// the pointer, or the top left of the rack.
+    pub(crate) fn placement_target(&self) -> GridPos {
+        self.last_rack_pointer.map_or_else(GridPos::default, RackGeometry::snap_to_grid)
+    }
+
+    /// Asks where to save the selected modules.
+    pub fn save_selection_as(&mut self) {
+        let mut dialog = FileDialog::new()
+            .set_file_name(format!("Selection.{}", SELECTION_EXTENSION))
+            .add_filter("Selections", &[SELECTION_EXTENSION]);
+        if let Some(dir) = self.dialog_directory() {
+            dialog = dialog.set_directory(dir);
+        }
+        if let Some(path) = dialog.save_file() {
+            match self.save_selection(&path) {
+                Ok(()) => println!("Selection saved to {}", path.display()),
+                Err(err) => println!("Failed to save selection {}: {}", path.display(), err),
+            }
+        }
+    }
+
+    /// Saves the selected modules and the cables between them to `path`.
+    pub fn save_selection(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let selection = self.plugin_manager.selection().ok_or("No modules are selected")?;
+        selection.save(path)
+    }
+
+    /// Asks for a selection file and places it on the rack.
+    pub fn import_selection(&mut self) {
+        let mut dialog = FileDialog::new().add_filter("Selections", &[SELECTION_EXTENSION]);
+        if let Some(dir) = self.dialog_directory() {
+            dialog = dialog.set_directory(dir);
+        }
+        if let Some(path) = dialog.pick_file() {
+            if let Err(err) = self.import_selection_file(&path, self.placement_target()) {
+                println!("Failed to import selection {}: {}", path.display(), err);
+            }
+        }
+    }
+
+    /// Places the selection saved at `path` as close to `at` as it fits.
+    /// Returns the ids of the new plugins, or an error when there is no
+    /// room for them on the rails.
+    pub fn import_selection_file(&mut self, path: &Path, at: GridPos) -> Result<Vec<ModuleId>, Box<dyn std::error::Error>> {
+        let selection = Selection::load(path)?;
+        let ids = self.plugin_manager.insert_selection(&selection, at);
+        if ids.is_empty() {
+            return Err("There is no room for the modules on the rails".into());
+        }
+        self.has_unsaved_changes = true;
+        Ok(ids)
+    }
+}
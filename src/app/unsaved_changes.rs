@@ -0,0 +1,130 @@
+use crate::app::VcvRackApp;
+use eframe::egui;
+use std::path::PathBuf;
+
+/// Something that replaces or closes the current rack, waiting for the user
+/// to decide what happens to unsaved changes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PendingAction {
+    Exit,
+    /// Pick a patch with the Open dialog.
+    Open,
+    OpenFile(PathBuf),
+    /// Pick a VCV Rack patch to import.
+    ImportVcv,
+}
+
+/// Answer to "Save changes?".
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SaveChoice {
+    Save,
+    DontSave,
+    Cancel,
+}
+
+impl VcvRackApp {
+    /// Runs `action` right away when there is nothing to lose, otherwise
+    /// asks whether to save first.
+    pub fn request_action(&mut self, action: PendingAction) {
+        if self.has_unsaved_changes {
+            self.pending_action = Some(action);
+        } else {
+            self.perform_action(action);
+        }
+    }
+
+    /// Action waiting for an answer; the dialog is shown while this is set.
+    pub fn pending_action(&self) -> Option<&PendingAction> {
+        self.pending_action.as_ref()
+    }
+
+    pub fn resolve_pending_action(&mut self, choice: SaveChoice) {
+        let Some(action) = self.pending_action.take() else {
+            return;
+        };
+        match choice {
+            SaveChoice::Save => {
+                self.save();
+                // Cancelling the Save As dialog or a failed save keeps the rack
+                if !self.has_unsaved_changes {
+                    self.perform_action(action);
+                }
+            }
+            SaveChoice::DontSave => self.perform_action(action),
+            SaveChoice::Cancel => {}
+        }
+    }
+
+    fn perform_action(&mut self, action: PendingAction) {
+        match action {
+            PendingAction::Exit => {
+                self.close_confirmed = true;
+                self.send_close = true;
+            }
+            PendingAction::Open => self.open(),
+            PendingAction::OpenFile(path) => {
+                self.open_file(&path);
+            }
+            PendingAction::ImportVcv => self.import_vcv(),
+        }
+    }
+
+    /// Decides what to do with a close request from the window. Returns true
+    /// when the close has to be cancelled to ask about unsaved changes first.
+    pub fn intercept_close_request(&mut self) -> bool {
+        if self.close_confirmed || !self.has_unsaved_changes {
+            return false;
+        }
+        self.pending_action = Some(PendingAction::Exit);
+        true
+    }
+
+    /// Handles the window close button and closes the window once exiting was confirmed.
+    pub(crate) fn handle_close_request(&mut self, ctx: &egui::Context) {
+        if ctx.input(|i| i.viewport().close_requested()) {
+            if self.intercept_close_request() {
+                ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+            } else {
+                // Closing normally, so nothing needs recovering next time
+                self.discard_autosave();
+            }
+        }
+        if std::mem::take(&mut self.send_close) {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+        }
+    }
+
+    pub(crate) fn save_changes_dialog(&mut self, ctx: &egui::Context) {
+        if self.pending_action.is_none() {
+            return;
+        }
+        let file_name = self.current_file.as_ref()
+            .and_then(|file| file.file_name())
+            .map_or_else(|| "this rack".to_string(), |name| format!("\"{}\"", name.to_string_lossy()));
+
+        let mut choice = None;
+        let modal = egui::Modal::new(egui::Id::new("save_changes")).show(ctx, |ui| {
+            ui.set_width(320.0);
+            ui.heading("Save changes?");
+            ui.label(format!("Do you want to save the changes to {}? Your changes will be lost if you don't save them.", file_name));
+            ui.add_space(8.0);
+            ui.horizontal(|ui| {
+                if ui.button("Save").clicked() {
+                    choice = Some(SaveChoice::Save);
+                }
+                if ui.button("Don't Save").clicked() {
+                    choice = Some(SaveChoice::DontSave);
+                }
+                if ui.button("Cancel").clicked() {
+                    choice = Some(SaveChoice::Cancel);
+                }
+            });
+        });
+        if modal.should_close() {
+            choice.get_or_insert(SaveChoice::Cancel);
+        }
+        if let Some(choice) = choice {
+            self.resolve_pending_action(choice);
+        }
+    }
+}
@@ -0,0 +1,31 @@
+use crate::app::VcvRackApp;
+use crate::vcv::export_file;
+use rfd::FileDialog;
+use std::path::Path;
+
+impl VcvRackApp {
+    /// Asks where to export the rack as a VCV Rack patch.
+    pub fn export_vcv(&mut self) {
+        let file_name = self.current_file.as_ref()
+            .and_then(|file| file.file_stem())
+            .map_or_else(|| "New rack.vcv".to_string(), |n| format!("{}.vcv", n.to_string_lossy()));
+        let mut dialog = FileDialog::new()
+            .set_file_name(file_name)
+            .add_filter("VCV Rack patches", &["vcv"]);
+        if let Some(dir) = self.dialog_directory() {
+            dialog = dialog.set_directory(dir);
+        }
+        if let Some(path) = dialog.save_file() {
+            match self.export_vcv_file(&path) {
+                Ok(()) => println!("Exported rack to {}", path.display()),
+                Err(err) => println!("Failed to export {}: {}", path.display(), err),
+            }
+        }
+    }
+
+    /// Writes the rack to `path` as a VCV Rack 2 patch. The open patch file
+    /// and the unsaved changes marker are left alone.
+    pub fn export_vcv_file(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        export_file(&self.plugin_manager.save_state(), path)
+    }
+}
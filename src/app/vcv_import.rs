@@ -0,0 +1,69 @@
+use crate::app::VcvRackApp;
+use crate::models::patch::PatchMetadata;
+use crate::vcv::{import_file, ImportReport};
+use eframe::egui;
+use rfd::FileDialog;
+use std::path::Path;
+
+impl VcvRackApp {
+    /// Asks for a VCV Rack patch and imports it.
+    pub fn import_vcv(&mut self) {
+        let mut dialog = FileDialog::new().add_filter("VCV Rack patches", &["vcv"]);
+        if let Some(dir) = self.dialog_directory() {
+            dialog = dialog.set_directory(dir);
+        }
+        if let Some(path) = dialog.pick_file() {
+            if let Err(err) = self.import_vcv_file(&path) {
+                println!("Failed to import {}: {}", path.display(), err);
+            }
+        }
+    }
+
+    /// Replaces the rack with the VCV Rack patch at `path`. The imported rack
+    /// has no file until it is saved, and anything that could not be mapped
+    /// is listed in a report window.
+    pub fn import_vcv_file(&mut self, path: &Path) -> Result<ImportReport, Box<dyn std::error::Error>> {
+        let (rack, report) = import_file(path)?;
+        self.plugin_manager.load_state(rack, self.blank_plate_plugin_texture.clone());
+        self.patch_metadata = PatchMetadata::default();
+        self.current_file = None;
+        self.has_unsaved_changes = true;
+        println!("Imported {}", path.display());
+        for line in report.lines() {
+            println!("{}", line);
+        }
+        self.import_report = (!report.is_empty()).then(|| report.clone());
+        Ok(report)
+    }
+
+    pub fn import_report(&self) -> Option<&ImportReport> {
+        self.import_report.as_ref()
+    }
+
+    /// Lists what the last import could not map.
+    pub(crate) fn import_report_window(&mut self, ctx: &egui::Context) {
+        let Some(report) = &self.import_report else {
+            return;
+        };
+        let mut open = true;
+        let mut close = false;
+        egui::Window::new("Import Report")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(true)
+            .show(ctx, |ui| {
+                ui.label("Some parts of the VCV Rack patch could not be imported:");
+                egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                    for line in report.lines() {
+                        ui.label(line);
+                    }
+                });
+                if ui.button("OK").clicked() {
+                    close = true;
+                }
+            });
+        if !open || close {
+            self.import_report = None;
+        }
+    }
+}
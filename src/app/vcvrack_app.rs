@@ -1,88 +1,134 @@
-use crate::models::plugin::{PluginManager, RackState};
+use crate::app::autosave::AUTOSAVE_INTERVAL;
+use crate::app::rail_view::{RailLayout, RAIL_TILE_SIZE};
+use crate::app::unsaved_changes::PendingAction;
+use crate::engine::{AudioEngine, DeviceBackend, EngineConfig, GraphSpec, NullBackend};
+use crate::models::browser::ModuleBrowser;
+use crate::models::geometry::{RackGeometry, GRID_ORIGIN};
+use crate::models::patch::{Patch, PatchMetadata};
+use crate::models::plugin::PluginManager;
+use crate::models::recent_files::RecentFiles;
+use crate::modules;
+use crate::vcv::ImportReport;
 use eframe::egui;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use directories::ProjectDirs;
 use std::fs;
-use serde_json;
-use rfd::FileDialog;
+use std::time::Instant;
 
 pub struct VcvRackApp {
     fullscreen: bool,
-    rack_texture: Option<egui::TextureHandle>,
-    blank_plate_plugin_texture: Option<egui::TextureHandle>,
-    zoom_level: f32,
+    pub(crate) rack_texture: Option<egui::TextureHandle>,
+    pub(crate) blank_plate_plugin_texture: Option<egui::TextureHandle>,
+    pub(crate) zoom_level: f32,
+    /// Scroll position of the rack view, in screen pixels.
+    pub(crate) scroll_offset: egui::Vec2,
+    /// Scroll position to apply on the next frame after zooming.
+    pub(crate) pending_scroll_offset: Option<egui::Vec2>,
+    /// Screen rect of the rack view in the last frame.
+    pub(crate) rack_viewport: egui::Rect,
+    /// Pixels per rack unit the panel textures were rendered at.
+    pub(crate) raster_scale: f32,
     pub plugin_manager: PluginManager,
     pub current_file: Option<PathBuf>,
+    /// Metadata of the open patch, written back when it is saved.
+    pub patch_metadata: PatchMetadata,
+    pub recent_files: RecentFiles,
     pub has_unsaved_changes: bool,
+    pub engine: AudioEngine,
+    synced_graph: GraphSpec,
+    pub module_browser: ModuleBrowser,
+    /// Where the module chosen in the open browser goes; `None` while it is closed.
+    pub(crate) browser_target: Option<egui::Pos2>,
+    pub(crate) focus_browser_search: bool,
+    /// Grid position under the pointer when it was last over the rack.
+    pub(crate) last_rack_pointer: Option<egui::Pos2>,
+    /// Waiting for the answer to "Save changes?".
+    pub(crate) pending_action: Option<PendingAction>,
+    /// The user agreed to exit; the window may close.
+    pub(crate) close_confirmed: bool,
+    /// Close the window on the next frame.
+    pub(crate) send_close: bool,
+    /// Folder of the autosave and the app settings; `None` keeps them off
+    /// disk.
+    pub(crate) settings_dir: Option<PathBuf>,
+    pub(crate) last_autosave: Instant,
+    /// Autosave found on startup that the user may restore.
+    pub(crate) autosave_offer: Option<PathBuf>,
+    /// What the last VCV Rack import could not map, shown until dismissed.
+    pub(crate) import_report: Option<ImportReport>,
+    /// Module ids the last opened patch file had to have repaired, shown
+    /// until dismissed.
+    pub(crate) load_report: Option<Vec<String>>,
 }
 
 #[allow(dead_code)]  // Temporarily allow dead code until we implement the UI
 impl VcvRackApp {
     pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
-        // Load Rail SVG
-        let rack_svg = std::fs::read_to_string("res/Rail.svg")
+        // Ctrl++ and Ctrl+- zoom the rack, not the whole user interface
+        cc.egui_ctx.options_mut(|options| options.zoom_with_keyboard = false);
+
+        let rack_texture = load_svg_texture(&cc.egui_ctx, "rack", "res/Rail.svg", 1.0)
             .expect("Failed to load Rail.svg");
 
-        // Parse Rail SVG
-        let opt = usvg::Options::default();
-        let tree = usvg::Tree::from_str(&rack_svg, &opt).unwrap();
-        let pixmap_size = tree.size();
-        let mut pixmap = tiny_skia::Pixmap::new(pixmap_size.width() as u32, pixmap_size.height() as u32)
-            .unwrap();
-        resvg::render(&tree, usvg::Transform::default(), &mut pixmap.as_mut());
-
-        let rack_image = egui::ColorImage::from_rgba_unmultiplied(
-            [pixmap_size.width() as _, pixmap_size.height() as _],
-            pixmap.data()
-        );
-        
-        let rack_texture = cc.egui_ctx.load_texture(
-            "rack",
-            rack_image,
-            egui::TextureOptions::default()
-        );
-
-        // Load BlankPlate SVG
-        let blank_plate_svg = std::fs::read_to_string("res/BlankPlatePlugin.svg")
-            .expect("Failed to load BlankPlatePlugin.svg");
-
-        // Parse BlankPlate SVG
-        let blank_plate_tree = usvg::Tree::from_str(&blank_plate_svg, &opt).unwrap();
-        let blank_plate_size = blank_plate_tree.size();
-        let mut blank_plate_pixmap = tiny_skia::Pixmap::new(
-            blank_plate_size.width() as u32, 
-            blank_plate_size.height() as u32
-        ).unwrap();
-        resvg::render(&blank_plate_tree, usvg::Transform::default(), &mut blank_plate_pixmap.as_mut());
-
-        let blank_plate_image = egui::ColorImage::from_rgba_unmultiplied(
-            [blank_plate_size.width() as _, blank_plate_size.height() as _],
-            blank_plate_pixmap.data()
-        );
-
-        let blank_plate_plugin_texture = cc.egui_ctx.load_texture(
-            "blank_plate_plugin",
-            blank_plate_image,
-            egui::TextureOptions::default()
-        );
+        // Render the panel of every module type once
+        let mut plugin_manager = PluginManager::new();
+        for descriptor in modules::MODULES {
+            match load_svg_texture(&cc.egui_ctx, descriptor.slug, descriptor.panel_svg, 1.0) {
+                Some(texture) => plugin_manager.set_panel_texture(descriptor.slug, texture),
+                None => println!("Failed to load panel {}", descriptor.panel_svg),
+            }
+        }
+        let blank_plate_plugin_texture = plugin_manager.panel_texture(modules::blank_plate().slug);
 
         let mut app = Self {
             fullscreen: false,
             rack_texture: Some(rack_texture),
-            blank_plate_plugin_texture: Some(blank_plate_plugin_texture),
+            blank_plate_plugin_texture,
             zoom_level: 1.0,
-            plugin_manager: PluginManager::new(),
+            scroll_offset: egui::Vec2::ZERO,
+            pending_scroll_offset: None,
+            rack_viewport: egui::Rect::ZERO,
+            raster_scale: 1.0,
+            plugin_manager,
             current_file: None,
+            patch_metadata: PatchMetadata::default(),
+            recent_files: RecentFiles::default(),
             has_unsaved_changes: false,
+            engine: AudioEngine::new(EngineConfig::default(), Box::new(DeviceBackend::new())),
+            synced_graph: GraphSpec::default(),
+            module_browser: ModuleBrowser::default(),
+            browser_target: None,
+            focus_browser_search: false,
+            last_rack_pointer: None,
+            pending_action: None,
+            close_confirmed: false,
+            send_close: false,
+            settings_dir: Self::get_save_directory(),
+            last_autosave: Instant::now(),
+            autosave_offer: None,
+            import_report: None,
+            load_report: None,
         };
 
-        // Try to load default.json on startup
+        app.recent_files = app.load_recent_files();
+        app.module_browser = app.load_module_browser();
+
+        // Try to load default.json on startup, after checking for work lost in a crash
+        app.autosave_offer = app.recoverable_autosave();
         if let Some(save_dir) = Self::get_save_directory() {
             let default_file = save_dir.join("default.json");
-            if default_file.exists() {
-                if let Ok(()) = app.load_rack_state("default") {
-                    app.current_file = Some(default_file);
-                }
+            if default_file.exists() && app.load_rack_state("default").is_ok() {
+                app.current_file = Some(default_file);
+            }
+        }
+
+        app.refresh_presets();
+        app.sync_engine();
+        if let Err(err) = app.engine.start() {
+            // Without a usable audio device the engine still runs, silently
+            println!("Failed to open the audio device: {}", err);
+            if let Err(err) = app.engine.set_backend(Box::new(NullBackend::new())).and_then(|_| app.engine.start()) {
+                println!("Failed to start audio engine: {}", err);
             }
         }
 
@@ -96,18 +142,36 @@ impl VcvRackApp {
             plugin_manager: PluginManager::new(),
             blank_plate_plugin_texture: None,
             zoom_level: 1.0,
+            scroll_offset: egui::Vec2::ZERO,
+            pending_scroll_offset: None,
+            rack_viewport: egui::Rect::ZERO,
+            raster_scale: 1.0,
             rack_texture: None,
             current_file: None,
+            patch_metadata: PatchMetadata::default(),
+            recent_files: RecentFiles::default(),
             has_unsaved_changes: false,
+            engine: AudioEngine::with_null_backend(),
+            synced_graph: GraphSpec::default(),
+            module_browser: ModuleBrowser::default(),
+            browser_target: None,
+            focus_browser_search: false,
+            last_rack_pointer: None,
+            pending_action: None,
+            close_confirmed: false,
+            send_close: false,
+            settings_dir: None,
+            last_autosave: Instant::now(),
+            autosave_offer: None,
+            import_report: None,
+            load_report: None,
         };
 
         // Try to load default.json on startup
         if let Some(save_dir) = Self::get_save_directory() {
             let default_file = save_dir.join("default.json");
-            if default_file.exists() {
-                if let Ok(()) = app.load_rack_state("default") {
-                    app.current_file = Some(default_file);
-                }
+            if default_file.exists() && app.load_rack_state("default").is_ok() {
+                app.current_file = Some(default_file);
             }
         }
 
@@ -119,68 +183,10 @@ impl VcvRackApp {
         ctx.send_viewport_cmd(egui::ViewportCommand::Fullscreen(self.fullscreen));
     }
 
-    pub fn reset_zoom(&mut self) {
-        self.zoom_level = 1.0;
-    }
-
-    const MIN_ZOOM: f32 = 0.4;
-    const MAX_ZOOM: f32 = 2.6;
-    const ZOOM_STEP: f32 = 0.2;
-
-    pub fn zoom_in(&mut self) {
-        let new_zoom = self.zoom_level + Self::ZOOM_STEP;
-        self.zoom_level = new_zoom.min(Self::MAX_ZOOM);
-    }
-
-    pub fn zoom_out(&mut self) {
-        let new_zoom = self.zoom_level - Self::ZOOM_STEP;
-        self.zoom_level = new_zoom.max(Self::MIN_ZOOM);
-    }
-
     fn update_menu(&mut self, ctx: &egui::Context, ui: &mut egui::Ui) {
         egui::menu::bar(ui, |ui| {
-            ui.menu_button("File", |ui| {
-                ui.set_min_width(200.0); // Match View menu width
-                let save_shortcut = egui::KeyboardShortcut::new(egui::Modifiers::CTRL, egui::Key::S);
-                if ui.add(egui::Button::new("Save").shortcut_text(ui.ctx().format_shortcut(&save_shortcut))).clicked() {
-                    if let Ok(()) = self.save_rack_state("default") {
-                        println!("Rack state saved successfully");
-                    }
-                    ui.close_menu();
-                }
-                let save_as_shortcut = egui::KeyboardShortcut::new(egui::Modifiers::CTRL | egui::Modifiers::SHIFT, egui::Key::S);
-                if ui.add(egui::Button::new("Save As...").shortcut_text(ui.ctx().format_shortcut(&save_as_shortcut))).clicked() {
-                    if let Some(save_dir) = Self::get_save_directory() {
-                        if let Some(file_path) = FileDialog::new()
-                            .set_directory(&save_dir)
-                            .set_file_name("New rack.json")
-                            .add_filter("JSON files", &["json"])
-                            .save_file() 
-                        {
-                            let file_name = file_path.file_stem()
-                                .and_then(|s| s.to_str())
-                                .unwrap_or("default");
-                            
-                            if let Ok(()) = self.save_rack_state(file_name) {
-                                println!("Rack state saved successfully as {}", file_name);
-                                self.current_file = Some(file_path);
-                            }
-                        }
-                    }
-                    ui.close_menu();
-                }
-                let open_shortcut = egui::KeyboardShortcut::new(egui::Modifiers::CTRL, egui::Key::O);
-                if ui.add(egui::Button::new("Open").shortcut_text(ui.ctx().format_shortcut(&open_shortcut))).clicked() {
-                    if let Ok(()) = self.load_rack_state("default") {
-                        println!("Rack state loaded successfully");
-                    }
-                    ui.close_menu();
-                }
-                ui.separator();
-                if ui.button("Exit").clicked() {
-                    ctx.send_viewport_cmd(egui::ViewportCommand::Close);
-                }
-            });
+            self.file_menu(ui);
+            self.edit_menu(ui);
             
             ui.menu_button("View", |ui| {
                 ui.set_min_width(200.0); // Match File menu width
@@ -245,94 +251,107 @@ impl VcvRackApp {
                     self.toggle_fullscreen(ctx);
                 }
             });
+
+            self.engine_menu(ui);
+            self.engine_status(ui);
         });
     }
 
+    /// Deletes the selected modules on Delete, unless a text field such as
+    /// the module browser search has the keyboard.
+    pub(crate) fn handle_delete_key(&mut self, ctx: &egui::Context) {
+        if ctx.wants_keyboard_input() || self.plugin_manager.get_selected_plugins().is_empty() {
+            return;
+        }
+        if ctx.input(|i| i.key_pressed(egui::Key::Delete)) {
+            self.plugin_manager.delete_selected_plugins();
+            self.has_unsaved_changes = true;
+        }
+    }
+
     pub fn draw_rack(&mut self, ui: &mut egui::Ui) {
-        let rail_width = 304.0;
-        let rail_height = 380.0;
-        let total_height = rail_height * 24.0;
-
-        if let Some(texture) = &self.rack_texture {
-            egui::ScrollArea::both()
-                .scroll_bar_visibility(egui::scroll_area::ScrollBarVisibility::AlwaysVisible)
-                .vertical_scroll_offset(0.0)
-                .show(ui, |ui| {
+        if let Some(texture) = self.rack_texture.clone() {
+            let mut scroll_area = egui::ScrollArea::both()
+                .scroll_bar_visibility(egui::scroll_area::ScrollBarVisibility::AlwaysVisible);
+            if let Some(offset) = self.pending_scroll_offset.take() {
+                scroll_area = scroll_area.scroll_offset(offset);
+            }
+            let output = scroll_area.show(ui, |ui| {
                     ui.visuals_mut().widgets.inactive.bg_fill = egui::Color32::from_rgba_premultiplied(100, 100, 100, 180);
                     ui.visuals_mut().widgets.active.bg_fill = egui::Color32::from_rgba_premultiplied(120, 120, 120, 180);
                     ui.visuals_mut().widgets.hovered.bg_fill = egui::Color32::from_rgba_premultiplied(140, 140, 140, 180);
                     
-                    ui.set_min_size(egui::vec2(rail_width * 200.0, total_height));
+                    // Everything on the rack is placed relative to the scrolled content
+                    let geometry = RackGeometry::new(ui.max_rect().min, self.zoom_level);
+                    let layout = RailLayout {
+                        origin: geometry.to_screen(GRID_ORIGIN),
+                        tile_size: RAIL_TILE_SIZE * self.zoom_level,
+                        ..RailLayout::default()
+                    };
+                    ui.set_min_size(GRID_ORIGIN.to_vec2() * self.zoom_level + layout.size());
 
                     let mut click_consumed = false;
 
-                    // Handle delete key press
-                    if ui.input(|i| i.key_pressed(egui::Key::Delete)) {
-                        if !self.plugin_manager.get_selected_plugins().is_empty() {
-                            self.plugin_manager.delete_selected_plugins();
-                            self.has_unsaved_changes = true;
-                        }
-                    }
-
-                    // First render all rails
-                    for row in 0..24 {
-                        for col in 0..200 {
-                            let image = egui::widgets::Image::new(texture)
-                                .fit_to_exact_size(egui::vec2(rail_width, rail_height));
-                             
-
-                            let pos = egui::pos2(col as f32 * rail_width, row as f32 * rail_height);
-                            let rail_rect = egui::Rect::from_min_size(pos, egui::vec2(rail_width, rail_height));
-
-                            // Render the rail
-                            ui.put(rail_rect, image.clone());
-
-                            // Handle plugin placement on click
-                            if ui.rect_contains_pointer(rail_rect) {
-                                if ui.input(|i| i.pointer.button_clicked(egui::PointerButton::Primary)) {
-                                    if let Some(pointer_pos) = ui.input(|i| i.pointer.interact_pos()) {
-                                        println!("Adding plugin at position: {:?}", pointer_pos);
-                                        
-                                        // Calculate the grid position based on the actual click position
-                                        let grid_x = (pointer_pos.x / (30.4 * self.zoom_level)).floor() * (30.4 * self.zoom_level);
-                                        let plugin_pos = egui::pos2(grid_x, pos.y);
-                                        
-                                        let ctrl_pressed = ui.input(|i| i.modifiers.ctrl);
-                                        
-                                        // Check if there's already a plugin at this position
-                                        if let Some(clicked_plugin) = self.plugin_manager.get_plugin_at_position(plugin_pos, self.zoom_level) {
-                                            // Store the selection state before modifying plugin manager
-                                            let was_selected = clicked_plugin.is_selected();
-                                            
-                                            // If there's already a plugin, handle selection/deselection
-                                            if !ctrl_pressed {
-                                                // Normal click: deselect all others and toggle this one
-                                                self.plugin_manager.deselect_all();
-                                                if !was_selected {
-                                                    self.plugin_manager.select_plugin(plugin_pos, self.zoom_level);
-                                                }
-                                            } else {
-                                                // Ctrl+click: toggle this plugin's selection without affecting others
-                                                self.plugin_manager.select_plugin(plugin_pos, self.zoom_level);
-                                            }
-                                        } else if let Some(texture) = &self.blank_plate_plugin_texture {
-                                            // If position is free, add a new plugin
-                                            self.plugin_manager.deselect_all();
-                                            self.plugin_manager.add_plugin(plugin_pos, Some(texture.clone()));
-                                            self.has_unsaved_changes = true;
-                                        }
-                                        click_consumed = true;
+                    self.handle_delete_key(ui.ctx());
+
+                    // First render the rails inside the viewport
+                    let viewport = ui.clip_rect();
+                    ui.painter().add(layout.mesh(texture.id(), viewport));
+
+                    // Map the pointer to the grid once instead of hit testing every tile
+                    let rail_pointer = ui.input(|i| i.pointer.interact_pos())
+                        .filter(|_| ui.rect_contains_pointer(viewport))
+                        .filter(|pointer_pos| layout.tile_at(*pointer_pos).is_some());
+                    if let Some(pointer_pos) = rail_pointer {
+                        // New modules go into the HP cell under the pointer
+                        let rack_pos = geometry.to_rack(pointer_pos);
+                        let plugin_pos = RackGeometry::grid_to_rack(geometry.screen_to_grid(pointer_pos));
+                        self.last_rack_pointer = Some(plugin_pos);
+
+                        // Handle selection on click and the module browser on double-click
+                        let (clicked, double_clicked) = ui.input(|i| (
+                            i.pointer.button_clicked(egui::PointerButton::Primary),
+                            i.pointer.button_double_clicked(egui::PointerButton::Primary),
+                        ));
+                        if clicked || double_clicked {
+                            let ctrl_pressed = ui.input(|i| i.modifiers.ctrl);
+                            
+                            // Check if there's already a plugin at this position
+                            if let Some(clicked_plugin) = self.plugin_manager.get_plugin_at_position(rack_pos, self.zoom_level) {
+                                // Store the selection state before modifying plugin manager
+                                let was_selected = clicked_plugin.is_selected();
+                                
+                                // If there's already a plugin, handle selection/deselection
+                                if !ctrl_pressed {
+                                    // Normal click: deselect all others and toggle this one
+                                    self.plugin_manager.deselect_all();
+                                    if !was_selected {
+                                        self.plugin_manager.select_plugin(rack_pos, self.zoom_level);
                                     }
+                                } else {
+                                    // Ctrl+click: toggle this plugin's selection without affecting others
+                                    self.plugin_manager.select_plugin(rack_pos, self.zoom_level);
+                                }
+                            } else {
+                                // Empty rail: deselect, and pick a module to put here on double-click
+                                self.plugin_manager.deselect_all();
+                                if double_clicked {
+                                    self.open_module_browser(plugin_pos);
                                 }
                             }
+                            click_consumed = true;
                         }
                     }
 
                     // Always draw plugins, but pass click_consumed to control click handling
-                    if self.blank_plate_plugin_texture.is_some() {
-                        self.plugin_manager.draw_plugins(ui, self.zoom_level, click_consumed);
+                    if self.blank_plate_plugin_texture.is_some()
+                        && self.plugin_manager.draw_plugins(ui, &geometry, click_consumed)
+                    {
+                        self.has_unsaved_changes = true;
                     }
                 });
+            self.scroll_offset = output.state.offset;
+            self.rack_viewport = output.inner_rect;
         }
     }
 
@@ -352,6 +371,15 @@ impl VcvRackApp {
         self.plugin_manager.get_plugins()
     }
 
+    /// Pushes the current rack to the audio engine if it changed since the last sync.
+    pub fn sync_engine(&mut self) {
+        let spec = self.plugin_manager.graph_spec();
+        if spec != self.synced_graph {
+            self.engine.update_graph(&spec);
+            self.synced_graph = spec;
+        }
+    }
+
     pub fn get_zoom_level(&self) -> f32 {
         self.zoom_level
     }
@@ -382,42 +410,57 @@ impl VcvRackApp {
         }
     }
 
-    pub fn save_rack_state(&mut self, name: &str) -> Result<(), Box<dyn std::error::Error>> {
-        let save_dir = Self::get_save_directory().ok_or("Could not get save directory")?;
-        let file_path = save_dir.join(format!("{}.json", name));
-        
-        let state = self.plugin_manager.save_state();
-        let json = serde_json::to_string_pretty(&state)?;
-        fs::write(&file_path, json)?;
-        
-        self.current_file = Some(file_path);
+    /// Saves the rack to the patch file at `path`, which becomes the current file.
+    pub fn save_patch(&mut self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let metadata = self.patch_metadata.touched();
+        Patch::new(self.plugin_manager.save_state(), metadata.clone()).save(path)?;
+
+        self.patch_metadata = metadata;
+        self.current_file = Some(path.to_path_buf());
+        self.plugin_manager.mark_saved();
         self.has_unsaved_changes = false;
+        self.add_recent_file(path);
+        self.discard_autosave();
         Ok(())
     }
 
-    pub fn load_rack_state(&mut self, name: &str) -> Result<(), Box<dyn std::error::Error>> {
-        let save_dir = Self::get_save_directory().ok_or("Could not get save directory")?;
-        let file_path = save_dir.join(format!("{}.json", name));
-        
-        let json = fs::read_to_string(&file_path)?;
-        let state: RackState = serde_json::from_str(&json)?;
-        
-        self.plugin_manager.load_state(state, self.blank_plate_plugin_texture.clone());
-        self.current_file = Some(file_path);
+    /// Loads the patch file at `path`, upgrading older patch formats.
+    /// Returns the migrations that were applied.
+    pub fn load_patch(&mut self, path: &Path) -> Result<Vec<&'static str>, Box<dyn std::error::Error>> {
+        let loaded = Patch::load(path)?;
+        for migration in &loaded.migrations {
+            println!("Upgraded {}: {}", path.display(), migration);
+        }
+
+        let repairs = self.plugin_manager.load_state(loaded.patch.rack, self.blank_plate_plugin_texture.clone());
+        self.load_report = (!repairs.is_empty()).then(|| repairs.iter().map(|repair| repair.to_string()).collect());
+        self.patch_metadata = loaded.patch.metadata;
+        self.current_file = Some(path.to_path_buf());
         self.has_unsaved_changes = false;
-        Ok(())
+        self.add_recent_file(path);
+        Ok(loaded.migrations)
+    }
+
+    /// Saves the rack as `name`.json in the save directory.
+    pub fn save_rack_state(&mut self, name: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let save_dir = Self::get_save_directory().ok_or("Could not get save directory")?;
+        self.save_patch(&save_dir.join(format!("{}.json", name)))
+    }
+
+    /// Loads `name`.json from the save directory.
+    pub fn load_rack_state(&mut self, name: &str) -> Result<Vec<&'static str>, Box<dyn std::error::Error>> {
+        let save_dir = Self::get_save_directory().ok_or("Could not get save directory")?;
+        self.load_patch(&save_dir.join(format!("{}.json", name)))
     }
 
     pub fn list_saved_states() -> Result<Vec<String>, Box<dyn std::error::Error>> {
         let save_dir = Self::get_save_directory().ok_or("Could not get save directory")?;
         let mut states = Vec::new();
         
-        for entry in fs::read_dir(save_dir)? {
-            if let Ok(entry) = entry {
-                if let Some(name) = entry.path().file_stem() {
-                    if let Some(name) = name.to_str() {
-                        states.push(name.to_string());
-                    }
+        for entry in fs::read_dir(save_dir)?.flatten() {
+            if let Some(name) = entry.path().file_stem() {
+                if let Some(name) = name.to_str() {
+                    states.push(name.to_string());
                 }
             }
         }
@@ -426,6 +469,21 @@ impl VcvRackApp {
     }
 }
 
+/// Rasterises an SVG file into a texture at `scale` times its natural size.
+pub(crate) fn load_svg_texture(ctx: &egui::Context, name: &str, path: &str, scale: f32) -> Option<egui::TextureHandle> {
+    let svg = std::fs::read_to_string(path).ok()?;
+    let tree = usvg::Tree::from_str(&svg, &usvg::Options::default()).ok()?;
+    let size = tree.size();
+    let mut pixmap = tiny_skia::Pixmap::new((size.width() * scale).ceil() as u32, (size.height() * scale).ceil() as u32)?;
+    resvg::render(&tree, usvg::Transform::from_scale(scale, scale), &mut pixmap.as_mut());
+
+    let image = egui::ColorImage::from_rgba_unmultiplied(
+        [pixmap.width() as _, pixmap.height() as _],
+        pixmap.data()
+    );
+    Some(ctx.load_texture(name, image, egui::TextureOptions::default()))
+}
+
 impl eframe::App for VcvRackApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         ctx.set_visuals(egui::Visuals::dark());
@@ -461,39 +519,41 @@ impl eframe::App for VcvRackApp {
             self.toggle_fullscreen(ctx);
         }
 
-        // Handle Ctrl+S for saving
-        if ctx.input(|i| i.modifiers.ctrl && !i.modifiers.shift && i.key_pressed(egui::Key::S)) {
-            if let Ok(()) = self.save_rack_state("default") {
-                println!("Rack state saved successfully");
-            }
+        self.handle_history_shortcuts(ctx);
+        self.handle_clipboard_shortcuts(ctx);
+        self.handle_zoom_input(ctx);
+        self.update_panel_resolution(ctx);
+
+        // Enter opens the module browser at the last rail position under the pointer
+        if !self.is_module_browser_open()
+            && !ctx.wants_keyboard_input()
+            && ctx.input(|i| i.key_pressed(egui::Key::Enter))
+        {
+            self.open_module_browser(self.last_rack_pointer.unwrap_or(GRID_ORIGIN));
         }
-
-        // Handle Ctrl+Shift+S for save as
-        if ctx.input(|i| i.modifiers.ctrl && i.modifiers.shift && i.key_pressed(egui::Key::S)) {
-            if let Some(save_dir) = Self::get_save_directory() {
-                if let Some(file_path) = FileDialog::new()
-                    .set_directory(&save_dir)
-                    .set_file_name("New rack.json")
-                    .add_filter("JSON files", &["json"])
-                    .save_file() 
-                {
-                    let file_name = file_path.file_stem()
-                        .and_then(|s| s.to_str())
-                        .unwrap_or("default");
-                    
-                    if let Ok(()) = self.save_rack_state(file_name) {
-                        println!("Rack state saved successfully as {}", file_name);
-                        self.current_file = Some(file_path);
-                    }
-                }
-            }
+        self.module_browser_window(ctx);
+
+        self.handle_preset_save_request();
+        self.handle_file_shortcuts(ctx);
+        self.save_changes_dialog(ctx);
+        self.autosave_restore_dialog(ctx);
+        self.import_report_window(ctx);
+        self.load_report_window(ctx);
+        self.handle_close_request(ctx);
+
+        self.tick_autosave(Instant::now());
+        if self.has_unsaved_changes {
+            // Wake up for the next autosave even when nothing happens
+            ctx.request_repaint_after(AUTOSAVE_INTERVAL);
         }
 
-        // Handle Ctrl+O for opening
-        if ctx.input(|i| i.modifiers.ctrl && i.key_pressed(egui::Key::O)) {
-            if let Ok(()) = self.load_rack_state("default") {
-                println!("Rack state loaded successfully");
-            }
+        self.sync_engine();
+        if self.engine.is_running() {
+            let lights = self.engine.module_lights();
+            self.plugin_manager.set_lights(&lights);
+            // Keep the CPU meter in the menu bar moving, and lights at a smooth frame rate
+            let interval = if lights.is_empty() { 250 } else { 33 };
+            ctx.request_repaint_after(std::time::Duration::from_millis(interval));
         }
     }
 }
\ No newline at end of file
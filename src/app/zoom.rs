@@ -0,0 +1,85 @@
+use crate::app::vcvrack_app::load_svg_texture;
+use crate::app::VcvRackApp;
+use crate::models::geometry::anchored_scroll_offset;
+use crate::modules;
+use eframe::egui;
+
+impl VcvRackApp {
+    pub const MIN_ZOOM: f32 = 0.4;
+    pub const MAX_ZOOM: f32 = 2.6;
+    const ZOOM_STEP: f32 = 0.2;
+
+    pub fn reset_zoom(&mut self) {
+        self.set_zoom(1.0, None);
+    }
+
+    pub fn zoom_in(&mut self) {
+        self.set_zoom(self.zoom_level + Self::ZOOM_STEP, None);
+    }
+
+    pub fn zoom_out(&mut self) {
+        self.set_zoom(self.zoom_level - Self::ZOOM_STEP, None);
+    }
+
+    /// Changes the zoom while keeping the rack point under `anchor` (relative
+    /// to the top left of the rack view) in place. Without an anchor the
+    /// centre of the view stays in place.
+    pub fn set_zoom(&mut self, zoom: f32, anchor: Option<egui::Vec2>) {
+        let zoom = zoom.clamp(Self::MIN_ZOOM, Self::MAX_ZOOM);
+        if zoom == self.zoom_level {
+            return;
+        }
+        let anchor = anchor.unwrap_or(self.rack_viewport.size() / 2.0);
+        self.scroll_offset = anchored_scroll_offset(self.scroll_offset, anchor, self.zoom_level, zoom);
+        self.pending_scroll_offset = Some(self.scroll_offset);
+        self.zoom_level = zoom;
+    }
+
+    pub fn scroll_offset(&self) -> egui::Vec2 {
+        self.scroll_offset
+    }
+
+    /// Handles Ctrl+mouse wheel and pinch zoom around the pointer, and Ctrl++ / Ctrl+-.
+    pub(crate) fn handle_zoom_input(&mut self, ctx: &egui::Context) {
+        let (zoom_delta, hover_pos, zoom_in, zoom_out) = ctx.input(|i| (
+            i.zoom_delta(),
+            i.pointer.hover_pos(),
+            i.modifiers.ctrl && (i.key_pressed(egui::Key::Plus) || i.key_pressed(egui::Key::Equals)),
+            i.modifiers.ctrl && i.key_pressed(egui::Key::Minus),
+        ));
+        let anchor = hover_pos
+            .filter(|pos| self.rack_viewport.contains(*pos))
+            .map(|pos| pos - self.rack_viewport.min);
+
+        if zoom_delta != 1.0 && anchor.is_some() {
+            self.set_zoom(self.zoom_level * zoom_delta, anchor);
+        }
+        if zoom_in {
+            self.set_zoom(self.zoom_level + Self::ZOOM_STEP, anchor);
+        }
+        if zoom_out {
+            self.set_zoom(self.zoom_level - Self::ZOOM_STEP, anchor);
+        }
+    }
+
+    /// Renders the rail and module panels again at the resolution they are
+    /// shown at, once the zoom has settled, so they stay crisp.
+    pub(crate) fn update_panel_resolution(&mut self, ctx: &egui::Context) {
+        let scale = self.zoom_level * ctx.pixels_per_point();
+        if (scale - self.raster_scale).abs() < 0.01 || ctx.input(|i| i.zoom_delta() != 1.0) {
+            return;
+        }
+        self.raster_scale = scale;
+
+        if let Some(texture) = load_svg_texture(ctx, "rack", "res/Rail.svg", scale) {
+            self.rack_texture = Some(texture);
+        }
+        for descriptor in modules::MODULES {
+            if let Some(texture) = load_svg_texture(ctx, descriptor.slug, descriptor.panel_svg, scale) {
+                self.plugin_manager.set_panel_texture(descriptor.slug, texture);
+            }
+        }
+        self.plugin_manager.refresh_plugin_textures();
+        self.blank_plate_plugin_texture = self.plugin_manager.panel_texture(modules::blank_plate().slug);
+    }
+}
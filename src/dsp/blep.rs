@@ -0,0 +1,45 @@
+/// Band-limits a naively generated waveform with PolyBLEP corrections.
+///
+/// Discontinuities are reported with their position inside the current
+/// sample. The correction reaches one sample back, so the output is delayed
+/// by one sample.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PolyBlep {
+    previous: f32,
+    /// Correction of the sample that is output next.
+    current: f32,
+    /// Correction of the sample being generated.
+    next: f32,
+}
+
+impl PolyBlep {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a step of `jump` that happened `offset` samples (0..1) before the
+    /// sample being generated.
+    pub fn insert_step(&mut self, offset: f32, jump: f32) {
+        let offset = offset.clamp(0.0, 1.0);
+        self.current += jump * offset * offset / 2.0;
+        self.next -= jump * (1.0 - offset) * (1.0 - offset) / 2.0;
+    }
+
+    /// Adds a change of slope by `change` per sample that happened `offset`
+    /// samples (0..1) before the sample being generated.
+    pub fn insert_kink(&mut self, offset: f32, change: f32) {
+        let offset = offset.clamp(0.0, 1.0);
+        self.current += change * offset * offset * offset / 6.0;
+        self.next += change * (1.0 - offset) * (1.0 - offset) * (1.0 - offset) / 6.0;
+    }
+
+    /// Takes the naive value of the new sample and returns the corrected
+    /// value of the previous one.
+    pub fn process(&mut self, naive: f32) -> f32 {
+        let out = self.previous + self.current;
+        self.previous = naive;
+        self.current = self.next;
+        self.next = 0.0;
+        out
+    }
+}
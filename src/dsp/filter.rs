@@ -0,0 +1,98 @@
+use std::f32::consts::PI;
+
+/// Outputs of a multimode filter for one sample.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FilterOutputs {
+    pub lowpass: f32,
+    pub highpass: f32,
+    pub bandpass: f32,
+    pub notch: f32,
+}
+
+/// Integrator gain of a zero-delay feedback filter with cutoff `freq`,
+/// prewarped so the cutoff lands where it should. The cutoff is kept below
+/// Nyquist so fast modulation can't make the filter blow up.
+fn prewarp(freq: f32, sample_time: f32) -> f32 {
+    let ratio = (freq * sample_time).clamp(1e-5, 0.45);
+    (PI * ratio).tan()
+}
+
+/// 4-pole transistor ladder lowpass with saturation in the feedback loop,
+/// solved without a unit delay so it tracks audio-rate cutoff modulation.
+/// The other responses are mixes of the stages.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LadderFilter {
+    stages: [f32; 4],
+}
+
+impl LadderFilter {
+    /// Feedback amount at which the ladder starts to self-oscillate.
+    pub const SELF_OSCILLATION: f32 = 4.0;
+
+    /// Filters `input` (around ±1) with cutoff `freq` in Hz and feedback
+    /// `resonance` from 0 upwards.
+    pub fn process(&mut self, input: f32, freq: f32, resonance: f32, sample_time: f32) -> FilterOutputs {
+        let g = prewarp(freq, sample_time);
+        let gain = g / (1.0 + g);
+
+        // Output of the last stage is gain^4 * u plus what the stage states add
+        let mut state_sum = 0.0;
+        for state in self.stages {
+            state_sum = state_sum * gain + state / (1.0 + g);
+        }
+        let feedback = (input - resonance * state_sum) / (1.0 + resonance * gain.powi(4));
+        let u = feedback.tanh();
+
+        let mut stage_outputs = [0.0; 4];
+        let mut x = u;
+        for (state, output) in self.stages.iter_mut().zip(&mut stage_outputs) {
+            let v = (x - *state) * gain;
+            let y = v + *state;
+            *state = y + v;
+            *output = y;
+            x = y;
+        }
+
+        let [y1, y2, y3, y4] = stage_outputs;
+        let bandpass = 4.0 * (y2 - 2.0 * y3 + y4);
+        FilterOutputs {
+            // Feedback takes the passband down by 1 + resonance, make up for it
+            lowpass: y4 * (1.0 + resonance),
+            highpass: u - 4.0 * y1 + 6.0 * y2 - 4.0 * y3 + y4,
+            bandpass,
+            notch: u - bandpass,
+        }
+    }
+}
+
+/// 2-pole state-variable filter (topology-preserving transform), which
+/// stays stable however fast its cutoff and damping change.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SvfFilter {
+    band: f32,
+    low: f32,
+}
+
+impl SvfFilter {
+    /// Filters `input` with cutoff `freq` in Hz and quality factor `q` (0.5 and up).
+    pub fn process(&mut self, input: f32, freq: f32, q: f32, sample_time: f32) -> FilterOutputs {
+        let g = prewarp(freq, sample_time);
+        let damping = 1.0 / (2.0 * q.max(0.5));
+
+        let highpass = (input - (2.0 * damping + g) * self.band - self.low) / (1.0 + 2.0 * damping * g + g * g);
+        let v1 = g * highpass;
+        let bandpass = v1 + self.band;
+        self.band = bandpass + v1;
+        let v2 = g * bandpass;
+        let lowpass = v2 + self.low;
+        self.low = lowpass + v2;
+
+        FilterOutputs {
+            lowpass,
+            highpass,
+            // Unity gain at the cutoff
+            bandpass: 2.0 * damping * bandpass,
+            notch: input - 2.0 * damping * bandpass,
+        }
+    }
+}
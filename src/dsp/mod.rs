@@ -0,0 +1,16 @@
+pub mod blep;
+pub mod filter;
+pub mod trigger;
+
+pub use blep::PolyBlep;
+pub use filter::{FilterOutputs, LadderFilter, SvfFilter};
+pub use trigger::{PulseGenerator, SchmittTrigger};
+
+/// Frequency of C4, the pitch of 0V on a 1V/octave input.
+pub const FREQ_C4: f32 = 261.625_58;
+
+/// Frequency of a 1V/octave pitch voltage.
+pub fn pitch_to_freq(pitch: f32) -> f32 {
+    FREQ_C4 * pitch.exp2()
+}
+
@@ -0,0 +1,54 @@
+/// Detects rising and falling edges of a gate or trigger signal, with
+/// hysteresis so a noisy signal near the threshold doesn't fire twice.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SchmittTrigger {
+    high: bool,
+}
+
+impl SchmittTrigger {
+    /// A high signal goes low at or below this voltage.
+    pub const LOW_THRESHOLD: f32 = 0.1;
+    /// A low signal goes high at or above this voltage.
+    pub const HIGH_THRESHOLD: f32 = 1.0;
+
+    /// Returns true when `voltage` goes high.
+    pub fn process(&mut self, voltage: f32) -> bool {
+        if self.high {
+            if voltage <= Self::LOW_THRESHOLD {
+                self.high = false;
+            }
+            false
+        } else if voltage >= Self::HIGH_THRESHOLD {
+            self.high = true;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn is_high(&self) -> bool {
+        self.high
+    }
+}
+
+/// Holds a trigger output high for a fixed time.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PulseGenerator {
+    remaining: f32,
+}
+
+impl PulseGenerator {
+    /// Length of the trigger pulses modules send, in seconds.
+    pub const TRIGGER_DURATION: f32 = 1e-3;
+
+    pub fn trigger(&mut self, duration: f32) {
+        self.remaining = self.remaining.max(duration);
+    }
+
+    /// Returns whether the pulse is high during this sample, then moves on by `sample_time`.
+    pub fn process(&mut self, sample_time: f32) -> bool {
+        let high = self.remaining > 0.0;
+        self.remaining = (self.remaining - sample_time).max(0.0);
+        high
+    }
+}
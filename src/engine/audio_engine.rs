@@ -0,0 +1,267 @@
+use super::backend::{AudioBlock, NullBackend, OutputBackend};
+use super::graph::{GraphSpec, ProcessGraph};
+use crate::models::module_id::ModuleId;
+use std::io;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+pub const OUTPUT_CHANNELS: usize = 2;
+pub const SAMPLE_RATES: [u32; 4] = [44100, 48000, 88200, 96000];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EngineConfig {
+    pub sample_rate: u32,
+    pub block_size: usize,
+}
+
+impl Default for EngineConfig {
+    fn default() -> Self {
+        Self {
+            sample_rate: 48000,
+            block_size: 256,
+        }
+    }
+}
+
+/// Snapshot of the engine shown in the menu bar.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EngineState {
+    pub running: bool,
+    pub sample_rate: u32,
+    /// Fraction of the block duration spent processing, 0.0 to 1.0+.
+    pub cpu_load: f32,
+    pub backend: String,
+}
+
+struct Shared {
+    running: AtomicBool,
+    graph: Mutex<ProcessGraph>,
+    cpu_load_bits: AtomicU32,
+    frames_processed: AtomicU64,
+}
+
+impl Shared {
+    fn graph(&self) -> MutexGuard<'_, ProcessGraph> {
+        // A panic on the engine thread must not take the UI down with it
+        self.graph.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn cpu_load(&self) -> f32 {
+        f32::from_bits(self.cpu_load_bits.load(Ordering::Relaxed))
+    }
+
+    fn set_cpu_load(&self, load: f32) {
+        self.cpu_load_bits.store(load.to_bits(), Ordering::Relaxed);
+    }
+}
+
+/// Runs the processing graph block by block on a dedicated thread.
+pub struct AudioEngine {
+    config: EngineConfig,
+    shared: Arc<Shared>,
+    backend: Option<Box<dyn OutputBackend>>,
+    backend_name: String,
+    thread: Option<JoinHandle<Box<dyn OutputBackend>>>,
+}
+
+impl AudioEngine {
+    pub fn new(config: EngineConfig, backend: Box<dyn OutputBackend>) -> Self {
+        Self {
+            config,
+            shared: Arc::new(Shared {
+                running: AtomicBool::new(false),
+                graph: Mutex::new(ProcessGraph::new()),
+                cpu_load_bits: AtomicU32::new(0.0f32.to_bits()),
+                frames_processed: AtomicU64::new(0),
+            }),
+            backend_name: backend.name().to_string(),
+            backend: Some(backend),
+            thread: None,
+        }
+    }
+
+    pub fn with_null_backend() -> Self {
+        Self::new(EngineConfig::default(), Box::new(NullBackend::new()))
+    }
+
+    pub fn start(&mut self) -> io::Result<()> {
+        if self.thread.is_some() {
+            if self.shared.running.load(Ordering::Acquire) {
+                return Ok(());
+            }
+            // The thread gave up after a backend error; take the backend
+            // back before opening it again
+            self.stop();
+        }
+
+        let mut backend = self.backend.take()
+            .ok_or_else(|| io::Error::other("No output backend"))?;
+        if let Err(err) = backend.open(self.config.sample_rate, OUTPUT_CHANNELS) {
+            self.backend = Some(backend);
+            return Err(err);
+        }
+
+        self.shared.running.store(true, Ordering::Release);
+        let shared = self.shared.clone();
+        let config = self.config;
+        let spawned = std::thread::Builder::new()
+            .name("audio-engine".to_string())
+            .spawn(move || run_engine(shared, backend, config));
+
+        match spawned {
+            Ok(handle) => {
+                self.thread = Some(handle);
+                Ok(())
+            }
+            Err(err) => {
+                self.shared.running.store(false, Ordering::Release);
+                Err(err)
+            }
+        }
+    }
+
+    pub fn stop(&mut self) {
+        self.shared.running.store(false, Ordering::Release);
+        if let Some(handle) = self.thread.take() {
+            match handle.join() {
+                Ok(mut backend) => {
+                    if let Err(err) = backend.close() {
+                        println!("Failed to close {} backend: {}", backend.name(), err);
+                    }
+                    self.backend = Some(backend);
+                }
+                Err(_) => println!("Audio engine thread panicked"),
+            }
+        }
+        self.shared.set_cpu_load(0.0);
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.thread.is_some() && self.shared.running.load(Ordering::Acquire)
+    }
+
+    /// Replaces the output backend, restarting the engine if it was running.
+    pub fn set_backend(&mut self, backend: Box<dyn OutputBackend>) -> io::Result<()> {
+        let was_running = self.thread.is_some();
+        self.stop();
+        self.backend_name = backend.name().to_string();
+        self.backend = Some(backend);
+        if was_running {
+            self.start()?;
+        }
+        Ok(())
+    }
+
+    /// Changes the sample rate, restarting the engine if it was running.
+    pub fn set_sample_rate(&mut self, sample_rate: u32) -> io::Result<()> {
+        if sample_rate == self.config.sample_rate {
+            return Ok(());
+        }
+        let was_running = self.thread.is_some();
+        self.stop();
+        self.config.sample_rate = sample_rate;
+        if was_running {
+            self.start()?;
+        }
+        Ok(())
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.config.sample_rate
+    }
+
+    pub fn config(&self) -> EngineConfig {
+        self.config
+    }
+
+    pub fn update_graph(&self, spec: &GraphSpec) {
+        self.shared.graph().sync(spec);
+    }
+
+    pub fn graph_node_count(&self) -> usize {
+        self.shared.graph().node_count()
+    }
+
+    /// Current light brightness of the modules, for drawing them.
+    pub fn module_lights(&self) -> Vec<(ModuleId, Vec<f32>)> {
+        self.shared.graph().lights()
+    }
+
+    pub fn frames_processed(&self) -> u64 {
+        self.shared.frames_processed.load(Ordering::Relaxed)
+    }
+
+    pub fn state(&self) -> EngineState {
+        EngineState {
+            running: self.is_running(),
+            sample_rate: self.config.sample_rate,
+            cpu_load: self.shared.cpu_load(),
+            backend: self.backend_name.clone(),
+        }
+    }
+
+    /// Renders `frames` frames synchronously on the calling thread.
+    ///
+    /// Used for offline rendering and tests; the engine must be stopped.
+    pub fn render_offline(&mut self, frames: usize) -> AudioBlock {
+        let mut output = AudioBlock::new(frames, OUTPUT_CHANNELS);
+        let mut block = AudioBlock::new(self.config.block_size, OUTPUT_CHANNELS);
+        let mut graph = self.shared.graph();
+        let mut done = 0;
+        while done < frames {
+            let count = self.config.block_size.min(frames - done);
+            if block.frames() != count {
+                block = AudioBlock::new(count, OUTPUT_CHANNELS);
+            }
+            graph.process_block(&mut block, self.config.sample_rate);
+            let start = done * OUTPUT_CHANNELS;
+            output.samples[start..start + block.samples.len()].copy_from_slice(&block.samples);
+            done += count;
+        }
+        self.shared.frames_processed.fetch_add(frames as u64, Ordering::Relaxed);
+        output
+    }
+}
+
+impl Drop for AudioEngine {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn run_engine(shared: Arc<Shared>, mut backend: Box<dyn OutputBackend>, config: EngineConfig) -> Box<dyn OutputBackend> {
+    let mut block = AudioBlock::new(config.block_size, OUTPUT_CHANNELS);
+    let block_duration = Duration::from_secs_f64(config.block_size as f64 / config.sample_rate as f64);
+    let mut deadline = Instant::now();
+    let mut smoothed_load = 0.0f32;
+
+    while shared.running.load(Ordering::Acquire) {
+        let started = Instant::now();
+        shared.graph().process_block(&mut block, config.sample_rate);
+        let load = started.elapsed().as_secs_f32() / block_duration.as_secs_f32();
+        smoothed_load += (load - smoothed_load) * 0.1;
+        shared.set_cpu_load(smoothed_load);
+
+        if let Err(err) = backend.write(&block) {
+            println!("Audio backend {} failed: {}", backend.name(), err);
+            break;
+        }
+        shared.frames_processed.fetch_add(config.block_size as u64, Ordering::Relaxed);
+
+        if !backend.is_blocking() {
+            deadline += block_duration;
+            let now = Instant::now();
+            if deadline > now {
+                std::thread::sleep(deadline - now);
+            } else {
+                // Fell behind; don't try to catch up with a burst of blocks
+                deadline = now;
+            }
+        }
+    }
+
+    shared.running.store(false, Ordering::Release);
+    backend
+}
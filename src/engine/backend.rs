@@ -0,0 +1,175 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Interleaved block of samples handed from the engine to an output backend.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioBlock {
+    pub channels: usize,
+    pub samples: Vec<f32>,
+}
+
+impl AudioBlock {
+    pub fn new(frames: usize, channels: usize) -> Self {
+        Self {
+            channels,
+            samples: vec![0.0; frames * channels],
+        }
+    }
+
+    pub fn frames(&self) -> usize {
+        self.samples.len().checked_div(self.channels).unwrap_or(0)
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.fill(0.0);
+    }
+
+    pub fn frame_mut(&mut self, frame: usize) -> &mut [f32] {
+        let start = frame * self.channels;
+        &mut self.samples[start..start + self.channels]
+    }
+}
+
+/// Destination for the audio produced by the engine.
+///
+/// Backends are swappable while the engine is stopped. `write` is called from
+/// the engine thread once per block.
+pub trait OutputBackend: Send {
+    fn name(&self) -> &str;
+
+    fn open(&mut self, sample_rate: u32, channels: usize) -> io::Result<()>;
+
+    fn write(&mut self, block: &AudioBlock) -> io::Result<()>;
+
+    fn close(&mut self) -> io::Result<()>;
+
+    /// Returns true when `write` blocks until the device has consumed the
+    /// block. Non-blocking backends are paced by the engine clock instead.
+    fn is_blocking(&self) -> bool {
+        false
+    }
+}
+
+/// Discards all audio. Used on headless machines and in tests.
+#[derive(Debug, Default)]
+pub struct NullBackend {
+    frames_written: Arc<AtomicU64>,
+}
+
+impl NullBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Shared counter that keeps working after the backend moved to the engine thread.
+    pub fn frames_written_counter(&self) -> Arc<AtomicU64> {
+        self.frames_written.clone()
+    }
+}
+
+impl OutputBackend for NullBackend {
+    fn name(&self) -> &str {
+        "Null"
+    }
+
+    fn open(&mut self, _sample_rate: u32, _channels: usize) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn write(&mut self, block: &AudioBlock) -> io::Result<()> {
+        self.frames_written.fetch_add(block.frames() as u64, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn close(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Records the engine output to a 32-bit float WAV file.
+pub struct WavFileBackend {
+    path: PathBuf,
+    writer: Option<BufWriter<File>>,
+    data_bytes: u32,
+}
+
+impl WavFileBackend {
+    const HEADER_SIZE: u32 = 44;
+    /// Most sample data a WAV file can hold: the RIFF chunk size, which
+    /// counts the header after its first 8 bytes, has to fit in 32 bits.
+    const MAX_DATA_BYTES: u32 = u32::MAX - (Self::HEADER_SIZE - 8);
+
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            writer: None,
+            data_bytes: 0,
+        }
+    }
+
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    fn write_header(writer: &mut impl Write, sample_rate: u32, channels: u16, data_bytes: u32) -> io::Result<()> {
+        let block_align = channels * 4;
+        writer.write_all(b"RIFF")?;
+        writer.write_all(&(36 + data_bytes).to_le_bytes())?;
+        writer.write_all(b"WAVE")?;
+        writer.write_all(b"fmt ")?;
+        writer.write_all(&16u32.to_le_bytes())?;
+        writer.write_all(&3u16.to_le_bytes())?; // IEEE float
+        writer.write_all(&channels.to_le_bytes())?;
+        writer.write_all(&sample_rate.to_le_bytes())?;
+        writer.write_all(&(sample_rate * block_align as u32).to_le_bytes())?;
+        writer.write_all(&block_align.to_le_bytes())?;
+        writer.write_all(&32u16.to_le_bytes())?;
+        writer.write_all(b"data")?;
+        writer.write_all(&data_bytes.to_le_bytes())?;
+        Ok(())
+    }
+}
+
+impl OutputBackend for WavFileBackend {
+    fn name(&self) -> &str {
+        "WAV file"
+    }
+
+    fn open(&mut self, sample_rate: u32, channels: usize) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(&self.path)?);
+        Self::write_header(&mut writer, sample_rate, channels as u16, 0)?;
+        self.writer = Some(writer);
+        self.data_bytes = 0;
+        Ok(())
+    }
+
+    fn write(&mut self, block: &AudioBlock) -> io::Result<()> {
+        let writer = self.writer.as_mut()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotConnected, "WAV backend is not open"))?;
+        // Stops before the block so the file keeps a valid header
+        let data_bytes = u32::try_from(block.samples.len() * 4).ok()
+            .and_then(|bytes| self.data_bytes.checked_add(bytes))
+            .filter(|&total| total <= Self::MAX_DATA_BYTES)
+            .ok_or_else(|| io::Error::other("WAV file reached its 4 GB size limit"))?;
+        for sample in &block.samples {
+            writer.write_all(&sample.to_le_bytes())?;
+        }
+        self.data_bytes = data_bytes;
+        Ok(())
+    }
+
+    fn close(&mut self) -> io::Result<()> {
+        if let Some(mut writer) = self.writer.take() {
+            // Patch the RIFF and data chunk sizes now that the length is known
+            writer.seek(SeekFrom::Start(4))?;
+            writer.write_all(&(Self::HEADER_SIZE - 8 + self.data_bytes).to_le_bytes())?;
+            writer.seek(SeekFrom::Start(40))?;
+            writer.write_all(&self.data_bytes.to_le_bytes())?;
+            writer.flush()?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,211 @@
+use super::backend::{AudioBlock, OutputBackend};
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{FromSample, SampleFormat, SizedSample};
+use std::collections::VecDeque;
+use std::io;
+use std::sync::mpsc;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// Seconds of audio queued ahead of the device. The engine blocks once the
+/// queue is full, so this is also the added latency.
+const LATENCY: f32 = 0.05;
+/// How long `write` waits for the device before giving up on it.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Interleaved samples on their way from the engine thread to the device
+/// callback.
+pub struct SampleQueue {
+    samples: Mutex<VecDeque<f32>>,
+    space: Condvar,
+    capacity: usize,
+}
+
+impl SampleQueue {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            samples: Mutex::new(VecDeque::with_capacity(capacity)),
+            space: Condvar::new(),
+            capacity,
+        }
+    }
+
+    fn samples(&self) -> MutexGuard<'_, VecDeque<f32>> {
+        self.samples.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Appends `samples` once there is room for them, waiting at most
+    /// `timeout`. More samples than the queue holds go in once it is empty.
+    /// Returns false when it timed out.
+    pub fn push(&self, samples: &[f32], timeout: Duration) -> bool {
+        let full = |queue: &mut VecDeque<f32>| !queue.is_empty() && queue.len() + samples.len() > self.capacity;
+        let (mut queue, result) = self.space.wait_timeout_while(self.samples(), timeout, full)
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if result.timed_out() {
+            return false;
+        }
+        queue.extend(samples);
+        true
+    }
+
+    /// Fills `out` from the front of the queue, with silence where the queue
+    /// ran dry. Returns how many samples came from the queue.
+    pub fn pop_into(&self, out: &mut [f32]) -> usize {
+        let mut queue = self.samples();
+        let count = out.len().min(queue.len());
+        for (sample, queued) in out.iter_mut().zip(queue.drain(..count)) {
+            *sample = queued;
+        }
+        out[count..].fill(0.0);
+        drop(queue);
+        self.space.notify_one();
+        count
+    }
+}
+
+/// The stream lives on its own thread because cpal streams can't move
+/// between threads on every platform, while backends move to the engine
+/// thread.
+struct StreamThread {
+    stop: mpsc::Sender<()>,
+    handle: JoinHandle<()>,
+}
+
+/// Plays the engine output on the system's default audio device.
+#[derive(Default)]
+pub struct DeviceBackend {
+    queue: Option<Arc<SampleQueue>>,
+    stream: Option<StreamThread>,
+}
+
+impl DeviceBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl OutputBackend for DeviceBackend {
+    fn name(&self) -> &str {
+        "Audio device"
+    }
+
+    fn open(&mut self, sample_rate: u32, channels: usize) -> io::Result<()> {
+        self.close()?;
+        let queue = Arc::new(SampleQueue::new((sample_rate as f32 * LATENCY) as usize * channels));
+        let (ready_sender, ready) = mpsc::channel();
+        let (stop, stopped) = mpsc::channel();
+        let stream_queue = queue.clone();
+        let handle = std::thread::Builder::new()
+            .name("audio-device".to_string())
+            .spawn(move || match open_stream(stream_queue, sample_rate, channels) {
+                Ok(stream) => {
+                    ready_sender.send(Ok(())).ok();
+                    // Runs until close() sends or drops the sender
+                    stopped.recv().ok();
+                    drop(stream);
+                }
+                Err(err) => {
+                    ready_sender.send(Err(err)).ok();
+                }
+            })?;
+
+        match ready.recv() {
+            Ok(Ok(())) => {
+                self.queue = Some(queue);
+                self.stream = Some(StreamThread { stop, handle });
+                Ok(())
+            }
+            Ok(Err(err)) => {
+                handle.join().ok();
+                Err(err)
+            }
+            Err(_) => Err(io::Error::other("Audio device thread stopped")),
+        }
+    }
+
+    fn write(&mut self, block: &AudioBlock) -> io::Result<()> {
+        let queue = self.queue.as_ref()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotConnected, "Audio device is not open"))?;
+        if queue.push(&block.samples, WRITE_TIMEOUT) {
+            Ok(())
+        } else {
+            Err(io::Error::new(io::ErrorKind::TimedOut, "Audio device stopped taking samples"))
+        }
+    }
+
+    fn close(&mut self) -> io::Result<()> {
+        self.queue = None;
+        if let Some(stream) = self.stream.take() {
+            stream.stop.send(()).ok();
+            stream.handle.join().map_err(|_| io::Error::other("Audio device thread panicked"))?;
+        }
+        Ok(())
+    }
+
+    fn is_blocking(&self) -> bool {
+        true
+    }
+}
+
+impl Drop for DeviceBackend {
+    fn drop(&mut self) {
+        self.close().ok();
+    }
+}
+
+fn open_stream(queue: Arc<SampleQueue>, sample_rate: u32, channels: usize) -> io::Result<cpal::Stream> {
+    let device = cpal::default_host().default_output_device()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "No audio output device"))?;
+    let supported = device.default_output_config().map_err(io::Error::other)?;
+    let config = cpal::StreamConfig {
+        channels: supported.channels(),
+        sample_rate: cpal::SampleRate(sample_rate),
+        buffer_size: cpal::BufferSize::Default,
+    };
+    let stream = match supported.sample_format() {
+        SampleFormat::F32 => build_stream::<f32>(&device, &config, queue, channels),
+        SampleFormat::I16 => build_stream::<i16>(&device, &config, queue, channels),
+        SampleFormat::U16 => build_stream::<u16>(&device, &config, queue, channels),
+        format => return Err(io::Error::new(io::ErrorKind::Unsupported, format!("Unsupported sample format {}", format))),
+    }
+    .map_err(io::Error::other)?;
+    stream.play().map_err(io::Error::other)?;
+    Ok(stream)
+}
+
+/// Output stream that plays our channels on the first channels of the
+/// device and leaves any others silent.
+fn build_stream<T>(device: &cpal::Device, config: &cpal::StreamConfig, queue: Arc<SampleQueue>, channels: usize) -> Result<cpal::Stream, cpal::BuildStreamError>
+where
+    T: SizedSample + FromSample<f32>,
+{
+    let device_channels = config.channels as usize;
+    let mut frames = Vec::new();
+    device.build_output_stream(
+        config,
+        move |data: &mut [T], _: &cpal::OutputCallbackInfo| {
+            frames.resize(data.len() / device_channels * channels, 0.0);
+            queue.pop_into(&mut frames);
+            for (out, frame) in data.chunks_mut(device_channels).zip(frames.chunks(channels)) {
+                for (channel, sample) in out.iter_mut().enumerate() {
+                    *sample = T::from_sample(frame.get(channel).copied().unwrap_or(0.0));
+                }
+            }
+        },
+        |err| println!("Audio device error: {}", err),
+        None,
+    )
+}
@@ -0,0 +1,156 @@
+use super::backend::AudioBlock;
+use crate::models::cable::PortRef;
+use crate::models::module::{Module, Port, ProcessContext};
+use crate::models::module_id::ModuleId;
+use crate::modules;
+
+/// Description of one module instance in the rack, as seen by the engine.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModuleSpec {
+    pub id: ModuleId,
+    pub slug: &'static str,
+    pub params: Vec<f32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CableSpec {
+    pub output: PortRef,
+    pub input: PortRef,
+}
+
+/// Snapshot of the rack that the engine builds its processing graph from.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GraphSpec {
+    pub modules: Vec<ModuleSpec>,
+    pub cables: Vec<CableSpec>,
+}
+
+struct Node {
+    id: ModuleId,
+    module: Box<dyn Module>,
+}
+
+/// Cable resolved to node indices so the audio loop avoids id lookups.
+#[derive(Debug, Clone, Copy)]
+struct Connection {
+    from_node: usize,
+    from_port: usize,
+    to_node: usize,
+    to_port: usize,
+}
+
+/// Processing graph owned by the engine thread.
+#[derive(Default)]
+pub struct ProcessGraph {
+    nodes: Vec<Node>,
+    connections: Vec<Connection>,
+    frame: u64,
+}
+
+impl ProcessGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rebuilds the node list from `spec`, keeping the DSP state of modules
+    /// that still exist and creating instances for new ones.
+    pub fn sync(&mut self, spec: &GraphSpec) {
+        let mut old_nodes = std::mem::take(&mut self.nodes);
+        self.nodes = spec.modules.iter()
+            .filter_map(|module_spec| {
+                let existing = old_nodes.iter().position(|node| {
+                    node.id == module_spec.id && node.module.descriptor().slug == module_spec.slug
+                });
+                let mut node = match existing {
+                    Some(index) => old_nodes.swap_remove(index),
+                    None => Node {
+                        id: module_spec.id,
+                        module: modules::create(module_spec.slug)?,
+                    },
+                };
+                for (index, value) in module_spec.params.iter().enumerate() {
+                    node.module.set_param(index, *value);
+                }
+                Some(node)
+            })
+            .collect();
+
+        self.connections = spec.cables.iter()
+            .filter_map(|cable| {
+                let from_node = self.nodes.iter().position(|n| n.id == cable.output.module_id)?;
+                let to_node = self.nodes.iter().position(|n| n.id == cable.input.module_id)?;
+                let valid = cable.output.port < self.nodes[from_node].module.outputs().len()
+                    && cable.input.port < self.nodes[to_node].module.inputs().len();
+                valid.then_some(Connection {
+                    from_node,
+                    from_port: cable.output.port,
+                    to_node,
+                    to_port: cable.input.port,
+                })
+            })
+            .collect();
+
+        // Unplugged inputs read 0V and report no channels
+        for node in &mut self.nodes {
+            for input in &mut node.module.io_mut().inputs {
+                *input = Port::default();
+            }
+        }
+        self.propagate_cables();
+    }
+
+    /// Copies every output into the inputs it is patched to. Cables add one
+    /// sample of latency, like in VCV Rack.
+    fn propagate_cables(&mut self) {
+        for connection in &self.connections {
+            let voltage = self.nodes[connection.from_node].module.outputs()[connection.from_port];
+            self.nodes[connection.to_node].module.io_mut().inputs[connection.to_port] = voltage;
+        }
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn node_ids(&self) -> Vec<ModuleId> {
+        self.nodes.iter().map(|node| node.id).collect()
+    }
+
+    pub fn module(&self, id: ModuleId) -> Option<&dyn Module> {
+        self.nodes.iter()
+            .find(|node| node.id == id)
+            .map(|node| node.module.as_ref())
+    }
+
+    /// Light brightness of every module that has lights.
+    pub fn lights(&self) -> Vec<(ModuleId, Vec<f32>)> {
+        self.nodes.iter()
+            .filter(|node| !node.module.lights().is_empty())
+            .map(|node| (node.id, node.module.lights().to_vec()))
+            .collect()
+    }
+
+    /// Number of frames processed since the graph was created.
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    pub fn process_block(&mut self, block: &mut AudioBlock, sample_rate: u32) {
+        block.clear();
+        for frame in 0..block.frames() {
+            let ctx = ProcessContext::new(sample_rate as f32, self.frame);
+            let output = block.frame_mut(frame);
+            for node in &mut self.nodes {
+                node.module.process(&ctx);
+                if let Some([left, right]) = node.module.audio_output() {
+                    output[0] += left;
+                    if output.len() > 1 {
+                        output[1] += right;
+                    }
+                }
+            }
+            self.propagate_cables();
+            self.frame += 1;
+        }
+    }
+}
@@ -0,0 +1,9 @@
+pub mod audio_engine;
+pub mod backend;
+pub mod device_backend;
+pub mod graph;
+
+pub use audio_engine::{AudioEngine, EngineConfig, EngineState};
+pub use backend::{AudioBlock, NullBackend, OutputBackend, WavFileBackend};
+pub use device_backend::{DeviceBackend, SampleQueue};
+pub use graph::{CableSpec, GraphSpec, ModuleSpec, ProcessGraph};
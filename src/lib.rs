@@ -1,7 +1,12 @@
 pub mod app;
+pub mod dsp;
+pub mod engine;
 pub mod models;
+pub mod modules;
+pub mod vcv;
 
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 pub mod tests {
     pub mod gui_tests;
     pub mod window_title_tests;
@@ -9,4 +14,32 @@ pub mod tests {
     pub mod vcvrack_app_tests;
     pub mod startup_tests;
     pub mod change_indicator_tests;
+    pub mod engine_tests;
+    pub mod module_tests;
+    pub mod cable_tests;
+    pub mod module_width_tests;
+    pub mod module_drag_tests;
+    pub mod history_tests;
+    pub mod module_browser_tests;
+    pub mod rail_view_tests;
+    pub mod zoom_tests;
+    pub mod geometry_tests;
+    pub mod patch_tests;
+    pub mod file_menu_tests;
+    pub mod unsaved_changes_tests;
+    pub mod autosave_tests;
+    pub mod vcv_import_tests;
+    pub mod vcv_export_tests;
+    pub mod preset_tests;
+    pub mod selection_tests;
+    pub mod clipboard_tests;
+    pub mod module_id_tests;
+    pub mod module_support;
+    pub mod vco_tests;
+    pub mod vcf_tests;
+    pub mod adsr_tests;
+    pub mod vca_tests;
+    pub mod mixer_tests;
+    pub mod lfo_tests;
+    pub mod audio_tests;
 }
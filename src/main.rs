@@ -1,7 +1,4 @@
-mod app;
-mod models;
-
-use app::VcvRackApp;
+use vcvrack_rs::app::VcvRackApp;
 
 fn main() -> eframe::Result<()> {
     let native_options = eframe::NativeOptions {
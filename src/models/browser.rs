@@ -0,0 +1,146 @@
+use crate::models::module::ModuleDescriptor;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// Number of recently used modules the browser remembers.
+pub const MAX_RECENT: usize = 8;
+
+/// Favourites and recently used modules, kept between sessions.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BrowserPrefs {
+    #[serde(default)]
+    pub favorites: Vec<String>,
+    /// Most recently used first.
+    #[serde(default)]
+    pub recent: Vec<String>,
+}
+
+impl BrowserPrefs {
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let json = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&json)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// Search and filter state of the module browser.
+#[derive(Debug, Clone, Default)]
+pub struct ModuleBrowser {
+    pub query: String,
+    pub tag: Option<&'static str>,
+    pub brand: Option<&'static str>,
+    pub favorites_only: bool,
+    pub prefs: BrowserPrefs,
+}
+
+impl ModuleBrowser {
+    pub fn new(prefs: BrowserPrefs) -> Self {
+        Self { prefs, ..Self::default() }
+    }
+
+    /// Clears the search so the browser opens on the full list.
+    pub fn reset_search(&mut self) {
+        self.query.clear();
+        self.tag = None;
+        self.brand = None;
+        self.favorites_only = false;
+    }
+
+    pub fn is_favorite(&self, slug: &str) -> bool {
+        self.prefs.favorites.iter().any(|s| s == slug)
+    }
+
+    pub fn toggle_favorite(&mut self, slug: &str) {
+        if self.is_favorite(slug) {
+            self.prefs.favorites.retain(|s| s != slug);
+        } else {
+            self.prefs.favorites.push(slug.to_string());
+        }
+    }
+
+    /// Moves `slug` to the front of the recently used list.
+    pub fn record_used(&mut self, slug: &str) {
+        self.prefs.recent.retain(|s| s != slug);
+        self.prefs.recent.insert(0, slug.to_string());
+        self.prefs.recent.truncate(MAX_RECENT);
+    }
+
+    fn recent_rank(&self, slug: &str) -> usize {
+        self.prefs.recent.iter().position(|s| s == slug).unwrap_or(MAX_RECENT)
+    }
+
+    /// Modules matching the query and filters. Without a query, recently used
+    /// modules come first and the rest are sorted by name; with a query the
+    /// best fuzzy matches come first.
+    pub fn search(&self, modules: &[&'static ModuleDescriptor]) -> Vec<&'static ModuleDescriptor> {
+        let query = self.query.trim();
+        let mut results: Vec<(i32, &'static ModuleDescriptor)> = modules.iter()
+            .copied()
+            .filter(|m| self.tag.is_none_or(|tag| m.tags.contains(&tag)))
+            .filter(|m| self.brand.is_none_or(|brand| m.brand == brand))
+            .filter(|m| !self.favorites_only || self.is_favorite(m.slug))
+            .filter_map(|m| {
+                if query.is_empty() {
+                    return Some((0, m));
+                }
+                [m.name, m.slug, m.brand].iter()
+                    .chain(m.tags.iter())
+                    .filter_map(|text| fuzzy_score(query, text))
+                    .max()
+                    .map(|score| (score, m))
+            })
+            .collect();
+
+        results.sort_by(|(score_a, a), (score_b, b)| {
+            score_b.cmp(score_a)
+                .then_with(|| self.recent_rank(a.slug).cmp(&self.recent_rank(b.slug)))
+                .then_with(|| a.name.cmp(b.name))
+        });
+        results.into_iter().map(|(_, m)| m).collect()
+    }
+}
+
+/// All tags used by `modules`, sorted and without duplicates.
+pub fn all_tags(modules: &[&'static ModuleDescriptor]) -> Vec<&'static str> {
+    let mut tags: Vec<&'static str> = modules.iter().flat_map(|m| m.tags.iter().copied()).collect();
+    tags.sort_unstable();
+    tags.dedup();
+    tags
+}
+
+/// All brands of `modules`, sorted and without duplicates.
+pub fn all_brands(modules: &[&'static ModuleDescriptor]) -> Vec<&'static str> {
+    let mut brands: Vec<&'static str> = modules.iter().map(|m| m.brand).collect();
+    brands.sort_unstable();
+    brands.dedup();
+    brands
+}
+
+/// Scores how well `query` matches `text` when its characters appear in order,
+/// ignoring case. Consecutive characters and matches at the start of a word
+/// score higher. Returns `None` when `text` does not contain the query.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let text: Vec<char> = text.chars().flat_map(char::to_lowercase).collect();
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous_match: Option<usize> = None;
+
+    for query_char in query.chars().flat_map(char::to_lowercase).filter(|c| !c.is_whitespace()) {
+        let index = position + text[position..].iter().position(|c| *c == query_char)?;
+        score += 1;
+        if previous_match.is_some_and(|previous| previous + 1 == index) {
+            score += 5;
+        }
+        if index == 0 || !text[index - 1].is_alphanumeric() {
+            score += 10;
+        }
+        previous_match = Some(index);
+        position = index + 1;
+    }
+    Some(score)
+}
@@ -0,0 +1,142 @@
+use crate::models::module_id::ModuleId;
+use eframe::egui;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Colours handed out to new cables in turn, like VCV Rack's default palette.
+pub const CABLE_COLORS: [CableColor; 5] = [
+    CableColor([0xf3, 0x37, 0x4b]),
+    CableColor([0xff, 0xb4, 0x37]),
+    CableColor([0x00, 0xb5, 0x6e]),
+    CableColor([0x36, 0x95, 0xef]),
+    CableColor([0x8b, 0x4a, 0xde]),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PortKind {
+    Input,
+    Output,
+}
+
+/// A port on a specific plugin in the rack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct PortRef {
+    pub module_id: ModuleId,
+    pub port: usize,
+}
+
+impl PortRef {
+    pub fn new(module_id: ModuleId, port: usize) -> Self {
+        Self { module_id, port }
+    }
+}
+
+/// Cable colour, stored as "#rrggbb" in patch files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CableColor(pub [u8; 3]);
+
+impl CableColor {
+    pub fn to_hex(self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.0[0], self.0[1], self.0[2])
+    }
+
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        let hex = hex.strip_prefix('#')?;
+        if hex.len() != 6 {
+            return None;
+        }
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+        Some(Self([channel(0)?, channel(2)?, channel(4)?]))
+    }
+
+    pub fn to_color32(self) -> egui::Color32 {
+        egui::Color32::from_rgb(self.0[0], self.0[1], self.0[2])
+    }
+}
+
+impl Serialize for CableColor {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_hex())
+    }
+}
+
+impl<'de> Deserialize<'de> for CableColor {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let hex = String::deserialize(deserializer)?;
+        Self::from_hex(&hex).ok_or_else(|| serde::de::Error::custom(format!("invalid cable colour {}", hex)))
+    }
+}
+
+/// Connection from an output port to an input port.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Cable {
+    pub id: usize,
+    pub output: PortRef,
+    pub input: PortRef,
+    pub color: CableColor,
+}
+
+impl Cable {
+    pub fn end(&self, kind: PortKind) -> PortRef {
+        match kind {
+            PortKind::Input => self.input,
+            PortKind::Output => self.output,
+        }
+    }
+}
+
+/// Control points of a cable hanging between two points, sagging under its own weight.
+pub fn cable_bezier(start: egui::Pos2, end: egui::Pos2, scale: f32) -> [egui::Pos2; 4] {
+    let sag = (start.distance(end) * 0.3 + 20.0 * scale).min(300.0 * scale);
+    let control1 = start + (end - start) / 3.0 + egui::vec2(0.0, sag);
+    let control2 = start + (end - start) * (2.0 / 3.0) + egui::vec2(0.0, sag);
+    [start, control1, control2, end]
+}
+
+pub fn draw_cable(painter: &egui::Painter, start: egui::Pos2, end: egui::Pos2, color: CableColor, scale: f32) {
+    let points = cable_bezier(start, end, scale);
+    let color = color.to_color32();
+    let shape = egui::epaint::CubicBezierShape::from_points_stroke(
+        points,
+        false,
+        egui::Color32::TRANSPARENT,
+        egui::Stroke::new(4.0 * scale, color.gamma_multiply(0.85)),
+    );
+    painter.add(shape);
+    // Plugs at both ends
+    for point in [start, end] {
+        painter.circle_filled(point, 5.0 * scale, color);
+    }
+}
+
+/// Shortest distance from `pos` to the cable curve, sampled along its length.
+pub fn distance_to_cable(pos: egui::Pos2, start: egui::Pos2, end: egui::Pos2, scale: f32) -> f32 {
+    const SEGMENTS: usize = 32;
+    let [p0, p1, p2, p3] = cable_bezier(start, end, scale);
+    let point_at = |t: f32| {
+        let u = 1.0 - t;
+        let v = p0.to_vec2() * (u * u * u)
+            + p1.to_vec2() * (3.0 * u * u * t)
+            + p2.to_vec2() * (3.0 * u * t * t)
+            + p3.to_vec2() * (t * t * t);
+        v.to_pos2()
+    };
+
+    let mut closest = f32::MAX;
+    let mut previous = p0;
+    for i in 1..=SEGMENTS {
+        let current = point_at(i as f32 / SEGMENTS as f32);
+        closest = closest.min(distance_to_segment(pos, previous, current));
+        previous = current;
+    }
+    closest
+}
+
+fn distance_to_segment(pos: egui::Pos2, a: egui::Pos2, b: egui::Pos2) -> f32 {
+    let ab = b - a;
+    let length_sq = ab.length_sq();
+    if length_sq == 0.0 {
+        return pos.distance(a);
+    }
+    let t = ((pos - a).dot(ab) / length_sq).clamp(0.0, 1.0);
+    pos.distance(a + ab * t)
+}
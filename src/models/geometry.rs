@@ -0,0 +1,104 @@
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+
+/// Width of 1HP in rack coordinates.
+pub const GRID_UNIT: f32 = 15.2;
+/// Height of a rail, which is also the height of every module panel.
+pub const RAIL_HEIGHT: f32 = 380.0;
+/// Number of rails in the rack.
+pub const RAIL_COUNT: i32 = 24;
+/// HP covered by one tile of `Rail.svg`.
+pub const RAIL_TILE_HP: i32 = 20;
+/// Tiles of `Rail.svg` along each rail.
+pub const RAIL_TILES: i32 = 200;
+/// Length of each rail in HP. Modules end at or before this column.
+pub const RAIL_HP: i32 = RAIL_TILES * RAIL_TILE_HP;
+/// Rack position of HP 0 on the first rail. The rack keeps a margin above
+/// and left of the rails.
+pub const GRID_ORIGIN: egui::Pos2 = egui::pos2(100.0, 100.0);
+
+/// Cell of the rack grid: a rail and an HP column on it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct GridPos {
+    pub rail: i32,
+    pub column: i32,
+}
+
+impl GridPos {
+    pub fn new(rail: i32, column: i32) -> Self {
+        Self { rail, column }
+    }
+
+    /// The nearest cell where a module `hp` wide is on the rack: on one of
+    /// the rails and between their ends.
+    pub fn on_rack(self, hp: u32) -> Self {
+        Self::new(self.rail.clamp(0, RAIL_COUNT - 1), self.column.clamp(0, (RAIL_HP - hp as i32).max(0)))
+    }
+}
+
+/// Maps rack coordinates, in which plugin positions are kept, to the screen
+/// and to the (rail, HP) grid. Rack coordinates are pixels at 100% zoom.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RackGeometry {
+    /// Screen position of the rack's (0, 0).
+    pub origin: egui::Pos2,
+    pub zoom: f32,
+}
+
+impl Default for RackGeometry {
+    fn default() -> Self {
+        Self::new(egui::Pos2::ZERO, 1.0)
+    }
+}
+
+impl RackGeometry {
+    pub fn new(origin: egui::Pos2, zoom: f32) -> Self {
+        Self { origin, zoom }
+    }
+
+    pub fn to_screen(&self, pos: egui::Pos2) -> egui::Pos2 {
+        self.origin + pos.to_vec2() * self.zoom
+    }
+
+    pub fn to_rack(&self, pos: egui::Pos2) -> egui::Pos2 {
+        ((pos - self.origin) / self.zoom).to_pos2()
+    }
+
+    /// Screen rect of a rack rect.
+    pub fn rect_to_screen(&self, rect: egui::Rect) -> egui::Rect {
+        egui::Rect::from_min_max(self.to_screen(rect.min), self.to_screen(rect.max))
+    }
+
+    /// Rack position of the top left corner of a grid cell.
+    pub fn grid_to_rack(grid: GridPos) -> egui::Pos2 {
+        GRID_ORIGIN + egui::vec2(grid.column as f32 * GRID_UNIT, grid.rail as f32 * RAIL_HEIGHT)
+    }
+
+    /// Grid cell whose corner is nearest to a rack position, used to snap panels.
+    pub fn snap_to_grid(pos: egui::Pos2) -> GridPos {
+        let offset = pos - GRID_ORIGIN;
+        GridPos::new((offset.y / RAIL_HEIGHT).round() as i32, (offset.x / GRID_UNIT).round() as i32)
+    }
+
+    /// Grid cell containing a rack position.
+    pub fn grid_cell_at(pos: egui::Pos2) -> GridPos {
+        let offset = pos - GRID_ORIGIN;
+        GridPos::new((offset.y / RAIL_HEIGHT).floor() as i32, (offset.x / GRID_UNIT).floor() as i32)
+    }
+
+    pub fn grid_to_screen(&self, grid: GridPos) -> egui::Pos2 {
+        self.to_screen(Self::grid_to_rack(grid))
+    }
+
+    /// Grid cell under a screen position.
+    pub fn screen_to_grid(&self, pos: egui::Pos2) -> GridPos {
+        Self::grid_cell_at(self.to_rack(pos))
+    }
+}
+
+/// Scroll offset that keeps the rack point under `anchor` (relative to the top
+/// left of the viewport) in place when the zoom changes from `old_zoom` to `new_zoom`.
+pub fn anchored_scroll_offset(offset: egui::Vec2, anchor: egui::Vec2, old_zoom: f32, new_zoom: f32) -> egui::Vec2 {
+    let rack_point = (offset + anchor) / old_zoom;
+    (rack_point * new_zoom - anchor).max(egui::Vec2::ZERO)
+}
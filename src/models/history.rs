@@ -0,0 +1,273 @@
+use crate::models::cable::Cable;
+use crate::models::module_id::ModuleId;
+use crate::models::placement::PluginMove;
+use crate::models::plugin::{Plugin, PluginManager};
+use std::collections::VecDeque;
+
+/// Number of commands kept for undo.
+pub const DEFAULT_HISTORY_LIMIT: usize = 200;
+
+/// A reversible edit of the rack.
+#[derive(Debug, Clone)]
+pub enum Command {
+    AddPlugin(Plugin),
+    /// Plugins placed together, e.g. from a selection file, with the cables between them.
+    AddPlugins { plugins: Vec<Plugin>, cables: Vec<Cable> },
+    /// Deleted plugins together with the cables that were plugged into them.
+    DeletePlugins { plugins: Vec<Plugin>, cables: Vec<Cable> },
+    MovePlugins(Vec<PluginMove>),
+    AddCable(Cable),
+    RemoveCable(Cable),
+    RerouteCable { from: Cable, to: Cable },
+    SetParam { id: ModuleId, index: usize, from: f32, to: f32 },
+    /// Every param of a module at once, from a preset, Initialize or Randomize.
+    SetParams { id: ModuleId, from: Vec<f32>, to: Vec<f32>, name: &'static str },
+}
+
+impl Command {
+    /// Name shown in the Edit menu.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Command::AddPlugin(_) => "Add module",
+            Command::AddPlugins { .. } => "Add modules",
+            Command::DeletePlugins { plugins, .. } if plugins.len() > 1 => "Delete modules",
+            Command::DeletePlugins { .. } => "Delete module",
+            Command::MovePlugins(_) => "Move module",
+            Command::AddCable(_) => "Add cable",
+            Command::RemoveCable(_) => "Remove cable",
+            Command::RerouteCable { .. } => "Move cable",
+            Command::SetParam { .. } => "Change parameter",
+            Command::SetParams { name, .. } => name,
+        }
+    }
+
+    pub(crate) fn apply(&self, manager: &mut PluginManager) {
+        match self {
+            Command::AddPlugin(plugin) => manager.restore_plugin(plugin),
+            Command::AddPlugins { plugins, cables } => {
+                for plugin in plugins {
+                    manager.restore_plugin(plugin);
+                }
+                for cable in cables {
+                    manager.restore_cable(*cable);
+                }
+            }
+            Command::DeletePlugins { plugins, .. } => {
+                for plugin in plugins {
+                    manager.remove_plugin(plugin.id);
+                }
+            }
+            Command::MovePlugins(moves) => manager.apply_moves(moves),
+            Command::AddCable(cable) => {
+                manager.restore_cable(*cable);
+            }
+            Command::RemoveCable(cable) => {
+                manager.take_cable(cable.id);
+            }
+            Command::RerouteCable { to, .. } => {
+                manager.take_cable(to.id);
+                manager.restore_cable(*to);
+            }
+            Command::SetParam { id, index, to, .. } => {
+                manager.restore_param(*id, *index, *to);
+            }
+            Command::SetParams { id, to, .. } => {
+                manager.restore_params(*id, to);
+            }
+        }
+    }
+
+    pub(crate) fn revert(&self, manager: &mut PluginManager) {
+        match self {
+            Command::AddPlugin(plugin) => manager.remove_plugin(plugin.id),
+            Command::AddPlugins { plugins, .. } => {
+                for plugin in plugins {
+                    manager.remove_plugin(plugin.id);
+                }
+            }
+            Command::DeletePlugins { plugins, cables } => {
+                for plugin in plugins {
+                    manager.restore_plugin(plugin);
+                }
+                for cable in cables {
+                    manager.restore_cable(*cable);
+                }
+            }
+            Command::MovePlugins(moves) => manager.revert_moves(moves),
+            Command::AddCable(cable) => {
+                manager.take_cable(cable.id);
+            }
+            Command::RemoveCable(cable) => {
+                manager.restore_cable(*cable);
+            }
+            Command::RerouteCable { from, .. } => {
+                manager.take_cable(from.id);
+                manager.restore_cable(*from);
+            }
+            Command::SetParam { id, index, from, .. } => {
+                manager.restore_param(*id, *index, *from);
+            }
+            Command::SetParams { id, from, .. } => {
+                manager.restore_params(*id, from);
+            }
+        }
+    }
+
+    /// Folds `next` into this command when both turn the same knob.
+    fn merge(&mut self, next: &Command) -> bool {
+        match (self, next) {
+            (Command::SetParam { id, index, to, .. }, Command::SetParam { id: next_id, index: next_index, to: next_to, .. })
+                if id == next_id && index == next_index =>
+            {
+                *to = *next_to;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    command: Command,
+    /// Identifies the rack state reached by applying the command.
+    revision: u64,
+}
+
+/// Bounded undo/redo stacks. Every state of the rack gets a revision number
+/// so the history can tell whether it is back at the last saved state.
+#[derive(Debug, Clone)]
+pub struct History {
+    undo: VecDeque<Entry>,
+    redo: Vec<Entry>,
+    limit: usize,
+    next_revision: u64,
+    /// Revision of the state before the oldest command still in the history.
+    base_revision: u64,
+    saved_revision: Option<u64>,
+    /// Whether the last pushed command may absorb the next one.
+    merge_open: bool,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self::with_limit(DEFAULT_HISTORY_LIMIT)
+    }
+}
+
+impl History {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_limit(limit: usize) -> Self {
+        Self {
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            limit: limit.max(1),
+            next_revision: 1,
+            base_revision: 0,
+            saved_revision: Some(0),
+            merge_open: false,
+        }
+    }
+
+    /// Records a command that has already been applied. Clears the redo stack.
+    pub fn push(&mut self, command: Command) {
+        self.push_entry(command);
+        self.merge_open = false;
+    }
+
+    /// Like `push`, but consecutive changes of the same param are folded into
+    /// one command until `seal` is called, so a knob drag undoes in one step.
+    pub fn push_mergeable(&mut self, command: Command) {
+        let revision = self.next_revision;
+        let merged = self.merge_open && self.undo.back_mut().is_some_and(|entry| {
+            let merged = entry.command.merge(&command);
+            if merged {
+                entry.revision = revision;
+            }
+            merged
+        });
+        if merged {
+            // The merged command leads to a new state
+            self.next_revision += 1;
+            self.redo.clear();
+        } else {
+            self.push_entry(command);
+        }
+        self.merge_open = true;
+    }
+
+    /// Ends the current knob gesture.
+    pub fn seal(&mut self) {
+        self.merge_open = false;
+    }
+
+    fn push_entry(&mut self, command: Command) {
+        self.redo.clear();
+        self.undo.push_back(Entry { command, revision: self.next_revision });
+        self.next_revision += 1;
+        while self.undo.len() > self.limit {
+            if let Some(entry) = self.undo.pop_front() {
+                self.base_revision = entry.revision;
+            }
+        }
+    }
+
+    pub(crate) fn take_undo(&mut self) -> Option<Command> {
+        self.merge_open = false;
+        let entry = self.undo.pop_back()?;
+        let command = entry.command.clone();
+        self.redo.push(entry);
+        Some(command)
+    }
+
+    pub(crate) fn take_redo(&mut self) -> Option<Command> {
+        self.merge_open = false;
+        let entry = self.redo.pop()?;
+        let command = entry.command.clone();
+        self.undo.push_back(entry);
+        Some(command)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn undo_name(&self) -> Option<&'static str> {
+        self.undo.back().map(|entry| entry.command.name())
+    }
+
+    pub fn redo_name(&self) -> Option<&'static str> {
+        self.redo.last().map(|entry| entry.command.name())
+    }
+
+    pub fn undo_len(&self) -> usize {
+        self.undo.len()
+    }
+
+    fn current_revision(&self) -> u64 {
+        self.undo.back().map_or(self.base_revision, |entry| entry.revision)
+    }
+
+    /// Remembers the current state as saved.
+    pub fn mark_saved(&mut self) {
+        self.saved_revision = Some(self.current_revision());
+        self.merge_open = false;
+    }
+
+    /// Whether the rack differs from the last saved state.
+    pub fn is_modified(&self) -> bool {
+        self.saved_revision != Some(self.current_revision())
+    }
+
+    /// Forgets all commands; the current state counts as saved.
+    pub fn clear(&mut self) {
+        *self = Self::with_limit(self.limit);
+    }
+}
@@ -1 +1,13 @@
-pub mod plugin;
\ No newline at end of file
+pub mod browser;
+pub mod cable;
+pub mod geometry;
+pub mod history;
+pub mod module;
+pub mod module_id;
+pub mod patch;
+pub mod placement;
+pub mod plugin;
+pub mod preset;
+pub mod recent_files;
+pub mod selection;
+pub mod widgets;
@@ -0,0 +1,242 @@
+/// Maximum number of polyphonic channels carried by a single port.
+pub const MAX_CHANNELS: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParamKind {
+    Knob,
+    /// Toggle or selector that snaps to whole numbers between min and max.
+    Switch,
+}
+
+/// Static description of one parameter. Positions are panel coordinates at 100% zoom.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParamInfo {
+    pub name: &'static str,
+    pub kind: ParamKind,
+    pub min: f32,
+    pub max: f32,
+    pub default: f32,
+    pub unit: &'static str,
+    pub pos: (f32, f32),
+}
+
+impl ParamInfo {
+    pub const fn knob(name: &'static str, min: f32, max: f32, default: f32, unit: &'static str, pos: (f32, f32)) -> Self {
+        Self { name, kind: ParamKind::Knob, min, max, default, unit, pos }
+    }
+
+    pub const fn switch(name: &'static str, positions: u32, default: f32, pos: (f32, f32)) -> Self {
+        Self { name, kind: ParamKind::Switch, min: 0.0, max: (positions - 1) as f32, default, unit: "", pos }
+    }
+
+    pub fn clamp(&self, value: f32) -> f32 {
+        let value = value.clamp(self.min, self.max);
+        match self.kind {
+            ParamKind::Knob => value,
+            ParamKind::Switch => value.round(),
+        }
+    }
+
+    pub fn normalize(&self, value: f32) -> f32 {
+        if self.max > self.min {
+            (value - self.min) / (self.max - self.min)
+        } else {
+            0.0
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PortInfo {
+    pub name: &'static str,
+    pub pos: (f32, f32),
+}
+
+impl PortInfo {
+    pub const fn new(name: &'static str, pos: (f32, f32)) -> Self {
+        Self { name, pos }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LightInfo {
+    pub name: &'static str,
+    pub pos: (f32, f32),
+    pub color: (u8, u8, u8),
+}
+
+impl LightInfo {
+    pub const fn new(name: &'static str, pos: (f32, f32), color: (u8, u8, u8)) -> Self {
+        Self { name, pos, color }
+    }
+}
+
+/// Everything the rack needs to know about a module type without instantiating it.
+#[derive(Debug)]
+pub struct ModuleDescriptor {
+    pub slug: &'static str,
+    pub name: &'static str,
+    /// Maker shown in the module browser.
+    pub brand: &'static str,
+    /// Categories used to filter the module browser, e.g. "Oscillator".
+    pub tags: &'static [&'static str],
+    /// Width in HP (1HP = 5.08mm).
+    pub hp: u32,
+    /// Path of the panel SVG, relative to the working directory.
+    pub panel_svg: &'static str,
+    pub params: &'static [ParamInfo],
+    pub inputs: &'static [PortInfo],
+    pub outputs: &'static [PortInfo],
+    pub lights: &'static [LightInfo],
+    pub create: fn() -> Box<dyn Module>,
+}
+
+impl ModuleDescriptor {
+    pub fn default_params(&self) -> Vec<f32> {
+        self.params.iter().map(|p| p.default).collect()
+    }
+}
+
+/// Polyphonic voltage carried by an input or output port.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Port {
+    pub voltages: [f32; MAX_CHANNELS],
+    /// 0 means unconnected, 1 is mono, up to `MAX_CHANNELS` for polyphonic cables.
+    pub channels: usize,
+}
+
+impl Default for Port {
+    fn default() -> Self {
+        Self {
+            voltages: [0.0; MAX_CHANNELS],
+            channels: 0,
+        }
+    }
+}
+
+impl Port {
+    pub fn is_connected(&self) -> bool {
+        self.channels > 0
+    }
+
+    pub fn voltage(&self) -> f32 {
+        self.voltages[0]
+    }
+
+    pub fn voltage_at(&self, channel: usize) -> f32 {
+        self.voltages[channel]
+    }
+
+    /// Voltage of `channel`, spreading a mono signal to every channel.
+    pub fn poly_voltage(&self, channel: usize) -> f32 {
+        if self.channels == 1 {
+            self.voltages[0]
+        } else {
+            self.voltages[channel]
+        }
+    }
+
+    /// Returns `normal` when nothing is patched into the port.
+    pub fn normal_voltage(&self, normal: f32) -> f32 {
+        if self.is_connected() {
+            self.voltages[0]
+        } else {
+            normal
+        }
+    }
+
+    pub fn set_voltage(&mut self, voltage: f32) {
+        self.voltages[0] = voltage;
+    }
+
+    pub fn set_voltage_at(&mut self, channel: usize, voltage: f32) {
+        self.voltages[channel] = voltage;
+    }
+
+    pub fn set_channels(&mut self, channels: usize) {
+        self.channels = channels.min(MAX_CHANNELS);
+    }
+}
+
+/// Params, ports and lights of a module instance, laid out like its descriptor.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModuleIo {
+    pub params: Vec<f32>,
+    pub inputs: Vec<Port>,
+    pub outputs: Vec<Port>,
+    pub lights: Vec<f32>,
+}
+
+impl ModuleIo {
+    pub fn new(descriptor: &ModuleDescriptor) -> Self {
+        Self {
+            params: descriptor.default_params(),
+            inputs: vec![Port::default(); descriptor.inputs.len()],
+            // Outputs always drive at least one channel so cables carry a signal
+            outputs: vec![Port { channels: 1, ..Port::default() }; descriptor.outputs.len()],
+            lights: vec![0.0; descriptor.lights.len()],
+        }
+    }
+}
+
+/// Timing information passed to `Module::process` once per sample.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProcessContext {
+    pub sample_rate: f32,
+    pub sample_time: f32,
+    /// Index of the sample being processed since the engine started.
+    pub frame: u64,
+}
+
+impl ProcessContext {
+    pub fn new(sample_rate: f32, frame: u64) -> Self {
+        Self {
+            sample_rate,
+            sample_time: 1.0 / sample_rate,
+            frame,
+        }
+    }
+}
+
+/// DSP implementation of a module type.
+///
+/// The engine writes connected input voltages into `io().inputs` and the current
+/// knob positions into `io().params` before calling `process` for every sample.
+pub trait Module: Send {
+    fn descriptor(&self) -> &'static ModuleDescriptor;
+
+    fn io(&self) -> &ModuleIo;
+
+    fn io_mut(&mut self) -> &mut ModuleIo;
+
+    fn process(&mut self, ctx: &ProcessContext);
+
+    fn params(&self) -> &[f32] {
+        &self.io().params
+    }
+
+    fn inputs(&self) -> &[Port] {
+        &self.io().inputs
+    }
+
+    fn outputs(&self) -> &[Port] {
+        &self.io().outputs
+    }
+
+    fn lights(&self) -> &[f32] {
+        &self.io().lights
+    }
+
+    fn set_param(&mut self, index: usize, value: f32) {
+        if let Some(info) = self.descriptor().params.get(index) {
+            let value = info.clamp(value);
+            self.io_mut().params[index] = value;
+        }
+    }
+
+    /// Stereo frame this module sends to the engine output. Only audio
+    /// interface modules return `Some`.
+    fn audio_output(&self) -> Option<[f32; 2]> {
+        None
+    }
+}
@@ -0,0 +1,80 @@
+use std::collections::HashSet;
+use std::fmt;
+
+/// Identifies a plugin on the rack and in patch files.
+///
+/// New ids are random like VCV Rack's, so they never clash with the ids of
+/// modules that were deleted, loaded or pasted. They stay below 2^53 so
+/// JSON readers that store numbers as doubles keep them exact.
+pub type ModuleId = u64;
+
+/// Largest id handed out or accepted when loading.
+pub const MAX_MODULE_ID: ModuleId = (1 << 53) - 1;
+
+/// Hands out random module ids that are not in use yet.
+#[derive(Debug, Clone)]
+pub struct IdAllocator {
+    rng: fastrand::Rng,
+}
+
+impl Default for IdAllocator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IdAllocator {
+    pub fn new() -> Self {
+        Self { rng: fastrand::Rng::new() }
+    }
+
+    /// Allocator that hands out the same ids every run.
+    pub fn with_seed(seed: u64) -> Self {
+        Self { rng: fastrand::Rng::with_seed(seed) }
+    }
+
+    /// A random id for which `in_use` is false.
+    pub fn allocate(&mut self, in_use: impl Fn(ModuleId) -> bool) -> ModuleId {
+        loop {
+            let id = self.rng.u64(1..=MAX_MODULE_ID);
+            if !in_use(id) {
+                return id;
+            }
+        }
+    }
+}
+
+/// A module whose id had to change while loading a rack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IdRepair {
+    /// Position of the module in the rack file.
+    pub index: usize,
+    pub from: ModuleId,
+    pub to: ModuleId,
+}
+
+impl fmt::Display for IdRepair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Module {} had the invalid or duplicate id {}, it now has id {}", self.index, self.from, self.to)
+    }
+}
+
+/// Gives every id that is out of range or already used by an earlier entry
+/// a fresh one. Returns the ids to use, in the same order, and what was changed.
+pub fn repair_ids(ids: &[ModuleId], allocator: &mut IdAllocator) -> (Vec<ModuleId>, Vec<IdRepair>) {
+    let mut taken: HashSet<ModuleId> = ids.iter().copied().filter(|&id| id <= MAX_MODULE_ID).collect();
+    let mut seen = HashSet::new();
+    let mut repaired = Vec::with_capacity(ids.len());
+    let mut repairs = Vec::new();
+    for (index, &id) in ids.iter().enumerate() {
+        if id <= MAX_MODULE_ID && seen.insert(id) {
+            repaired.push(id);
+            continue;
+        }
+        let to = allocator.allocate(|candidate| taken.contains(&candidate));
+        taken.insert(to);
+        repaired.push(to);
+        repairs.push(IdRepair { index, from: id, to });
+    }
+    (repaired, repairs)
+}
@@ -0,0 +1,171 @@
+use crate::models::geometry::RackGeometry;
+use crate::models::plugin::RackState;
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Version of the patch files this build writes.
+///
+/// - 0: bare `{"plugins": [...], "cables": [...]}` with pixel positions
+/// - 1: bare rack with (rail, column) grid positions
+/// - 2: `{"version", "metadata", "rack"}`
+pub const FORMAT_VERSION: u32 = 2;
+
+/// Upgrades a patch file from one format version to the next.
+pub struct Migration {
+    /// Version this migration upgrades from, to `from + 1`.
+    pub from: u32,
+    pub description: &'static str,
+    apply: fn(&mut Value) -> Result<(), Box<dyn Error>>,
+}
+
+static MIGRATIONS: &[Migration] = &[
+    Migration {
+        from: 0,
+        description: "Store module positions as rail and HP column",
+        apply: pixel_positions_to_grid,
+    },
+    Migration {
+        from: 1,
+        description: "Add format version and metadata",
+        apply: wrap_rack_in_patch,
+    },
+];
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PatchMetadata {
+    /// Version of the app that last saved the patch.
+    pub app_version: String,
+    /// Seconds since the Unix epoch, 0 when unknown.
+    pub created: u64,
+    pub modified: u64,
+    pub author: String,
+    pub description: String,
+}
+
+impl PatchMetadata {
+    /// Metadata of a patch that is about to be saved now.
+    pub fn touched(&self) -> Self {
+        let now = unix_time();
+        Self {
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            created: if self.created == 0 { now } else { self.created },
+            modified: now,
+            ..self.clone()
+        }
+    }
+}
+
+/// Contents of a patch file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Patch {
+    pub version: u32,
+    #[serde(default)]
+    pub metadata: PatchMetadata,
+    pub rack: RackState,
+    /// Patch file an autosave was made from; only autosaves have it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<PathBuf>,
+}
+
+/// A patch read from disk, with the migrations that upgraded it.
+#[derive(Debug, Clone)]
+pub struct LoadedPatch {
+    pub patch: Patch,
+    pub migrations: Vec<&'static str>,
+}
+
+impl Patch {
+    pub fn new(rack: RackState, metadata: PatchMetadata) -> Self {
+        Self { version: FORMAT_VERSION, metadata, rack, source: None }
+    }
+
+    /// Writes the patch to a temporary file next to `path` and renames it
+    /// over `path`, so a crash never leaves a half written patch behind.
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        let file_name = path.file_name().ok_or("Patch path has no file name")?;
+        let temp_path = path.with_file_name(format!("{}.tmp", file_name.to_string_lossy()));
+        fs::write(&temp_path, serde_json::to_string_pretty(self)?)?;
+        if let Err(err) = fs::rename(&temp_path, path) {
+            fs::remove_file(&temp_path).ok();
+            return Err(err.into());
+        }
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<LoadedPatch, Box<dyn Error>> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+
+    /// Parses a patch of any known format version, upgrading it step by step.
+    pub fn from_json(json: &str) -> Result<LoadedPatch, Box<dyn Error>> {
+        let mut value: Value = serde_json::from_str(json)?;
+        let mut version = format_version(&value)?;
+        if version > FORMAT_VERSION {
+            return Err(format!(
+                "Patch format version {} is newer than the supported version {}", version, FORMAT_VERSION
+            ).into());
+        }
+
+        let mut migrations = Vec::new();
+        while version < FORMAT_VERSION {
+            let migration = MIGRATIONS.iter()
+                .find(|m| m.from == version)
+                .ok_or_else(|| format!("No migration from patch format version {}", version))?;
+            (migration.apply)(&mut value)?;
+            migrations.push(migration.description);
+            version += 1;
+        }
+
+        let patch: Patch = serde_json::from_value(value)?;
+        Ok(LoadedPatch { patch, migrations })
+    }
+}
+
+/// Format version of a parsed patch file. Files from before versioning are
+/// told apart by how they store positions.
+fn format_version(value: &Value) -> Result<u32, Box<dyn Error>> {
+    if let Some(version) = value.get("version") {
+        let version = version.as_u64().ok_or("Patch format version is not a number")?;
+        return Ok(u32::try_from(version)?);
+    }
+    let plugins = value.get("plugins").and_then(Value::as_array).ok_or("Not a patch file")?;
+    let has_pixel_positions = plugins.iter().any(|p| p.get("x").is_some() && p.get("rail").is_none());
+    Ok(if has_pixel_positions { 0 } else { 1 })
+}
+
+fn pixel_positions_to_grid(value: &mut Value) -> Result<(), Box<dyn Error>> {
+    let plugins = value.get_mut("plugins").and_then(Value::as_array_mut).ok_or("Not a patch file")?;
+    for plugin in plugins.iter_mut().filter_map(Value::as_object_mut) {
+        if plugin.contains_key("rail") {
+            continue;
+        }
+        let coordinate = |name: &str| plugin.get(name).and_then(Value::as_f64).unwrap_or(0.0) as f32;
+        let grid = RackGeometry::snap_to_grid(egui::pos2(coordinate("x"), coordinate("y")));
+        plugin.remove("x");
+        plugin.remove("y");
+        plugin.insert("rail".to_string(), grid.rail.into());
+        plugin.insert("column".to_string(), grid.column.max(0).into());
+    }
+    Ok(())
+}
+
+fn wrap_rack_in_patch(value: &mut Value) -> Result<(), Box<dyn Error>> {
+    let rack = value.take();
+    *value = serde_json::json!({
+        "version": 2,
+        "metadata": PatchMetadata::default(),
+        "rack": rack,
+    });
+    Ok(())
+}
+
+/// Seconds since the Unix epoch.
+pub fn unix_time() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
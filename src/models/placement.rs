@@ -0,0 +1,89 @@
+use crate::models::geometry::RAIL_HP;
+use crate::models::module_id::ModuleId;
+use eframe::egui;
+
+/// Columns covered by one plugin on a rail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub id: ModuleId,
+    pub column: i32,
+    pub hp: u32,
+}
+
+impl Span {
+    pub fn new(id: ModuleId, column: i32, hp: u32) -> Self {
+        Self { id, column, hp }
+    }
+
+    pub fn end(&self) -> i32 {
+        self.column + self.hp as i32
+    }
+
+    fn center(&self) -> f32 {
+        self.column as f32 + self.hp as f32 / 2.0
+    }
+
+    fn overlaps(&self, column: i32, hp: u32) -> bool {
+        column < self.end() && self.column < column + hp as i32
+    }
+}
+
+/// One plugin moving from one rack position to another.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PluginMove {
+    pub id: ModuleId,
+    pub from: egui::Pos2,
+    pub to: egui::Pos2,
+}
+
+/// Places the `others` on a rail around the `moving` spans, which keep their
+/// columns. Plugins in the way are pushed aside the way VCV Rack does it:
+/// the ones whose centre lies left of the nearest moving span go left, the
+/// rest go right, shoving their neighbours along. Plugins that would be pushed
+/// past either end of the rail go the other way instead. Returns the new
+/// spans of `others`, or `None` when the rail has no room for all of them.
+pub fn push_aside(moving: &[Span], others: &[Span]) -> Option<Vec<Span>> {
+    let mut placed: Vec<Span> = moving.to_vec();
+    let (mut left, mut right): (Vec<Span>, Vec<Span>) = others.iter().partition(|other| {
+        moving.iter()
+            .min_by(|a, b| (a.center() - other.center()).abs().total_cmp(&(b.center() - other.center()).abs()))
+            .is_some_and(|nearest| other.center() < nearest.center())
+    });
+
+    right.sort_by_key(|span| span.column);
+    for span in right {
+        let column = free_column_right(&placed, span.column, span.hp)
+            .or_else(|| free_column_left(&placed, span.column, span.hp))?;
+        placed.push(Span { column, ..span });
+    }
+
+    left.sort_by_key(|span| std::cmp::Reverse(span.column));
+    for span in left {
+        let column = free_column_left(&placed, span.column, span.hp)
+            .or_else(|| free_column_right(&placed, span.column, span.hp))?;
+        placed.push(Span { column, ..span });
+    }
+
+    Some(placed.split_off(moving.len()))
+}
+
+fn is_free(placed: &[Span], column: i32, hp: u32) -> bool {
+    !placed.iter().any(|span| span.overlaps(column, hp))
+}
+
+fn free_column_right(placed: &[Span], mut column: i32, hp: u32) -> Option<i32> {
+    while !is_free(placed, column, hp) {
+        column += 1;
+    }
+    (column + hp as i32 <= RAIL_HP).then_some(column)
+}
+
+fn free_column_left(placed: &[Span], mut column: i32, hp: u32) -> Option<i32> {
+    while !is_free(placed, column, hp) {
+        column -= 1;
+        if column < 0 {
+            return None;
+        }
+    }
+    Some(column)
+}
@@ -1,18 +1,48 @@
+use crate::engine::{CableSpec, GraphSpec, ModuleSpec};
+use crate::models::cable::{self, Cable, PortKind, PortRef, CABLE_COLORS};
+use crate::models::geometry::{GridPos, RackGeometry, GRID_UNIT, RAIL_COUNT, RAIL_HEIGHT, RAIL_HP};
+use crate::models::history::{Command, History};
+use crate::models::module::ModuleDescriptor;
+use crate::models::module_id::{self, IdAllocator, IdRepair, ModuleId, MAX_MODULE_ID};
+use crate::models::placement::{self, PluginMove, Span};
+use crate::models::preset::{self, Preset, PresetAction};
+use crate::models::selection::Selection;
+use crate::models::widgets;
+use crate::modules;
 use eframe::egui;
 use serde::{Serialize, Deserialize};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
 
+/// On-rack instance of a module type.
 #[derive(Clone)]
 pub struct Plugin {
     #[allow(dead_code)]
     pub texture: Option<egui::TextureHandle>,
     pub position: egui::Pos2,
     pub selected: bool,
-    pub id: usize,
+    pub id: ModuleId,
+    pub descriptor: &'static ModuleDescriptor,
+    pub params: Vec<f32>,
+    /// Brightness of the lights as last reported by the engine; not saved.
+    pub lights: Vec<f32>,
+}
+
+/// What happened to a plugin while it was drawn.
+pub struct PluginResponse {
+    pub response: egui::Response,
+    pub delete_requested: bool,
+    /// Params turned this frame as (index, old value, new value).
+    pub param_changes: Vec<(usize, f32, f32)>,
+    /// Port the user started dragging a cable from.
+    pub port_drag_started: Option<(PortKind, usize)>,
+    pub preset_action: Option<PresetAction>,
 }
 
 impl std::fmt::Debug for Plugin {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Plugin")
+            .field("slug", &self.descriptor.slug)
             .field("position", &self.position)
             .field("selected", &self.selected)
             .field("id", &self.id)
@@ -25,13 +55,7 @@ impl Serialize for Plugin {
     where
         S: serde::Serializer,
     {
-        let state = PluginState {
-            x: self.position.x,
-            y: self.position.y,
-            selected: self.selected,
-            id: self.id,
-        };
-        state.serialize(serializer)
+        self.to_state().serialize(serializer)
     }
 }
 
@@ -47,75 +71,133 @@ impl<'de> Deserialize<'de> for Plugin {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PluginState {
-    pub x: f32,
-    pub y: f32,
+    pub rail: i32,
+    pub column: i32,
     pub selected: bool,
-    pub id: usize,
+    pub id: ModuleId,
+    /// Module type; racks saved before module types existed only contain blank plates.
+    #[serde(default = "default_slug")]
+    pub slug: String,
+    #[serde(default)]
+    pub params: Vec<f32>,
+}
+
+fn default_slug() -> String {
+    modules::blank_plate().slug.to_string()
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RackState {
     pub plugins: Vec<PluginState>,
+    #[serde(default)]
+    pub cables: Vec<Cable>,
 }
 
 impl Plugin {
-    pub fn new(position: egui::Pos2, texture: Option<egui::TextureHandle>, id: usize) -> Self {
-        let grid_unit = 15.2;
-        let relative_x = position.x - 100.0;
-        
-        let grid_index = if relative_x <= 0.0 {
-            0
-        } else {
-            (relative_x / grid_unit).round() as i32
-        };
-        
-        let grid_x = 100.0 + (grid_index as f32 * grid_unit);
-        
+    /// Creates a blank plate.
+    pub fn new(position: egui::Pos2, texture: Option<egui::TextureHandle>, id: ModuleId) -> Self {
+        Self::with_module(modules::blank_plate(), position, texture, id)
+    }
+
+    /// Creates a module snapped to the grid cell on the rack nearest to
+    /// `position`.
+    pub fn with_module(descriptor: &'static ModuleDescriptor, position: egui::Pos2, texture: Option<egui::TextureHandle>, id: ModuleId) -> Self {
         Self {
-            position: egui::pos2(grid_x, position.y),
+            position: RackGeometry::grid_to_rack(RackGeometry::snap_to_grid(position).on_rack(descriptor.hp)),
             texture,
             selected: false,  // Explicitly set to false
             id,
+            descriptor,
+            params: descriptor.default_params(),
+            lights: vec![0.0; descriptor.lights.len()],
         }
     }
 
+    pub fn slug(&self) -> &'static str {
+        self.descriptor.slug
+    }
+
+    /// Sets a param, clamped to its range. Returns false for an unknown index.
+    pub fn set_param(&mut self, index: usize, value: f32) -> bool {
+        match self.descriptor.params.get(index) {
+            Some(info) => {
+                self.params[index] = info.clamp(value);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Centre of a port in rack coordinates.
+    pub fn port_center(&self, kind: PortKind, index: usize) -> Option<egui::Pos2> {
+        let ports = match kind {
+            PortKind::Input => self.descriptor.inputs,
+            PortKind::Output => self.descriptor.outputs,
+        };
+        ports.get(index)
+            .map(|info| self.position + egui::vec2(info.pos.0, info.pos.1))
+    }
+
+    /// Checks whether `pos` lies anywhere on the panel.
     pub fn is_at_position(&self, pos: egui::Pos2, _zoom_level: f32) -> bool {
-        const GRID_UNIT: f32 = 15.2;
-        
-        // Calculate grid boundaries
-        let half_grid = GRID_UNIT / 2.0;
-        let min_x = self.position.x - half_grid;
-        let max_x = self.position.x + half_grid;
-        
-        // Check if position is within grid boundaries
-        pos.x >= min_x && pos.x < max_x && 
-        pos.y >= self.position.y && pos.y < self.position.y + GRID_UNIT
+        pos.x >= self.position.x && pos.x < self.position.x + self.get_width() &&
+        pos.y >= self.position.y && pos.y < self.position.y + RAIL_HEIGHT
     }
 
+    /// Checks whether the grid cell at (`grid_x`, `grid_y`) is covered by this plugin.
     pub fn is_at_grid_position(&self, grid_x: f32, grid_y: f32) -> bool {
-        const GRID_UNIT: f32 = 15.2;
-        const RAIL_HEIGHT: f32 = 380.0;
-        
-        let plugin_relative_x = self.position.x - 100.0;
-        let plugin_grid_x = (plugin_relative_x / GRID_UNIT).round() as i32;
-        let plugin_grid_y = ((self.position.y - 100.0) / RAIL_HEIGHT).round() as i32;
-        
-        let target_grid_x = ((grid_x - 100.0) / GRID_UNIT).round() as i32;
-        let target_grid_y = ((grid_y - 100.0) / RAIL_HEIGHT).round() as i32;
-        
-        plugin_grid_x == target_grid_x && plugin_grid_y == target_grid_y
+        let target = RackGeometry::snap_to_grid(egui::pos2(grid_x, grid_y));
+        self.occupies(target.rail, target.column, 1)
+    }
+
+    /// Grid cell of the left edge of the panel.
+    pub fn grid(&self) -> GridPos {
+        RackGeometry::snap_to_grid(self.position)
+    }
+
+    /// Index of the HP column the left edge of the panel sits on.
+    pub fn grid_column(&self) -> i32 {
+        self.grid().column
+    }
+
+    pub fn rail_index(&self) -> i32 {
+        self.grid().rail
+    }
+
+    /// Checks whether this plugin overlaps `hp` columns starting at `column` on `rail`.
+    pub fn occupies(&self, rail: i32, column: i32, hp: u32) -> bool {
+        let start = self.grid_column();
+        let end = start + self.hp() as i32;
+        self.rail_index() == rail && column < end && start < column + hp as i32
+    }
+
+    pub fn hp(&self) -> u32 {
+        self.descriptor.hp
+    }
+
+    /// Moves the panel so its left edge sits on `column` of `rail`.
+    pub fn set_grid_position(&mut self, rail: i32, column: i32) {
+        self.position = RackGeometry::grid_to_rack(GridPos::new(rail, column));
     }
 
-    pub fn draw(&self, ui: &mut egui::Ui, zoom_level: f32) -> (egui::Response, Option<usize>) {
-        let mut delete_id = None;
+    fn span(&self) -> Span {
+        Span::new(self.id, self.grid_column(), self.hp())
+    }
+
+    /// Draws the plugin; `presets` are the preset files of its module type.
+    pub fn draw(&mut self, ui: &mut egui::Ui, geometry: &RackGeometry, presets: &[PathBuf]) -> PluginResponse {
+        let mut delete_requested = false;
+        let mut preset_action = None;
+        let mut param_changes = Vec::new();
+        let mut port_drag_started = None;
         let mut response = ui.allocate_response(egui::Vec2::ZERO, egui::Sense::click());
         
         if let Some(texture) = &self.texture {
-            let size = texture.size_vec2() / zoom_level;
-            let rect = egui::Rect::from_min_size(self.position, size);
+            let size = egui::vec2(self.get_width(), RAIL_HEIGHT);
+            let rect = geometry.rect_to_screen(egui::Rect::from_min_size(self.position, size));
             
             // First allocate the response for the entire plugin area
-            response = ui.allocate_rect(rect, egui::Sense::click());
+            response = ui.allocate_rect(rect, egui::Sense::click_and_drag());
             
             // Then draw the plugin texture
             let mut mesh = egui::Mesh::with_texture(texture.id());
@@ -129,11 +211,42 @@ impl Plugin {
             
             ui.painter().add(mesh);
 
+            // Draw the param knobs on top of the panel
+            let scale = geometry.zoom;
+            for (index, info) in self.descriptor.params.iter().enumerate() {
+                let center = geometry.to_screen(self.position + egui::vec2(info.pos.0, info.pos.1));
+                let id = ui.id().with(("param", self.id, index));
+                if let Some(value) = widgets::param_knob(ui, id, center, scale, info, self.params[index]) {
+                    param_changes.push((index, self.params[index], value));
+                    self.params[index] = value;
+                }
+            }
+
+            // Draw the jacks and report where a cable drag starts
+            for (kind, ports) in [(PortKind::Input, self.descriptor.inputs), (PortKind::Output, self.descriptor.outputs)] {
+                for (index, info) in ports.iter().enumerate() {
+                    let center = geometry.to_screen(self.position + egui::vec2(info.pos.0, info.pos.1));
+                    let id = ui.id().with(("port", self.id, kind == PortKind::Input, index));
+                    if widgets::port(ui, id, center, scale, kind, info.name).drag_started() {
+                        port_drag_started = Some((kind, index));
+                    }
+                }
+            }
+
+            for (info, brightness) in self.descriptor.lights.iter().zip(&self.lights) {
+                let center = geometry.to_screen(self.position + egui::vec2(info.pos.0, info.pos.1));
+                widgets::light(ui, center, scale, info.color, *brightness);
+            }
+
             // Handle context menu
             response.context_menu(|ui| {
+                ui.label(self.descriptor.name);
+                ui.separator();
+                preset_action = preset::preset_menu(ui, !self.descriptor.params.is_empty(), presets);
+                ui.separator();
                 if ui.button("Delete").clicked() {
                     ui.close_menu();
-                    delete_id = Some(self.id);
+                    delete_requested = true;
                 }
             });
             
@@ -143,7 +256,13 @@ impl Plugin {
             }
         }
         
-        (response, delete_id)
+        PluginResponse {
+            response,
+            delete_requested,
+            param_changes,
+            port_drag_started,
+            preset_action,
+        }
     }
 
     pub fn set_selected(&mut self, selected: bool) {
@@ -159,87 +278,249 @@ impl Plugin {
         self.selected
     }
 
-    #[allow(dead_code)]
+    /// Panel width at 100% zoom.
     pub fn get_width(&self) -> f32 {
-        15.2
+        self.hp() as f32 * GRID_UNIT
     }
 
     pub fn to_state(&self) -> PluginState {
+        let grid = self.grid();
         PluginState {
-            x: self.position.x,
-            y: self.position.y,
+            rail: grid.rail,
+            column: grid.column,
             selected: self.selected,
             id: self.id,
+            slug: self.descriptor.slug.to_string(),
+            params: self.params.clone(),
         }
     }
 
+    /// Restores a plugin. Unknown module types become blank plates, missing
+    /// or out of range params fall back to their defaults and positions off
+    /// the rack are moved onto its nearest rail.
     pub fn from_state(state: PluginState, texture: Option<egui::TextureHandle>) -> Self {
+        let descriptor = modules::find(&state.slug).unwrap_or(modules::blank_plate());
+        let params = descriptor.params.iter()
+            .enumerate()
+            .map(|(index, info)| {
+                state.params.get(index).map_or(info.default, |value| info.clamp(*value))
+            })
+            .collect();
         Self {
             texture,
-            position: egui::pos2(state.x, state.y),
+            position: RackGeometry::grid_to_rack(GridPos::new(state.rail, state.column).on_rack(descriptor.hp)),
             selected: state.selected,
             id: state.id,
+            descriptor,
+            params,
+            lights: vec![0.0; descriptor.lights.len()],
         }
     }
 }
 
+/// Cable being dragged from `anchor` towards the pointer.
+#[derive(Debug, Clone, Copy)]
+struct CableDrag {
+    anchor_kind: PortKind,
+    anchor: PortRef,
+    /// Cable whose other end was picked up; dropping it on empty space deletes it.
+    rerouted: Option<Cable>,
+}
+
+/// Selected plugins being dragged along the rails.
+#[derive(Debug, Clone)]
+struct ModuleDrag {
+    /// Where every plugin in the rack was when the drag started.
+    origin: Vec<(ModuleId, egui::Pos2)>,
+    moving: Vec<ModuleId>,
+    start_pointer: egui::Pos2,
+}
+
+#[derive(Default)]
 pub struct PluginManager {
     plugins: Vec<Plugin>,
-    next_id: usize,
+    /// Hands out the ids of new plugins.
+    ids: IdAllocator,
+    panel_textures: HashMap<&'static str, egui::TextureHandle>,
+    cables: Vec<Cable>,
+    next_cable_id: usize,
+    cable_drag: Option<CableDrag>,
+    module_drag: Option<ModuleDrag>,
+    history: History,
+    /// Preset files per module type, listed in the module context menu.
+    presets: HashMap<&'static str, Vec<PathBuf>>,
+    /// Plugin whose params the user wants to save as a preset.
+    preset_save_request: Option<ModuleId>,
 }
 
 impl PluginManager {
     pub fn new() -> Self {
         Self {
             plugins: Vec::new(),
-            next_id: 0,
+            ids: IdAllocator::new(),
+            panel_textures: HashMap::new(),
+            cables: Vec::new(),
+            next_cable_id: 0,
+            cable_drag: None,
+            module_drag: None,
+            history: History::new(),
+            presets: HashMap::new(),
+            preset_save_request: None,
         }
     }
 
-    pub fn add_plugin(&mut self, position: egui::Pos2, texture: Option<egui::TextureHandle>) {
-        const GRID_UNIT: f32 = 15.2;
-        const RAIL_HEIGHT: f32 = 380.0;
-        
-        let relative_x = position.x - 100.0;
-        let grid_index = if relative_x <= 0.0 {
-            0
-        } else {
-            (relative_x / GRID_UNIT).round() as i32
-        };
+    /// Registers the rendered panel of a module type, used when plugins are
+    /// recreated from saved state.
+    pub fn set_panel_texture(&mut self, slug: &'static str, texture: egui::TextureHandle) {
+        self.panel_textures.insert(slug, texture);
+    }
 
-        let grid_x = 100.0 + (grid_index as f32 * GRID_UNIT);
-        let rail_index = ((position.y - 100.0) / RAIL_HEIGHT).round() as i32;
-        let grid_y = 100.0 + (rail_index as f32 * RAIL_HEIGHT);
+    pub fn panel_texture(&self, slug: &str) -> Option<egui::TextureHandle> {
+        self.panel_textures.get(slug).cloned()
+    }
 
-        // Check for existing plugins using grid position
-        for plugin in &self.plugins {
-            if plugin.is_at_grid_position(grid_x, grid_y) {
-                #[cfg(not(test))]
-                println!("Cannot add plugin: grid position already occupied on this rail");
-                return;
+    /// Shows the light brightness the engine reported for each module.
+    pub fn set_lights(&mut self, lights: &[(ModuleId, Vec<f32>)]) {
+        for (id, brightness) in lights {
+            if let Some(plugin) = self.plugins.iter_mut().find(|p| p.id == *id) {
+                if plugin.lights.len() == brightness.len() {
+                    plugin.lights.clone_from(brightness);
+                }
             }
         }
+    }
 
-        let id = self.next_id;
-        self.next_id += 1;
+    /// Gives every plugin the currently registered panel of its module type,
+    /// e.g. after the panels were rendered again for a new zoom level.
+    pub fn refresh_plugin_textures(&mut self) {
+        for plugin in &mut self.plugins {
+            if let Some(texture) = self.panel_textures.get(plugin.descriptor.slug) {
+                plugin.texture = Some(texture.clone());
+            }
+        }
+    }
+
+    /// Adds a blank plate.
+    pub fn add_plugin(&mut self, position: egui::Pos2, texture: Option<egui::TextureHandle>) {
+        self.add_module(modules::blank_plate(), position, texture);
+    }
+
+    /// Adds a module of the given type and returns its id, or `None` when the
+    /// grid position is already occupied.
+    pub fn add_module(&mut self, descriptor: &'static ModuleDescriptor, position: egui::Pos2, texture: Option<egui::TextureHandle>) -> Option<ModuleId> {
+        let grid = RackGeometry::snap_to_grid(position).on_rack(descriptor.hp);
+
+        // The whole width of the new panel has to be free on this rail
+        if !self.is_span_free(grid.rail, grid.column, descriptor.hp, None) {
+            #[cfg(not(test))]
+            println!("Cannot add plugin: grid position already occupied on this rail");
+            return None;
+        }
+
+        let id = self.allocate_id(&[]);
 
         // Create new plugin and ensure it's not selected
-        let mut new_plugin = Plugin::new(position, texture, id);
+        let mut new_plugin = Plugin::with_module(descriptor, position, texture, id);
         new_plugin.set_selected(false);
+        self.history.push(Command::AddPlugin(new_plugin.clone()));
         self.plugins.push(new_plugin);
 
         #[cfg(not(test))]
-        println!("Added plugin at position: {:?}", position);
+        println!("Added {} at position: {:?}", descriptor.slug, position);
+        Some(id)
+    }
+
+    /// Checks that `hp` columns starting at `column` are on `rail` and that
+    /// no plugin other than `ignore_id` overlaps them.
+    pub fn is_span_free(&self, rail: i32, column: i32, hp: u32, ignore_id: Option<ModuleId>) -> bool {
+        column >= 0 && column + hp as i32 <= RAIL_HP && !self.plugins.iter()
+            .filter(|p| Some(p.id) != ignore_id)
+            .any(|p| p.occupies(rail, column, hp))
+    }
+
+    /// Selected plugins and the cables between them, or `None` when nothing is selected.
+    pub fn selection(&self) -> Option<Selection> {
+        let plugins: Vec<PluginState> = self.get_selected_plugins().iter().map(|p| p.to_state()).collect();
+        (!plugins.is_empty()).then(|| Selection::new(plugins, &self.cables))
+    }
+
+    /// Places a copy of `selection` with fresh ids as close to `at` as it
+    /// fits without overlapping other plugins, keeping its layout, as one
+    /// undo step. The copies become the selection. Returns their ids, none
+    /// when the selection doesn't fit on the rails.
+    pub fn insert_selection(&mut self, selection: &Selection, at: GridPos) -> Vec<ModuleId> {
+        let Some(origin) = selection.free_position(at, |rail, column, hp| self.is_span_free(rail, column, hp, None)) else {
+            return Vec::new();
+        };
+        let mut ids = Vec::with_capacity(selection.plugins.len());
+        for _ in &selection.plugins {
+            let id = self.allocate_id(&ids);
+            ids.push(id);
+        }
+        let placed = selection.placed(origin, &ids, self.next_cable_id);
+        self.deselect_all();
+
+        let plugins: Vec<Plugin> = placed.plugins.into_iter()
+            .map(|state| {
+                let mut plugin = Plugin::from_state(PluginState { selected: true, ..state }, None);
+                plugin.texture = self.panel_textures.get(plugin.descriptor.slug).cloned();
+                plugin
+            })
+            .collect();
+        self.plugins.extend(plugins.iter().cloned());
+        // Cables to ports the modules don't have are left out
+        let cables: Vec<Cable> = placed.cables.into_iter()
+            .filter(|cable| self.insert_cable(*cable).is_some())
+            .collect();
+
+        let ids = plugins.iter().map(|p| p.id).collect();
+        self.history.push(Command::AddPlugins { plugins, cables });
+        ids
     }
 
     pub fn delete_plugin(&mut self, pos: egui::Pos2, zoom_level: f32) {
-        if let Some(index) = self.plugins.iter().position(|p| p.is_at_position(pos, zoom_level)) {
-            self.plugins.remove(index);
+        if let Some(id) = self.get_plugin_at_position(pos, zoom_level).map(|p| p.id) {
+            self.delete_plugins(&[id]);
+        }
+    }
+
+    /// Deletes the plugins with the given ids and the cables plugged into
+    /// them as one undoable step. Returns true when anything was deleted.
+    pub fn delete_plugins(&mut self, ids: &[ModuleId]) -> bool {
+        let plugins: Vec<Plugin> = self.plugins.iter()
+            .filter(|p| ids.contains(&p.id))
+            .cloned()
+            .collect();
+        if plugins.is_empty() {
+            return false;
+        }
+        let cables: Vec<Cable> = self.cables.iter()
+            .filter(|c| ids.contains(&c.output.module_id) || ids.contains(&c.input.module_id))
+            .copied()
+            .collect();
+        for plugin in &plugins {
+            self.remove_plugin(plugin.id);
+        }
+        self.history.push(Command::DeletePlugins { plugins, cables });
+        true
+    }
+
+    /// Removes a plugin and its cables without recording it.
+    pub(crate) fn remove_plugin(&mut self, id: ModuleId) {
+        self.plugins.retain(|p| p.id != id);
+        self.remove_dangling_cables();
+    }
+
+    /// Puts a plugin back on the rack without recording it.
+    pub(crate) fn restore_plugin(&mut self, plugin: &Plugin) {
+        if self.get_plugin(plugin.id).is_some() {
+            return;
         }
+        self.plugins.push(Plugin { selected: false, ..plugin.clone() });
     }
 
     pub fn select_plugin(&mut self, pos: egui::Pos2, zoom_level: f32) {
-        if let Some(_) = self.get_plugin_at_position(pos, zoom_level) {
+        if self.get_plugin_at_position(pos, zoom_level).is_some() {
             if let Some(plugin) = self.plugins.iter_mut().find(|p| p.is_at_position(pos, zoom_level)) {
                 // Toggle selection if clicking on a selected plugin
                 plugin.set_selected(!plugin.is_selected());
@@ -281,23 +562,43 @@ impl PluginManager {
         self.plugins.is_empty()
     }
 
-    pub fn draw_plugins(&mut self, ui: &mut egui::Ui, zoom_level: f32, ignore_clicks: bool) {
+    /// Draws all plugins and their cables and applies their actions.
+    /// Returns true when the rack changed.
+    pub fn draw_plugins(&mut self, ui: &mut egui::Ui, geometry: &RackGeometry, ignore_clicks: bool) -> bool {
         let mut plugins_to_delete = Vec::new();
-        let mut plugin_to_toggle: Option<usize> = None;
+        let mut plugin_to_toggle: Option<ModuleId> = None;
+        let mut port_drag_started = None;
+        let mut module_drag_started = None;
+        let mut preset_actions = Vec::new();
+        let mut changed = false;
         
         // First pass: Draw plugins and collect actions
         for plugin in self.plugins.iter_mut() {
-            let (response, delete_id) = plugin.draw(ui, zoom_level);
+            let presets = self.presets.get(plugin.descriptor.slug).map_or(&[][..], Vec::as_slice);
+            let plugin_response = plugin.draw(ui, geometry, presets);
             
             // Handle selection on click, but only if we're not ignoring clicks
-            if !ignore_clicks && response.clicked() {
+            if !ignore_clicks && plugin_response.response.clicked() {
                 #[cfg(not(test))]
                 println!("Click detected on plugin {}", plugin.id);
                 plugin_to_toggle = Some(plugin.id);
             }
+            if !ignore_clicks && plugin_response.response.drag_started() {
+                module_drag_started = Some(plugin.id);
+            }
             
-            if let Some(id) = delete_id {
-                plugins_to_delete.push(id);
+            if plugin_response.delete_requested {
+                plugins_to_delete.push(plugin.id);
+            }
+            if let Some((kind, port)) = plugin_response.port_drag_started {
+                port_drag_started = Some((kind, PortRef::new(plugin.id, port)));
+            }
+            if let Some(action) = plugin_response.preset_action {
+                preset_actions.push((plugin.id, action));
+            }
+            for (index, from, to) in plugin_response.param_changes {
+                self.history.push_mergeable(Command::SetParam { id: plugin.id, index, from, to });
+                changed = true;
             }
         }
         
@@ -315,30 +616,422 @@ impl PluginManager {
             }
         }
         
+        // Move the dragged plugins with the pointer
+        if let Some(id) = module_drag_started {
+            if let Some(pointer) = ui.input(|i| i.pointer.press_origin()) {
+                self.begin_module_drag(id, geometry.to_rack(pointer));
+            }
+        }
+        if self.module_drag.is_some() {
+            if let Some(pointer) = ui.input(|i| i.pointer.interact_pos()) {
+                self.drag_modules(geometry.to_rack(pointer));
+            }
+            if ui.input(|i| i.pointer.any_released()) {
+                changed |= self.end_module_drag().is_some();
+            }
+        }
+
+        // Cables are drawn last so they hang in front of the panels
+        if let Some((kind, port)) = port_drag_started {
+            let ctrl = ui.input(|i| i.modifiers.command);
+            self.begin_cable_drag(kind, port, ctrl);
+        }
+        changed |= self.draw_cables(ui, geometry);
+
+        // A knob gesture ends when the mouse button is let go
+        if ui.input(|i| i.pointer.any_released()) {
+            self.history.seal();
+        }
+
+        for (id, action) in preset_actions {
+            changed |= self.apply_preset_action(id, action);
+        }
+
         // Finally: Remove deleted plugins
-        self.plugins.retain(|plugin| !plugins_to_delete.contains(&plugin.id));
+        changed |= self.delete_plugins(&plugins_to_delete);
+        changed
     }
 
     pub fn delete_selected_plugins(&mut self) {
-        self.plugins.retain(|plugin| !plugin.selected);
+        let ids: Vec<ModuleId> = self.plugins.iter().filter(|p| p.selected).map(|p| p.id).collect();
+        self.delete_plugins(&ids);
+    }
+
+    /// Starts dragging the selection from `pointer`. Dragging a plugin that
+    /// is not selected selects only that plugin first.
+    pub fn begin_module_drag(&mut self, id: ModuleId, pointer: egui::Pos2) {
+        if !self.get_plugin(id).is_some_and(|p| p.selected) {
+            for plugin in &mut self.plugins {
+                plugin.selected = plugin.id == id;
+            }
+        }
+        self.module_drag = Some(ModuleDrag {
+            origin: self.plugins.iter().map(|p| (p.id, p.position)).collect(),
+            moving: self.plugins.iter().filter(|p| p.selected).map(|p| p.id).collect(),
+            start_pointer: pointer,
+        });
+    }
+
+    /// Lays the rack out as if the dragged plugins had been moved from where
+    /// the drag started to `pointer`, snapped to whole HP and rails, pushing
+    /// neighbours aside.
+    pub fn drag_modules(&mut self, pointer: egui::Pos2) {
+        let Some(drag) = &self.module_drag else {
+            return;
+        };
+        for plugin in &mut self.plugins {
+            if let Some((_, position)) = drag.origin.iter().find(|(id, _)| *id == plugin.id) {
+                plugin.position = *position;
+            }
+        }
+
+        let moving: Vec<&Plugin> = self.plugins.iter().filter(|p| drag.moving.contains(&p.id)).collect();
+        let (Some(min_column), Some(max_end), Some(min_rail), Some(max_rail)) = (
+            moving.iter().map(|p| p.grid_column()).min(),
+            moving.iter().map(|p| p.span().end()).max(),
+            moving.iter().map(|p| p.rail_index()).min(),
+            moving.iter().map(|p| p.rail_index()).max(),
+        ) else {
+            return;
+        };
+        let delta = pointer - drag.start_pointer;
+        let column_offset = ((delta.x / GRID_UNIT).round() as i32).clamp(-min_column, (RAIL_HP - max_end).max(-min_column));
+        let rail_offset = ((delta.y / RAIL_HEIGHT).round() as i32).clamp(-min_rail, RAIL_COUNT - 1 - max_rail);
+        if column_offset == 0 && rail_offset == 0 {
+            return;
+        }
+
+        let targets: Vec<(i32, Span)> = moving.iter()
+            .map(|p| (p.rail_index() + rail_offset, Span::new(p.id, p.grid_column() + column_offset, p.hp())))
+            .collect();
+        let mut rails: Vec<i32> = targets.iter().map(|(rail, _)| *rail).collect();
+        rails.sort_unstable();
+        rails.dedup();
+
+        let mut layout = Vec::new();
+        for rail in rails {
+            let moving_spans: Vec<Span> = targets.iter()
+                .filter(|(r, _)| *r == rail)
+                .map(|(_, span)| *span)
+                .collect();
+            let others: Vec<Span> = self.plugins.iter()
+                .filter(|p| p.rail_index() == rail && !drag.moving.contains(&p.id))
+                .map(|p| p.span())
+                .collect();
+            // No room for the neighbours on this rail; the plugins stay where
+            // the drag started
+            let Some(pushed) = placement::push_aside(&moving_spans, &others) else {
+                return;
+            };
+            layout.extend(pushed.into_iter().map(|span| (rail, span)));
+            layout.extend(moving_spans.into_iter().map(|span| (rail, span)));
+        }
+
+        for (rail, span) in layout {
+            if let Some(plugin) = self.plugins.iter_mut().find(|p| p.id == span.id) {
+                if plugin.rail_index() != rail || plugin.grid_column() != span.column {
+                    plugin.set_grid_position(rail, span.column);
+                }
+            }
+        }
+    }
+
+    /// Finishes dragging and returns the moves it made, or `None` when
+    /// nothing ended up somewhere else.
+    pub fn end_module_drag(&mut self) -> Option<Vec<PluginMove>> {
+        let drag = self.module_drag.take()?;
+        let moves: Vec<PluginMove> = drag.origin.iter()
+            .filter_map(|(id, from)| {
+                let to = self.get_plugin(*id)?.position;
+                (to != *from).then_some(PluginMove { id: *id, from: *from, to })
+            })
+            .collect();
+        if moves.is_empty() {
+            return None;
+        }
+        self.history.push(Command::MovePlugins(moves.clone()));
+        Some(moves)
+    }
+
+    pub fn is_dragging_modules(&self) -> bool {
+        self.module_drag.is_some()
+    }
+
+    /// Puts every plugin in `moves` at its destination.
+    pub fn apply_moves(&mut self, moves: &[PluginMove]) {
+        for plugin_move in moves {
+            if let Some(plugin) = self.plugins.iter_mut().find(|p| p.id == plugin_move.id) {
+                plugin.position = plugin_move.to;
+            }
+        }
+    }
+
+    /// Puts every plugin in `moves` back where it came from.
+    pub fn revert_moves(&mut self, moves: &[PluginMove]) {
+        for plugin_move in moves {
+            if let Some(plugin) = self.plugins.iter_mut().find(|p| p.id == plugin_move.id) {
+                plugin.position = plugin_move.from;
+            }
+        }
+    }
+
+    pub fn get_cables(&self) -> &[Cable] {
+        &self.cables
+    }
+
+    pub fn get_cable(&self, id: usize) -> Option<&Cable> {
+        self.cables.iter().find(|c| c.id == id)
+    }
+
+    fn port_exists(&self, kind: PortKind, port: PortRef) -> bool {
+        self.get_plugin(port.module_id).is_some_and(|plugin| {
+            let ports = match kind {
+                PortKind::Input => plugin.descriptor.inputs,
+                PortKind::Output => plugin.descriptor.outputs,
+            };
+            port.port < ports.len()
+        })
+    }
+
+    /// Connects an output to an input with the next palette colour. Returns the
+    /// cable id, or `None` if a port does not exist or the input is already patched.
+    pub fn add_cable(&mut self, output: PortRef, input: PortRef) -> Option<usize> {
+        let color = CABLE_COLORS[self.next_cable_id % CABLE_COLORS.len()];
+        let cable = Cable { id: self.next_cable_id, output, input, color };
+        let id = self.insert_cable(cable)?;
+        self.history.push(Command::AddCable(cable));
+        Some(id)
+    }
+
+    /// Plugs a cable back in without recording it.
+    pub(crate) fn restore_cable(&mut self, cable: Cable) -> Option<usize> {
+        self.insert_cable(cable)
+    }
+
+    fn insert_cable(&mut self, cable: Cable) -> Option<usize> {
+        if !self.port_exists(PortKind::Output, cable.output) || !self.port_exists(PortKind::Input, cable.input) {
+            return None;
+        }
+        // An input accepts a single cable, outputs can fan out
+        if self.cables.iter().any(|c| c.input == cable.input) {
+            return None;
+        }
+        self.next_cable_id = self.next_cable_id.max(cable.id + 1);
+        self.cables.push(cable);
+        Some(cable.id)
+    }
+
+    pub fn remove_cable(&mut self, id: usize) -> Option<Cable> {
+        let cable = self.take_cable(id)?;
+        self.history.push(Command::RemoveCable(cable));
+        Some(cable)
+    }
+
+    /// Unplugs a cable without recording it.
+    pub(crate) fn take_cable(&mut self, id: usize) -> Option<Cable> {
+        let index = self.cables.iter().position(|c| c.id == id)?;
+        Some(self.cables.remove(index))
+    }
+
+    fn remove_dangling_cables(&mut self) {
+        let plugins = &self.plugins;
+        self.cables.retain(|cable| {
+            plugins.iter().any(|p| p.id == cable.output.module_id)
+                && plugins.iter().any(|p| p.id == cable.input.module_id)
+        });
+    }
+
+    /// Finds the port under `pos`, in rack coordinates.
+    pub fn port_at(&self, pos: egui::Pos2, _zoom_level: f32) -> Option<(PortKind, PortRef)> {
+        let radius = widgets::PORT_RADIUS;
+        self.plugins.iter().find_map(|plugin| {
+            [(PortKind::Input, plugin.descriptor.inputs.len()), (PortKind::Output, plugin.descriptor.outputs.len())]
+                .into_iter()
+                .find_map(|(kind, count)| {
+                    (0..count).find(|index| {
+                        plugin.port_center(kind, *index)
+                            .is_some_and(|center| center.distance(pos) <= radius)
+                    })
+                    .map(|index| (kind, PortRef::new(plugin.id, index)))
+                })
+        })
+    }
+
+    fn port_position(&self, kind: PortKind, port: PortRef) -> Option<egui::Pos2> {
+        self.get_plugin(port.module_id)?.port_center(kind, port.port)
+    }
+
+    /// Starts dragging a cable. Dragging from a patched input picks up that
+    /// cable's plug; Ctrl-dragging from a patched output picks up its newest cable.
+    pub fn begin_cable_drag(&mut self, kind: PortKind, port: PortRef, ctrl: bool) {
+        let picked_up = match kind {
+            PortKind::Input => self.cables.iter().rposition(|c| c.input == port),
+            PortKind::Output if ctrl => self.cables.iter().rposition(|c| c.output == port),
+            PortKind::Output => None,
+        };
+
+        self.cable_drag = Some(match picked_up {
+            Some(index) => {
+                let cable = self.cables.remove(index);
+                // The end that was not grabbed stays plugged in
+                let anchor_kind = match kind {
+                    PortKind::Input => PortKind::Output,
+                    PortKind::Output => PortKind::Input,
+                };
+                CableDrag { anchor_kind, anchor: cable.end(anchor_kind), rerouted: Some(cable) }
+            }
+            None => CableDrag { anchor_kind: kind, anchor: port, rerouted: None },
+        });
+    }
+
+    /// Drops the dragged cable at `pos`. Returns true when the patch changed.
+    pub fn end_cable_drag(&mut self, pos: Option<egui::Pos2>, zoom_level: f32) -> bool {
+        let Some(drag) = self.cable_drag.take() else {
+            return false;
+        };
+        let target = pos.and_then(|pos| self.port_at(pos, zoom_level))
+            .filter(|(kind, _)| *kind != drag.anchor_kind);
+
+        match (target, drag.rerouted) {
+            (Some((kind, port)), rerouted) => {
+                let (output, input) = match kind {
+                    PortKind::Input => (drag.anchor, port),
+                    PortKind::Output => (port, drag.anchor),
+                };
+                match rerouted {
+                    Some(from) => {
+                        let to = Cable { output, input, ..from };
+                        if self.insert_cable(to).is_none() {
+                            // Input already taken: put the cable back where it was
+                            self.insert_cable(from);
+                            return false;
+                        }
+                        if to == from {
+                            return false;
+                        }
+                        self.history.push(Command::RerouteCable { from, to });
+                        true
+                    }
+                    None => self.add_cable(output, input).is_some(),
+                }
+            }
+            // Dropping a picked up cable on empty space unplugs it
+            (None, Some(cable)) => {
+                self.history.push(Command::RemoveCable(cable));
+                true
+            }
+            (None, None) => false,
+        }
+    }
+
+    fn draw_cables(&mut self, ui: &mut egui::Ui, geometry: &RackGeometry) -> bool {
+        let scale = geometry.zoom;
+        let painter = ui.painter().clone();
+        let pointer = ui.input(|i| i.pointer.interact_pos());
+        let mut changed = false;
+
+        let mut cable_to_delete = None;
+        for cable in &self.cables {
+            let (Some(start), Some(end)) = (
+                self.port_position(PortKind::Output, cable.output),
+                self.port_position(PortKind::Input, cable.input),
+            ) else {
+                continue;
+            };
+            let (start, end) = (geometry.to_screen(start), geometry.to_screen(end));
+            cable::draw_cable(&painter, start, end, cable.color, scale);
+
+            // Right-click on a cable removes it
+            if let Some(pos) = pointer {
+                if ui.input(|i| i.pointer.button_clicked(egui::PointerButton::Secondary))
+                    && cable::distance_to_cable(pos, start, end, scale) < 6.0 * scale
+                {
+                    cable_to_delete = Some(cable.id);
+                }
+            }
+        }
+        if let Some(id) = cable_to_delete {
+            changed |= self.remove_cable(id).is_some();
+        }
+
+        if let Some(drag) = self.cable_drag {
+            if let (Some(anchor), Some(pos)) = (self.port_position(drag.anchor_kind, drag.anchor), pointer) {
+                let color = drag.rerouted.map_or(CABLE_COLORS[self.next_cable_id % CABLE_COLORS.len()], |c| c.color);
+                cable::draw_cable(&painter, geometry.to_screen(anchor), pos, color, scale);
+            }
+            if ui.input(|i| i.pointer.any_released()) {
+                changed |= self.end_cable_drag(pointer.map(|pos| geometry.to_rack(pos)), geometry.zoom);
+            }
+        }
+        changed
+    }
+
+    pub fn is_dragging_cable(&self) -> bool {
+        self.cable_drag.is_some()
     }
 
     pub fn save_state(&self) -> RackState {
         RackState {
             plugins: self.plugins.iter().map(|p| p.to_state()).collect(),
+            cables: self.cables.clone(),
         }
     }
 
-    pub fn load_state(&mut self, state: RackState, texture: Option<egui::TextureHandle>) {
-        let plugins_len = state.plugins.len();
+    /// Replaces the rack with `state`. Plugins use their registered panel
+    /// texture, or `texture` when none was registered for their module type.
+    /// Ids that are out of range or used twice are replaced; the changes are returned.
+    pub fn load_state(&mut self, mut state: RackState, texture: Option<egui::TextureHandle>) -> Vec<IdRepair> {
+        let repairs = Self::repair_ids(&mut state, &mut self.ids);
         self.plugins = state.plugins.into_iter()
             .map(|p| {
-                let mut plugin = Plugin::from_state(p, texture.clone());
+                let texture = self.panel_textures.get(p.slug.as_str()).cloned().or(texture.clone());
+                let mut plugin = Plugin::from_state(p, texture);
                 plugin.selected = false;  // Ensure all plugins are deselected when loading
                 plugin
             })
             .collect();
-        self.next_id = self.next_id.max(plugins_len);
+
+        // Drop cables that point at missing plugins or ports
+        self.cables.clear();
+        self.cable_drag = None;
+        self.module_drag = None;
+        self.history.clear();
+        for cable in state.cables {
+            if self.insert_cable(cable).is_none() {
+                println!("Skipping invalid cable {}", cable.id);
+            }
+        }
+        repairs
+    }
+
+    fn repair_ids(state: &mut RackState, allocator: &mut IdAllocator) -> Vec<IdRepair> {
+        let ids: Vec<ModuleId> = state.plugins.iter().map(|p| p.id).collect();
+        let (ids, repairs) = module_id::repair_ids(&ids, allocator);
+        for (plugin, id) in state.plugins.iter_mut().zip(ids) {
+            plugin.id = id;
+        }
+
+        let mut moved = HashSet::new();
+        for repair in &repairs {
+            // Cables of a duplicate stay with the first module that has the id,
+            // cables of an out of range id follow its (first) module
+            if repair.from > MAX_MODULE_ID && moved.insert(repair.from) {
+                for cable in &mut state.cables {
+                    for port in [&mut cable.output, &mut cable.input] {
+                        if port.module_id == repair.from {
+                            port.module_id = repair.to;
+                        }
+                    }
+                }
+            }
+        }
+        repairs
+    }
+
+    /// A random id that no plugin on the rack has and that is not `reserved`.
+    fn allocate_id(&mut self, reserved: &[ModuleId]) -> ModuleId {
+        let plugins = &self.plugins;
+        self.ids.allocate(|id| reserved.contains(&id) || plugins.iter().any(|p| p.id == id))
     }
 
     pub fn get_selected_plugins(&self) -> Vec<&Plugin> {
@@ -346,4 +1039,171 @@ impl PluginManager {
             .filter(|p| p.is_selected())
             .collect()
     }
+
+    pub fn get_plugin(&self, id: ModuleId) -> Option<&Plugin> {
+        self.plugins.iter().find(|p| p.id == id)
+    }
+
+    /// Sets a param of the plugin with `id`. Returns false if either does not exist.
+    pub fn set_param(&mut self, id: ModuleId, index: usize, value: f32) -> bool {
+        let Some(from) = self.get_plugin(id).and_then(|p| p.params.get(index).copied()) else {
+            return false;
+        };
+        if !self.restore_param(id, index, value) {
+            return false;
+        }
+        let to = self.get_plugin(id).map_or(from, |p| p.params[index]);
+        if to != from {
+            self.history.push(Command::SetParam { id, index, from, to });
+        }
+        true
+    }
+
+    /// Carries out a preset menu entry. Returns true when params changed.
+    pub fn apply_preset_action(&mut self, id: ModuleId, action: PresetAction) -> bool {
+        match action {
+            PresetAction::Save => {
+                self.preset_save_request = Some(id);
+                false
+            }
+            PresetAction::Load(path) => {
+                let result = Preset::load(&path).map_err(|err| err.to_string())
+                    .and_then(|preset| self.load_preset(id, &preset));
+                result.unwrap_or_else(|err| {
+                    println!("Failed to load preset {}: {}", path.display(), err);
+                    false
+                })
+            }
+            PresetAction::Initialize => self.initialize_params(id),
+            PresetAction::Randomize => self.randomize_params(id, &mut fastrand::Rng::new()),
+        }
+    }
+
+    /// Plugin whose params should be saved as a preset, asked for from its context menu.
+    pub fn take_preset_save_request(&mut self) -> Option<ModuleId> {
+        self.preset_save_request.take()
+    }
+
+    pub fn set_presets(&mut self, slug: &'static str, presets: Vec<PathBuf>) {
+        self.presets.insert(slug, presets);
+    }
+
+    /// Preset files of module type `slug`.
+    pub fn presets(&self, slug: &str) -> &[PathBuf] {
+        self.presets.get(slug).map_or(&[], Vec::as_slice)
+    }
+
+    /// Current params of a plugin as a preset.
+    pub fn preset_of(&self, id: ModuleId) -> Option<Preset> {
+        self.get_plugin(id).map(|plugin| Preset {
+            slug: plugin.descriptor.slug.to_string(),
+            params: plugin.params.clone(),
+        })
+    }
+
+    /// Sets the params of a plugin from `preset`, as one undo step.
+    pub fn load_preset(&mut self, id: ModuleId, preset: &Preset) -> Result<bool, String> {
+        let plugin = self.get_plugin(id).ok_or_else(|| format!("No module with id {}", id))?;
+        let params = preset.params_for(plugin.descriptor)?;
+        Ok(self.replace_params(id, params, "Load preset"))
+    }
+
+    /// Resets every param of a plugin to its default.
+    pub fn initialize_params(&mut self, id: ModuleId) -> bool {
+        let Some(params) = self.get_plugin(id).map(|p| p.descriptor.default_params()) else {
+            return false;
+        };
+        self.replace_params(id, params, "Initialize module")
+    }
+
+    pub fn randomize_params(&mut self, id: ModuleId, rng: &mut fastrand::Rng) -> bool {
+        let Some(params) = self.get_plugin(id).map(|p| preset::random_params(p.descriptor, rng)) else {
+            return false;
+        };
+        self.replace_params(id, params, "Randomize module")
+    }
+
+    /// Sets all params of a plugin as a single command named `name`.
+    /// Returns false when nothing changed.
+    fn replace_params(&mut self, id: ModuleId, to: Vec<f32>, name: &'static str) -> bool {
+        let Some(from) = self.get_plugin(id).map(|p| p.params.clone()) else {
+            return false;
+        };
+        if from == to {
+            return false;
+        }
+        self.restore_params(id, &to);
+        self.history.push(Command::SetParams { id, from, to, name });
+        true
+    }
+
+    /// Sets all params without recording them.
+    pub(crate) fn restore_params(&mut self, id: ModuleId, values: &[f32]) {
+        for (index, &value) in values.iter().enumerate() {
+            self.restore_param(id, index, value);
+        }
+    }
+
+    /// Sets a param without recording it.
+    pub(crate) fn restore_param(&mut self, id: ModuleId, index: usize, value: f32) -> bool {
+        self.plugins.iter_mut()
+            .find(|p| p.id == id)
+            .is_some_and(|plugin| plugin.set_param(index, value))
+    }
+
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+
+    /// Remembers the current rack as saved, so undoing back to it clears the
+    /// unsaved changes marker.
+    pub fn mark_saved(&mut self) {
+        self.history.mark_saved();
+    }
+
+    /// Reverts the last edit. Returns false when there is nothing to undo or
+    /// a drag is in progress.
+    pub fn undo(&mut self) -> bool {
+        if self.is_dragging_cable() || self.is_dragging_modules() {
+            return false;
+        }
+        match self.history.take_undo() {
+            Some(command) => {
+                command.revert(self);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Re-applies the last undone edit. Returns false when there is nothing to
+    /// redo or a drag is in progress.
+    pub fn redo(&mut self) -> bool {
+        if self.is_dragging_cable() || self.is_dragging_modules() {
+            return false;
+        }
+        match self.history.take_redo() {
+            Some(command) => {
+                command.apply(self);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Describes the current rack for the audio engine's processing graph.
+    pub fn graph_spec(&self) -> GraphSpec {
+        GraphSpec {
+            modules: self.plugins.iter()
+                .map(|p| ModuleSpec {
+                    id: p.id,
+                    slug: p.descriptor.slug,
+                    params: p.params.clone(),
+                })
+                .collect(),
+            cables: self.cables.iter()
+                .map(|c| CableSpec { output: c.output, input: c.input })
+                .collect(),
+        }
+    }
 }
\ No newline at end of file
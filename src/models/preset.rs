@@ -0,0 +1,115 @@
+use crate::models::module::{ModuleDescriptor, ParamKind};
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// File extension of module presets, like VCV Rack's `.vcvm` files.
+pub const PRESET_EXTENSION: &str = "vcvm";
+
+/// Param values of one module, stored as a preset file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Preset {
+    /// Module type the preset belongs to.
+    pub slug: String,
+    pub params: Vec<f32>,
+}
+
+/// Preset chosen from a module's context menu.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PresetAction {
+    /// Ask for a name and save the module's params.
+    Save,
+    Load(PathBuf),
+    /// Reset every param to its default.
+    Initialize,
+    Randomize,
+}
+
+impl Preset {
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let json = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&json)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Params for a module of type `descriptor`, clamped to their ranges.
+    /// Params the preset has no value for get their default.
+    pub fn params_for(&self, descriptor: &ModuleDescriptor) -> Result<Vec<f32>, String> {
+        if self.slug != descriptor.slug {
+            return Err(format!("Preset is for {}, not {}", self.slug, descriptor.slug));
+        }
+        Ok(descriptor.params.iter().enumerate()
+            .map(|(index, info)| self.params.get(index).map_or(info.default, |&value| info.clamp(value)))
+            .collect())
+    }
+}
+
+/// Random value for every param: anywhere in range for knobs, a random
+/// position for switches.
+pub fn random_params(descriptor: &ModuleDescriptor, rng: &mut fastrand::Rng) -> Vec<f32> {
+    descriptor.params.iter()
+        .map(|info| match info.kind {
+            ParamKind::Knob => info.min + rng.f32() * (info.max - info.min),
+            ParamKind::Switch => info.clamp(rng.i32(info.min as i32..=info.max as i32) as f32),
+        })
+        .collect()
+}
+
+/// Folder with the presets of module type `slug`.
+pub fn preset_directory(save_dir: &Path, slug: &str) -> PathBuf {
+    save_dir.join("presets").join(slug)
+}
+
+/// Preset files in `dir`, sorted by name.
+pub fn list_presets(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut presets: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == PRESET_EXTENSION))
+        .collect();
+    presets.sort_by_key(|path| preset_name(path).to_lowercase());
+    presets
+}
+
+/// Name shown in the preset menu.
+pub fn preset_name(path: &Path) -> String {
+    path.file_stem().map_or_else(String::new, |stem| stem.to_string_lossy().into_owned())
+}
+
+/// Preset entries of a module context menu. Initialize and Randomize are
+/// greyed out for modules without params.
+pub fn preset_menu(ui: &mut egui::Ui, has_params: bool, presets: &[PathBuf]) -> Option<PresetAction> {
+    let mut action = None;
+    if ui.add_enabled(has_params, egui::Button::new("Save preset...")).clicked() {
+        action = Some(PresetAction::Save);
+    }
+    ui.add_enabled_ui(!presets.is_empty(), |ui| {
+        ui.menu_button("Load preset", |ui| {
+            for path in presets {
+                if ui.button(preset_name(path)).clicked() {
+                    action = Some(PresetAction::Load(path.clone()));
+                }
+            }
+        });
+    });
+    if ui.add_enabled(has_params, egui::Button::new("Initialize")).clicked() {
+        action = Some(PresetAction::Initialize);
+    }
+    if ui.add_enabled(has_params, egui::Button::new("Randomize")).clicked() {
+        action = Some(PresetAction::Randomize);
+    }
+    if action.is_some() {
+        ui.close_menu();
+    }
+    action
+}
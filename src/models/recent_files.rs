@@ -0,0 +1,41 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Number of patch files the File menu remembers.
+pub const MAX_RECENT_FILES: usize = 10;
+
+/// Recently opened or saved patch files, kept between sessions.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RecentFiles {
+    /// Most recently used first.
+    #[serde(default)]
+    pub files: Vec<PathBuf>,
+}
+
+impl RecentFiles {
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let json = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&json)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Moves `file` to the top of the list.
+    pub fn add(&mut self, file: &Path) {
+        self.remove(file);
+        self.files.insert(0, file.to_path_buf());
+        self.files.truncate(MAX_RECENT_FILES);
+    }
+
+    pub fn remove(&mut self, file: &Path) {
+        self.files.retain(|f| f != file);
+    }
+
+    pub fn clear(&mut self) {
+        self.files.clear();
+    }
+}
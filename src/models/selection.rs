@@ -0,0 +1,147 @@
+use crate::models::cable::{Cable, PortRef};
+use crate::models::geometry::{GridPos, RAIL_COUNT, RAIL_HP};
+use crate::models::module_id::ModuleId;
+use crate::models::plugin::PluginState;
+use crate::modules;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// File extension of saved selections, like VCV Rack's `.vcvs` files.
+pub const SELECTION_EXTENSION: &str = "vcvs";
+
+/// Modules and the cables between them, cut loose from the rack so they can
+/// be saved and placed again. Positions are relative to the top left module.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Selection {
+    pub plugins: Vec<PluginState>,
+    #[serde(default)]
+    pub cables: Vec<Cable>,
+}
+
+impl Selection {
+    /// Builds a selection from plugins and cables on the rack. Only cables
+    /// with both ends inside the selection are kept.
+    pub fn new(mut plugins: Vec<PluginState>, cables: &[Cable]) -> Self {
+        let min_rail = plugins.iter().map(|p| p.rail).min().unwrap_or(0);
+        let min_column = plugins.iter().map(|p| p.column).min().unwrap_or(0);
+        for plugin in &mut plugins {
+            plugin.rail -= min_rail;
+            plugin.column -= min_column;
+            plugin.selected = false;
+        }
+        let contains = |id: ModuleId| plugins.iter().any(|p| p.id == id);
+        let cables = cables.iter()
+            .filter(|c| contains(c.output.module_id) && contains(c.input.module_id))
+            .copied()
+            .collect();
+        Self { plugins, cables }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.plugins.is_empty()
+    }
+
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let selection: Self = serde_json::from_str(json)?;
+        if selection.is_empty() {
+            return Err("The selection contains no modules".into());
+        }
+        if !selection.fits_rack() {
+            return Err(format!("The selection doesn't fit on the {} rails of the rack", RAIL_COUNT).into());
+        }
+        Ok(selection)
+    }
+
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// The selection placed with its top left at `origin`. Plugins get
+    /// `new_ids` in order and cable ids count up from `first_cable_id`.
+    pub fn placed(&self, origin: GridPos, new_ids: &[ModuleId], first_cable_id: usize) -> Selection {
+        let mut ids = HashMap::new();
+        let plugins = self.plugins.iter().zip(new_ids)
+            .map(|(p, &id)| {
+                ids.entry(p.id).or_insert(id);
+                PluginState {
+                    rail: origin.rail + p.rail,
+                    column: origin.column + p.column,
+                    id,
+                    ..p.clone()
+                }
+            })
+            .collect();
+        let cables = self.cables.iter()
+            .filter_map(|c| Some((ids.get(&c.output.module_id)?, ids.get(&c.input.module_id)?, c)))
+            .enumerate()
+            .map(|(index, (&output, &input, c))| Cable {
+                id: first_cable_id + index,
+                output: PortRef::new(output, c.output.port),
+                input: PortRef::new(input, c.input.port),
+                color: c.color,
+            })
+            .collect();
+        Selection { plugins, cables }
+    }
+
+    /// Rails the selection covers.
+    pub fn height(&self) -> i32 {
+        self.plugins.iter().map(|p| p.rail + 1).max().unwrap_or(0)
+    }
+
+    /// HP the selection covers.
+    pub fn width(&self) -> i32 {
+        self.spans().map(|(_, column, hp)| column + hp as i32).max().unwrap_or(0)
+    }
+
+    /// Whether the selection can go on the rack: no taller than the rails,
+    /// no wider than them and, since positions are relative to the top left,
+    /// none negative.
+    pub fn fits_rack(&self) -> bool {
+        self.plugins.iter().all(|p| p.rail >= 0 && p.column >= 0)
+            && self.height() <= RAIL_COUNT
+            && self.width() <= RAIL_HP
+    }
+
+    /// (rail, column, hp) of every module relative to the top left.
+    fn spans(&self) -> impl Iterator<Item = (i32, i32, u32)> + '_ {
+        self.plugins.iter().map(|p| {
+            let hp = modules::find(&p.slug).unwrap_or(modules::blank_plate()).hp;
+            (p.rail, p.column, hp)
+        })
+    }
+
+    /// Top left position closest to `at` where the whole selection fits,
+    /// keeping its layout. The selection stays on the rails and moves
+    /// sideways along them, preferring the right on a tie. `None` when the
+    /// selection doesn't fit the rack or there is no room for it there.
+    pub fn free_position(&self, at: GridPos, is_free: impl Fn(i32, i32, u32) -> bool) -> Option<GridPos> {
+        if !self.fits_rack() {
+            return None;
+        }
+        let rail = at.rail.clamp(0, RAIL_COUNT - self.height());
+        let last_column = RAIL_HP - self.width();
+        let column = at.column.clamp(0, last_column);
+        let fits = |column: i32| self.spans().all(|(r, c, hp)| is_free(rail + r, column + c, hp));
+        for distance in 0..=last_column {
+            if column + distance <= last_column && fits(column + distance) {
+                return Some(GridPos::new(rail, column + distance));
+            }
+            if distance > 0 && column - distance >= 0 && fits(column - distance) {
+                return Some(GridPos::new(rail, column - distance));
+            }
+        }
+        None
+    }
+}
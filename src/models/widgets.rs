@@ -0,0 +1,76 @@
+use crate::models::cable::PortKind;
+use crate::models::module::{ParamInfo, ParamKind};
+use eframe::egui;
+
+const KNOB_RADIUS: f32 = 9.0;
+const KNOB_SWEEP: f32 = 0.75 * std::f32::consts::PI;
+/// Vertical drag distance in points that moves a knob across its full range.
+const KNOB_DRAG_RANGE: f32 = 150.0;
+
+/// Draws a param control and handles dragging it. Returns the new value when it changed.
+pub fn param_knob(ui: &mut egui::Ui, id: egui::Id, center: egui::Pos2, scale: f32, info: &ParamInfo, value: f32) -> Option<f32> {
+    let radius = KNOB_RADIUS * scale;
+    let rect = egui::Rect::from_center_size(center, egui::vec2(radius * 2.0, radius * 2.0));
+    let response = ui.interact(rect, id, egui::Sense::click_and_drag())
+        .on_hover_text(format!("{}: {:.2}{}", info.name, value, info.unit));
+
+    let mut new_value = value;
+    match info.kind {
+        ParamKind::Knob => {
+            if response.dragged() {
+                let delta = -response.drag_delta().y / KNOB_DRAG_RANGE * (info.max - info.min);
+                new_value = info.clamp(value + delta);
+            }
+            if response.double_clicked() {
+                new_value = info.default;
+            }
+        }
+        ParamKind::Switch => {
+            if response.clicked() {
+                new_value = if value >= info.max { info.min } else { value + 1.0 };
+            }
+        }
+    }
+
+    let painter = ui.painter();
+    painter.circle(center, radius, egui::Color32::from_gray(40), egui::Stroke::new(1.0, egui::Color32::from_gray(180)));
+    let angle = -KNOB_SWEEP + info.normalize(new_value) * 2.0 * KNOB_SWEEP - std::f32::consts::FRAC_PI_2;
+    let tip = center + egui::vec2(angle.cos(), angle.sin()) * radius * 0.8;
+    painter.line_segment([center, tip], egui::Stroke::new(2.0 * scale, egui::Color32::WHITE));
+
+    (new_value != value).then_some(new_value)
+}
+
+pub const PORT_RADIUS: f32 = 8.0;
+
+/// Draws a jack. Outputs sit on a dark plate so they stand out from inputs.
+pub fn port(ui: &mut egui::Ui, id: egui::Id, center: egui::Pos2, scale: f32, kind: PortKind, name: &str) -> egui::Response {
+    let radius = PORT_RADIUS * scale;
+    let rect = egui::Rect::from_center_size(center, egui::vec2(radius * 2.0, radius * 2.0));
+    let response = ui.interact(rect, id, egui::Sense::drag())
+        .on_hover_text(name);
+
+    let painter = ui.painter();
+    if kind == PortKind::Output {
+        painter.rect_filled(rect.expand(3.0 * scale), 3.0 * scale, egui::Color32::from_gray(50));
+    }
+    let ring = if response.hovered() { egui::Color32::WHITE } else { egui::Color32::from_gray(190) };
+    painter.circle(center, radius, egui::Color32::from_gray(110), egui::Stroke::new(1.5 * scale, ring));
+    painter.circle_filled(center, radius * 0.45, egui::Color32::from_gray(15));
+    response
+}
+
+pub const LIGHT_RADIUS: f32 = 3.0;
+
+/// Draws an LED in `color` at `brightness` (0..1).
+pub fn light(ui: &egui::Ui, center: egui::Pos2, scale: f32, color: (u8, u8, u8), brightness: f32) {
+    let brightness = brightness.clamp(0.0, 1.0);
+    let off = egui::Color32::from_gray(40);
+    let on = egui::Color32::from_rgb(color.0, color.1, color.2);
+    let painter = ui.painter();
+    if brightness > 0.0 {
+        // Glow around a lit LED
+        painter.circle_filled(center, 2.0 * LIGHT_RADIUS * scale, on.gamma_multiply(0.25 * brightness));
+    }
+    painter.circle_filled(center, LIGHT_RADIUS * scale, off.lerp_to_gamma(on, brightness));
+}
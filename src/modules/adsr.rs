@@ -0,0 +1,263 @@
+use crate::dsp::{PulseGenerator, SchmittTrigger};
+use crate::models::module::{LightInfo, Module, ModuleDescriptor, ModuleIo, ParamInfo, PortInfo, ProcessContext, MAX_CHANNELS};
+
+pub const ATTACK_PARAM: usize = 0;
+pub const DECAY_PARAM: usize = 1;
+pub const SUSTAIN_PARAM: usize = 2;
+pub const RELEASE_PARAM: usize = 3;
+/// 0 = linear, 1 = exponential segments.
+pub const CURVE_PARAM: usize = 4;
+
+pub const ATTACK_INPUT: usize = 0;
+pub const DECAY_INPUT: usize = 1;
+pub const SUSTAIN_INPUT: usize = 2;
+pub const RELEASE_INPUT: usize = 3;
+pub const GATE_INPUT: usize = 4;
+pub const RETRIGGER_INPUT: usize = 5;
+
+pub const ENVELOPE_OUTPUT: usize = 0;
+pub const EOC_OUTPUT: usize = 1;
+
+pub const ATTACK_LIGHT: usize = 0;
+pub const DECAY_LIGHT: usize = 1;
+pub const SUSTAIN_LIGHT: usize = 2;
+pub const RELEASE_LIGHT: usize = 3;
+
+/// Shortest and longest stage time in seconds, at the ends of the knobs.
+pub const MIN_TIME: f32 = 1e-3;
+pub const MAX_TIME: f32 = 10.0;
+/// Voltage of a full envelope and of the EOC trigger.
+const LEVEL: f32 = 10.0;
+/// How strongly the exponential segments bend.
+const CURVE: f64 = 4.0;
+const STAGE_LIGHT: (u8, u8, u8) = (80, 220, 120);
+
+pub static DESCRIPTOR: ModuleDescriptor = ModuleDescriptor {
+    slug: "ADSR",
+    name: "ADSR",
+    brand: "Core",
+    tags: &["Envelope Generator", "Polyphonic"],
+    hp: 9,
+    panel_svg: "res/ADSR.svg",
+    params: &[
+        ParamInfo::knob("Attack", 0.0, 1.0, 0.5, "", (34.0, 70.0)),
+        ParamInfo::knob("Decay", 0.0, 1.0, 0.5, "", (34.0, 120.0)),
+        ParamInfo::knob("Sustain", 0.0, 1.0, 0.5, "", (34.0, 170.0)),
+        ParamInfo::knob("Release", 0.0, 1.0, 0.5, "", (34.0, 220.0)),
+        ParamInfo::switch("Curve", 2, 1.0, (34.0, 270.0)),
+    ],
+    inputs: &[
+        PortInfo::new("Attack", (104.0, 70.0)),
+        PortInfo::new("Decay", (104.0, 120.0)),
+        PortInfo::new("Sustain", (104.0, 170.0)),
+        PortInfo::new("Release", (104.0, 220.0)),
+        PortInfo::new("Gate", (21.0, 330.0)),
+        PortInfo::new("Retrigger", (52.0, 330.0)),
+    ],
+    outputs: &[
+        PortInfo::new("Envelope", (85.0, 330.0)),
+        PortInfo::new("End of Cycle", (116.0, 330.0)),
+    ],
+    lights: &[
+        LightInfo::new("Attack", (68.0, 70.0), STAGE_LIGHT),
+        LightInfo::new("Decay", (68.0, 120.0), STAGE_LIGHT),
+        LightInfo::new("Sustain", (68.0, 170.0), STAGE_LIGHT),
+        LightInfo::new("Release", (68.0, 220.0), STAGE_LIGHT),
+    ],
+    create,
+};
+
+fn create() -> Box<dyn Module> {
+    Box::new(Adsr::new())
+}
+
+/// Stage time in seconds of a time knob (0..1), spread exponentially from
+/// `MIN_TIME` to `MAX_TIME`.
+pub fn stage_time(knob: f32) -> f32 {
+    MIN_TIME * (MAX_TIME / MIN_TIME).powf(knob.clamp(0.0, 1.0))
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Stage {
+    #[default]
+    Idle,
+    Attack,
+    Decay,
+    Sustain,
+    Release,
+}
+
+/// Stage times and sustain level for one sample.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EnvelopeSettings {
+    pub attack: f32,
+    pub decay: f32,
+    /// 0..1
+    pub sustain: f32,
+    pub release: f32,
+    pub exponential: bool,
+}
+
+/// Envelope of one polyphonic channel.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Envelope {
+    stage: Stage,
+    level: f32,
+    /// Level the current stage started from.
+    start: f32,
+    /// How far the current stage is, 0..1. Kept in f64 so long stages end
+    /// on the right sample.
+    progress: f64,
+    gate: SchmittTrigger,
+    retrigger: SchmittTrigger,
+    end_of_cycle: PulseGenerator,
+}
+
+impl Envelope {
+    pub fn stage(&self) -> Stage {
+        self.stage
+    }
+
+    /// Level between 0 and 1.
+    pub fn level(&self) -> f32 {
+        self.level
+    }
+
+    fn enter(&mut self, stage: Stage) {
+        self.stage = stage;
+        self.start = self.level;
+        self.progress = 0.0;
+    }
+
+    /// Runs one sample. Returns whether the end of cycle trigger is high.
+    pub fn process(&mut self, gate: f32, retrigger: f32, settings: &EnvelopeSettings, sample_time: f32) -> bool {
+        let gate_rose = self.gate.process(gate);
+        let retriggered = self.retrigger.process(retrigger) && self.gate.is_high();
+        if gate_rose || retriggered {
+            self.enter(Stage::Attack);
+        } else if !self.gate.is_high() && matches!(self.stage, Stage::Attack | Stage::Decay | Stage::Sustain) {
+            self.enter(Stage::Release);
+        }
+
+        match self.stage {
+            Stage::Idle => self.level = 0.0,
+            Stage::Sustain => self.level = settings.sustain,
+            // Attack time is the time of a rise from 0, so a retrigger from
+            // a higher level is quicker
+            Stage::Attack => {
+                self.advance(settings.attack * (1.0 - self.start), 1.0, Stage::Decay, settings, sample_time);
+            }
+            Stage::Decay => {
+                self.advance(settings.decay, settings.sustain, Stage::Sustain, settings, sample_time);
+            }
+            Stage::Release => {
+                if self.advance(settings.release, 0.0, Stage::Idle, settings, sample_time) {
+                    self.end_of_cycle.trigger(PulseGenerator::TRIGGER_DURATION);
+                }
+            }
+        }
+        self.end_of_cycle.process(sample_time)
+    }
+
+    /// Moves the current stage towards `target` so that it takes `duration`
+    /// seconds. Returns true when the stage ended on this sample.
+    fn advance(&mut self, duration: f32, target: f32, next: Stage, settings: &EnvelopeSettings, sample_time: f32) -> bool {
+        let step = if duration > 0.0 { sample_time as f64 / duration as f64 } else { 1.0 };
+        self.progress += step;
+        // End on the sample nearest to the end of the stage
+        if self.progress >= 1.0 - 0.5 * step {
+            self.level = target;
+            self.enter(next);
+            return true;
+        }
+        let shape = if settings.exponential {
+            (1.0 - (-CURVE * self.progress).exp()) / (1.0 - (-CURVE).exp())
+        } else {
+            self.progress
+        };
+        self.level = self.start + (target - self.start) * shape as f32;
+        false
+    }
+}
+
+/// Polyphonic ADSR envelope generator with gate and retrigger inputs, CV
+/// for every stage, linear or exponential segments and an end of cycle
+/// trigger when the release is over.
+pub struct Adsr {
+    io: ModuleIo,
+    envelopes: [Envelope; MAX_CHANNELS],
+}
+
+impl Adsr {
+    pub fn new() -> Self {
+        Self {
+            io: ModuleIo::new(&DESCRIPTOR),
+            envelopes: [Envelope::default(); MAX_CHANNELS],
+        }
+    }
+
+    pub fn envelope(&self, channel: usize) -> &Envelope {
+        &self.envelopes[channel]
+    }
+}
+
+impl Default for Adsr {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Module for Adsr {
+    fn descriptor(&self) -> &'static ModuleDescriptor {
+        &DESCRIPTOR
+    }
+
+    fn io(&self) -> &ModuleIo {
+        &self.io
+    }
+
+    fn io_mut(&mut self) -> &mut ModuleIo {
+        &mut self.io
+    }
+
+    fn process(&mut self, ctx: &ProcessContext) {
+        let params = &self.io.params;
+        let inputs = &self.io.inputs;
+        let channels = inputs[GATE_INPUT].channels.max(1);
+        // Every input adds 10V across the range of its knob
+        let knob = |param: usize, input: usize, channel: usize| {
+            (params[param] + inputs[input].poly_voltage(channel) / 10.0).clamp(0.0, 1.0)
+        };
+
+        let mut lights = [0.0; 4];
+        for (channel, envelope) in self.envelopes.iter_mut().enumerate().take(channels) {
+            let settings = EnvelopeSettings {
+                attack: stage_time(knob(ATTACK_PARAM, ATTACK_INPUT, channel)),
+                decay: stage_time(knob(DECAY_PARAM, DECAY_INPUT, channel)),
+                sustain: knob(SUSTAIN_PARAM, SUSTAIN_INPUT, channel),
+                release: stage_time(knob(RELEASE_PARAM, RELEASE_INPUT, channel)),
+                exponential: params[CURVE_PARAM] >= 0.5,
+            };
+            let gate = inputs[GATE_INPUT].poly_voltage(channel);
+            let retrigger = inputs[RETRIGGER_INPUT].poly_voltage(channel);
+            let end_of_cycle = envelope.process(gate, retrigger, &settings, ctx.sample_time);
+
+            self.io.outputs[ENVELOPE_OUTPUT].set_voltage_at(channel, LEVEL * envelope.level());
+            self.io.outputs[EOC_OUTPUT].set_voltage_at(channel, if end_of_cycle { LEVEL } else { 0.0 });
+            let light = match envelope.stage() {
+                Stage::Attack => Some(ATTACK_LIGHT),
+                Stage::Decay => Some(DECAY_LIGHT),
+                Stage::Sustain => Some(SUSTAIN_LIGHT),
+                Stage::Release => Some(RELEASE_LIGHT),
+                Stage::Idle => None,
+            };
+            if let Some(light) = light {
+                lights[light] = 1.0;
+            }
+        }
+        self.io.lights.copy_from_slice(&lights);
+        for output in &mut self.io.outputs {
+            output.set_channels(channels);
+        }
+    }
+}
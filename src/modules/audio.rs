@@ -0,0 +1,93 @@
+use crate::models::module::{LightInfo, Module, ModuleDescriptor, ModuleIo, ParamInfo, Port, PortInfo, ProcessContext};
+
+pub const LEVEL_PARAM: usize = 0;
+
+pub const LEFT_INPUT: usize = 0;
+/// Normalled to the left input, so a mono signal plays on both sides.
+pub const RIGHT_INPUT: usize = 1;
+
+pub const LEFT_LIGHT: usize = 0;
+pub const RIGHT_LIGHT: usize = 1;
+
+/// Voltage that plays at full scale on the audio device.
+pub const FULL_SCALE: f32 = 10.0;
+const METER_LIGHT: (u8, u8, u8) = (80, 220, 120);
+
+pub static DESCRIPTOR: ModuleDescriptor = ModuleDescriptor {
+    slug: "Audio",
+    name: "Audio",
+    brand: "Core",
+    tags: &["External"],
+    hp: 6,
+    panel_svg: "res/Audio.svg",
+    params: &[ParamInfo::knob("Level", 0.0, 2.0, 1.0, "", (45.6, 90.0))],
+    inputs: &[
+        PortInfo::new("Left", (22.0, 330.0)),
+        PortInfo::new("Right", (69.0, 330.0)),
+    ],
+    outputs: &[],
+    lights: &[
+        LightInfo::new("Left", (22.0, 270.0), METER_LIGHT),
+        LightInfo::new("Right", (69.0, 270.0), METER_LIGHT),
+    ],
+    create,
+};
+
+fn create() -> Box<dyn Module> {
+    Box::new(Audio::new())
+}
+
+/// Sum of all voices at an input.
+fn mix_down(port: &Port) -> f32 {
+    (0..port.channels).map(|channel| port.voltage_at(channel)).sum()
+}
+
+/// Sends its inputs to the engine output, and from there to the audio
+/// device. Every voice of a polyphonic cable is mixed in.
+pub struct Audio {
+    io: ModuleIo,
+    frame: [f32; 2],
+}
+
+impl Audio {
+    pub fn new() -> Self {
+        Self {
+            io: ModuleIo::new(&DESCRIPTOR),
+            frame: [0.0; 2],
+        }
+    }
+}
+
+impl Default for Audio {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Module for Audio {
+    fn descriptor(&self) -> &'static ModuleDescriptor {
+        &DESCRIPTOR
+    }
+
+    fn io(&self) -> &ModuleIo {
+        &self.io
+    }
+
+    fn io_mut(&mut self) -> &mut ModuleIo {
+        &mut self.io
+    }
+
+    fn process(&mut self, _ctx: &ProcessContext) {
+        let left = mix_down(&self.io.inputs[LEFT_INPUT]);
+        let right = if self.io.inputs[RIGHT_INPUT].is_connected() { mix_down(&self.io.inputs[RIGHT_INPUT]) } else { left };
+        let gain = self.io.params[LEVEL_PARAM] / FULL_SCALE;
+        // The device clips there anyway
+        self.frame = [(left * gain).clamp(-1.0, 1.0), (right * gain).clamp(-1.0, 1.0)];
+        self.io.lights[LEFT_LIGHT] = self.frame[0].abs();
+        self.io.lights[RIGHT_LIGHT] = self.frame[1].abs();
+    }
+
+    fn audio_output(&self) -> Option<[f32; 2]> {
+        Some(self.frame)
+    }
+}
@@ -0,0 +1,54 @@
+use crate::models::module::{Module, ModuleDescriptor, ModuleIo, ProcessContext};
+
+pub static DESCRIPTOR: ModuleDescriptor = ModuleDescriptor {
+    slug: "Blank",
+    name: "Blank Plate",
+    brand: "Core",
+    tags: &["Blank"],
+    hp: 2,
+    panel_svg: "res/BlankPlatePlugin.svg",
+    params: &[],
+    inputs: &[],
+    outputs: &[],
+    lights: &[],
+    create,
+};
+
+fn create() -> Box<dyn Module> {
+    Box::new(BlankPlate::new())
+}
+
+/// Fills empty rack space. Has no params or ports and does no processing.
+pub struct BlankPlate {
+    io: ModuleIo,
+}
+
+impl BlankPlate {
+    pub fn new() -> Self {
+        Self {
+            io: ModuleIo::new(&DESCRIPTOR),
+        }
+    }
+}
+
+impl Default for BlankPlate {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Module for BlankPlate {
+    fn descriptor(&self) -> &'static ModuleDescriptor {
+        &DESCRIPTOR
+    }
+
+    fn io(&self) -> &ModuleIo {
+        &self.io
+    }
+
+    fn io_mut(&mut self) -> &mut ModuleIo {
+        &mut self.io
+    }
+
+    fn process(&mut self, _ctx: &ProcessContext) {}
+}
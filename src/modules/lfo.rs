@@ -0,0 +1,218 @@
+use crate::dsp::SchmittTrigger;
+use crate::models::module::{LightInfo, Module, ModuleDescriptor, ModuleIo, ParamInfo, PortInfo, ProcessContext};
+use std::f32::consts::{PI, TAU};
+
+pub const RATE_PARAM: usize = 0;
+/// One of the `*_SHAPE` values.
+pub const SHAPE_PARAM: usize = 1;
+/// 0 = bipolar (±5V), 1 = unipolar (0..10V).
+pub const POLARITY_PARAM: usize = 2;
+
+pub const RATE_INPUT: usize = 0;
+pub const RESET_INPUT: usize = 1;
+pub const CLOCK_INPUT: usize = 2;
+
+/// The outputs carry the same shape a quarter of a cycle apart:
+/// 0°, 90°, 180° and 270°.
+pub const PHASE_0_OUTPUT: usize = 0;
+pub const PHASE_90_OUTPUT: usize = 1;
+pub const PHASE_180_OUTPUT: usize = 2;
+pub const PHASE_270_OUTPUT: usize = 3;
+
+pub const RATE_LIGHT: usize = 0;
+
+pub const SINE_SHAPE: usize = 0;
+pub const TRIANGLE_SHAPE: usize = 1;
+pub const SAW_SHAPE: usize = 2;
+pub const SQUARE_SHAPE: usize = 3;
+pub const RANDOM_SHAPE: usize = 4;
+
+/// The outputs are computed once every this many samples and held in
+/// between; an LFO doesn't need audio rate. Reset and clock edges are still
+/// seen on the sample they arrive.
+pub const CONTROL_RATE_DIVIDER: u32 = 16;
+/// Peak voltage of the bipolar outputs.
+const AMPLITUDE: f32 = 5.0;
+
+pub static DESCRIPTOR: ModuleDescriptor = ModuleDescriptor {
+    slug: "LFO",
+    name: "LFO",
+    brand: "Core",
+    tags: &["LFO", "Clock Modulator"],
+    hp: 8,
+    panel_svg: "res/LFO.svg",
+    params: &[
+        ParamInfo::knob("Rate", -8.0, 6.0, 1.0, "oct", (30.0, 80.0)),
+        ParamInfo::switch("Shape", 5, 0.0, (91.0, 80.0)),
+        ParamInfo::switch("Polarity", 2, 0.0, (30.0, 150.0)),
+    ],
+    inputs: &[
+        PortInfo::new("Rate", (22.0, 270.0)),
+        PortInfo::new("Reset", (61.0, 270.0)),
+        PortInfo::new("Clock", (100.0, 270.0)),
+    ],
+    outputs: &[
+        PortInfo::new("0°", (17.0, 330.0)),
+        PortInfo::new("90°", (46.0, 330.0)),
+        PortInfo::new("180°", (75.0, 330.0)),
+        PortInfo::new("270°", (104.0, 330.0)),
+    ],
+    lights: &[LightInfo::new("Rate", (91.0, 150.0), (255, 180, 60))],
+    create,
+};
+
+fn create() -> Box<dyn Module> {
+    Box::new(Lfo::new())
+}
+
+/// Frequency in Hz of the Rate knob plus the 1V/octave rate CV; the knob
+/// is in octaves above 1Hz.
+pub fn rate_freq(rate: f32, cv: f32) -> f32 {
+    (rate + cv).exp2()
+}
+
+/// Value between -1 and 1 of a fixed shape at `phase` (0..1). The random
+/// shape is not fixed and gives 0.
+pub fn waveform(shape: usize, phase: f32) -> f32 {
+    match shape {
+        SINE_SHAPE => (TAU * phase).sin(),
+        // Rises from 0 like the sine
+        TRIANGLE_SHAPE => 1.0 - 4.0 * ((phase + 0.25).fract() - 0.5).abs(),
+        SAW_SHAPE => 2.0 * phase - 1.0,
+        SQUARE_SHAPE => if phase < 0.5 { 1.0 } else { -1.0 },
+        _ => 0.0,
+    }
+}
+
+/// Low frequency oscillator with quadrature outputs, a reset input and a
+/// clock input that its period locks to.
+pub struct Lfo {
+    io: ModuleIo,
+    phase: f32,
+    /// Frequency in Hz as of the last control rate update.
+    freq: f32,
+    /// Samples since the outputs were last computed.
+    elapsed: u32,
+    reset: SchmittTrigger,
+    clock: SchmittTrigger,
+    /// Samples since the last clock edge, `None` before the first one.
+    since_clock: Option<u32>,
+    /// Frequency of the clock once two edges were seen.
+    clock_freq: Option<f32>,
+    /// Random levels at the start of this cycle and the next two; the
+    /// later outputs reach into the next cycle.
+    random: [f32; 3],
+    rng: fastrand::Rng,
+}
+
+impl Lfo {
+    pub fn new() -> Self {
+        let mut rng = fastrand::Rng::new();
+        let random = std::array::from_fn(|_| 2.0 * rng.f32() - 1.0);
+        Self {
+            io: ModuleIo::new(&DESCRIPTOR),
+            phase: 0.0,
+            freq: 0.0,
+            elapsed: 0,
+            reset: SchmittTrigger::default(),
+            clock: SchmittTrigger::default(),
+            since_clock: None,
+            clock_freq: None,
+            random,
+            rng,
+        }
+    }
+
+    pub fn phase(&self) -> f32 {
+        self.phase
+    }
+
+    /// Frequency the clock input locked to, if any.
+    pub fn clock_freq(&self) -> Option<f32> {
+        self.clock_freq
+    }
+
+    /// Smooth random level at `phase`, which may reach into the next cycle.
+    fn random_value(&self, phase: f32) -> f32 {
+        let (from, to, t) = if phase < 1.0 {
+            (self.random[0], self.random[1], phase)
+        } else {
+            (self.random[1], self.random[2], phase - 1.0)
+        };
+        from + (to - from) * 0.5 * (1.0 - (PI * t).cos())
+    }
+
+    /// Computes the frequency and the outputs at the current phase.
+    fn update(&mut self) {
+        let params = &self.io.params;
+        self.freq = self.clock_freq.unwrap_or_else(|| rate_freq(params[RATE_PARAM], self.io.inputs[RATE_INPUT].voltage()));
+
+        let shape = params[SHAPE_PARAM] as usize;
+        let unipolar = params[POLARITY_PARAM] >= 0.5;
+        for (output, offset) in [(PHASE_0_OUTPUT, 0.0), (PHASE_90_OUTPUT, 0.25), (PHASE_180_OUTPUT, 0.5), (PHASE_270_OUTPUT, 0.75)] {
+            let phase = self.phase + offset;
+            let value = if shape == RANDOM_SHAPE { self.random_value(phase) } else { waveform(shape, phase.fract()) };
+            if output == PHASE_0_OUTPUT {
+                self.io.lights[RATE_LIGHT] = 0.5 * (value + 1.0);
+            }
+            let voltage = if unipolar { AMPLITUDE * (value + 1.0) } else { AMPLITUDE * value };
+            self.io.outputs[output].set_voltage(voltage);
+            self.io.outputs[output].set_channels(1);
+        }
+    }
+}
+
+impl Default for Lfo {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Module for Lfo {
+    fn descriptor(&self) -> &'static ModuleDescriptor {
+        &DESCRIPTOR
+    }
+
+    fn io(&self) -> &ModuleIo {
+        &self.io
+    }
+
+    fn io_mut(&mut self) -> &mut ModuleIo {
+        &mut self.io
+    }
+
+    fn process(&mut self, ctx: &ProcessContext) {
+        let reset = self.reset.process(self.io.inputs[RESET_INPUT].voltage());
+        let clock = &self.io.inputs[CLOCK_INPUT];
+        let mut clocked = false;
+        if clock.is_connected() {
+            clocked = self.clock.process(clock.voltage());
+            self.since_clock = self.since_clock.map(|samples| samples.saturating_add(1));
+            if clocked {
+                if let Some(samples) = self.since_clock {
+                    self.clock_freq = Some(ctx.sample_rate / samples as f32);
+                }
+                self.since_clock = Some(0);
+            }
+        } else {
+            self.since_clock = None;
+            self.clock_freq = None;
+        }
+
+        if reset || clocked {
+            // Every clock edge starts a cycle, which keeps the phase locked
+            self.phase = 0.0;
+            self.elapsed = 0;
+        }
+        if self.elapsed == 0 {
+            self.update();
+        }
+        self.elapsed = (self.elapsed + 1) % CONTROL_RATE_DIVIDER;
+
+        self.phase += self.freq * ctx.sample_time;
+        while self.phase >= 1.0 {
+            self.phase -= 1.0;
+            self.random = [self.random[1], self.random[2], 2.0 * self.rng.f32() - 1.0];
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use crate::models::module::{Module, ModuleDescriptor, ModuleIo, ParamInfo, Port, PortInfo, ProcessContext, MAX_CHANNELS};
+
+/// Number of mixer channels.
+pub const CHANNELS: usize = 4;
+
+/// Level of channel `i` is param `LEVEL_PARAM + i`, likewise for pan and mute.
+pub const LEVEL_PARAM: usize = 0;
+pub const PAN_PARAM: usize = LEVEL_PARAM + CHANNELS;
+pub const MUTE_PARAM: usize = PAN_PARAM + CHANNELS;
+pub const MASTER_PARAM: usize = MUTE_PARAM + CHANNELS;
+
+pub const MASTER_CV_INPUT: usize = 0;
+/// Audio input of channel `i` is `IN_INPUT + i`, its level CV `LEVEL_CV_INPUT + i`.
+pub const IN_INPUT: usize = 1;
+pub const LEVEL_CV_INPUT: usize = IN_INPUT + CHANNELS;
+
+pub const LEFT_OUTPUT: usize = 0;
+/// Post-fader output of channel `i` is `CHANNEL_OUTPUT + i`.
+pub const CHANNEL_OUTPUT: usize = 1;
+pub const RIGHT_OUTPUT: usize = CHANNEL_OUTPUT + CHANNELS;
+
+const COLUMNS: [f32; CHANNELS] = [22.0, 56.0, 90.0, 124.0];
+const MASTER_COLUMN: f32 = 160.0;
+
+pub static DESCRIPTOR: ModuleDescriptor = ModuleDescriptor {
+    slug: "Mixer",
+    name: "Mixer",
+    brand: "Core",
+    tags: &["Mixer", "Polyphonic"],
+    hp: 12,
+    panel_svg: "res/Mixer.svg",
+    params: &[
+        ParamInfo::knob("Level 1", 0.0, 1.0, 1.0, "", (COLUMNS[0], 70.0)),
+        ParamInfo::knob("Level 2", 0.0, 1.0, 1.0, "", (COLUMNS[1], 70.0)),
+        ParamInfo::knob("Level 3", 0.0, 1.0, 1.0, "", (COLUMNS[2], 70.0)),
+        ParamInfo::knob("Level 4", 0.0, 1.0, 1.0, "", (COLUMNS[3], 70.0)),
+        ParamInfo::knob("Pan 1", -1.0, 1.0, 0.0, "", (COLUMNS[0], 120.0)),
+        ParamInfo::knob("Pan 2", -1.0, 1.0, 0.0, "", (COLUMNS[1], 120.0)),
+        ParamInfo::knob("Pan 3", -1.0, 1.0, 0.0, "", (COLUMNS[2], 120.0)),
+        ParamInfo::knob("Pan 4", -1.0, 1.0, 0.0, "", (COLUMNS[3], 120.0)),
+        ParamInfo::switch("Mute 1", 2, 0.0, (COLUMNS[0], 165.0)),
+        ParamInfo::switch("Mute 2", 2, 0.0, (COLUMNS[1], 165.0)),
+        ParamInfo::switch("Mute 3", 2, 0.0, (COLUMNS[2], 165.0)),
+        ParamInfo::switch("Mute 4", 2, 0.0, (COLUMNS[3], 165.0)),
+        ParamInfo::knob("Master", 0.0, 1.0, 1.0, "", (MASTER_COLUMN, 70.0)),
+    ],
+    inputs: &[
+        PortInfo::new("Master CV", (MASTER_COLUMN, 215.0)),
+        PortInfo::new("In 1", (COLUMNS[0], 270.0)),
+        PortInfo::new("In 2", (COLUMNS[1], 270.0)),
+        PortInfo::new("In 3", (COLUMNS[2], 270.0)),
+        PortInfo::new("In 4", (COLUMNS[3], 270.0)),
+        PortInfo::new("Level CV 1", (COLUMNS[0], 215.0)),
+        PortInfo::new("Level CV 2", (COLUMNS[1], 215.0)),
+        PortInfo::new("Level CV 3", (COLUMNS[2], 215.0)),
+        PortInfo::new("Level CV 4", (COLUMNS[3], 215.0)),
+    ],
+    outputs: &[
+        PortInfo::new("Left", (MASTER_COLUMN, 270.0)),
+        PortInfo::new("Channel 1", (COLUMNS[0], 330.0)),
+        PortInfo::new("Channel 2", (COLUMNS[1], 330.0)),
+        PortInfo::new("Channel 3", (COLUMNS[2], 330.0)),
+        PortInfo::new("Channel 4", (COLUMNS[3], 330.0)),
+        PortInfo::new("Right", (MASTER_COLUMN, 330.0)),
+    ],
+    lights: &[],
+    create,
+};
+
+fn create() -> Box<dyn Module> {
+    Box::new(Mixer::new())
+}
+
+/// Left and right gain of a pan position from -1 (left) to 1 (right). The
+/// centre sends the signal unchanged to both sides; panning turns the other
+/// side down.
+pub fn pan_gains(pan: f32) -> (f32, f32) {
+    let pan = pan.clamp(-1.0, 1.0);
+    ((1.0 - pan).min(1.0), (1.0 + pan).min(1.0))
+}
+
+/// Gain of a level CV input: 0V to 10V fade from silent to full level, an
+/// unpatched input is full level.
+fn cv_gain(port: &Port, channel: usize) -> f32 {
+    if port.is_connected() {
+        (port.poly_voltage(channel) / 10.0).clamp(0.0, 1.0)
+    } else {
+        1.0
+    }
+}
+
+/// Four channel stereo mixer with level, pan and mute per channel and a
+/// master level. Polyphonic inputs stay polyphonic: voice `n` of every input
+/// is mixed into voice `n` of the stereo outputs.
+pub struct Mixer {
+    io: ModuleIo,
+}
+
+impl Mixer {
+    pub fn new() -> Self {
+        Self {
+            io: ModuleIo::new(&DESCRIPTOR),
+        }
+    }
+}
+
+impl Default for Mixer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Module for Mixer {
+    fn descriptor(&self) -> &'static ModuleDescriptor {
+        &DESCRIPTOR
+    }
+
+    fn io(&self) -> &ModuleIo {
+        &self.io
+    }
+
+    fn io_mut(&mut self) -> &mut ModuleIo {
+        &mut self.io
+    }
+
+    fn process(&mut self, _ctx: &ProcessContext) {
+        let params = &self.io.params;
+        let inputs = &self.io.inputs;
+        let mut left = [0.0; MAX_CHANNELS];
+        let mut right = [0.0; MAX_CHANNELS];
+        let mut mix_channels = 1;
+
+        for i in 0..CHANNELS {
+            let input = &inputs[IN_INPUT + i];
+            let output = &mut self.io.outputs[CHANNEL_OUTPUT + i];
+            let channels = input.channels;
+            let muted = params[MUTE_PARAM + i] >= 0.5;
+            let (left_gain, right_gain) = pan_gains(params[PAN_PARAM + i]);
+            for channel in 0..channels {
+                let level = if muted { 0.0 } else { params[LEVEL_PARAM + i] * cv_gain(&inputs[LEVEL_CV_INPUT + i], channel) };
+                let voltage = input.voltage_at(channel) * level;
+                output.set_voltage_at(channel, voltage);
+                left[channel] += voltage * left_gain;
+                right[channel] += voltage * right_gain;
+            }
+            output.set_channels(channels.max(1));
+            if channels == 0 {
+                output.set_voltage(0.0);
+            }
+            mix_channels = mix_channels.max(channels);
+        }
+
+        for channel in 0..mix_channels {
+            let master = params[MASTER_PARAM] * cv_gain(&inputs[MASTER_CV_INPUT], channel);
+            self.io.outputs[LEFT_OUTPUT].set_voltage_at(channel, left[channel] * master);
+            self.io.outputs[RIGHT_OUTPUT].set_voltage_at(channel, right[channel] * master);
+        }
+        self.io.outputs[LEFT_OUTPUT].set_channels(mix_channels);
+        self.io.outputs[RIGHT_OUTPUT].set_channels(mix_channels);
+    }
+}
@@ -0,0 +1,35 @@
+use crate::models::module::{Module, ModuleDescriptor};
+
+pub mod adsr;
+pub mod audio;
+pub mod blank_plate;
+pub mod lfo;
+pub mod mixer;
+pub mod vca;
+pub mod vcf;
+pub mod vco;
+
+/// Every module type that can be placed on the rack.
+pub static MODULES: &[&ModuleDescriptor] = &[
+    &blank_plate::DESCRIPTOR,
+    &vco::DESCRIPTOR,
+    &vcf::DESCRIPTOR,
+    &adsr::DESCRIPTOR,
+    &vca::DESCRIPTOR,
+    &mixer::DESCRIPTOR,
+    &lfo::DESCRIPTOR,
+    &audio::DESCRIPTOR,
+];
+
+pub fn find(slug: &str) -> Option<&'static ModuleDescriptor> {
+    MODULES.iter().copied().find(|descriptor| descriptor.slug == slug)
+}
+
+pub fn create(slug: &str) -> Option<Box<dyn Module>> {
+    find(slug).map(|descriptor| (descriptor.create)())
+}
+
+/// Descriptor used for plugins created without an explicit module type.
+pub fn blank_plate() -> &'static ModuleDescriptor {
+    &blank_plate::DESCRIPTOR
+}
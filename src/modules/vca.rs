@@ -0,0 +1,118 @@
+use crate::models::module::{Module, ModuleDescriptor, ModuleIo, ParamInfo, Port, PortInfo, ProcessContext};
+
+pub const LEVEL1_PARAM: usize = 0;
+pub const LEVEL2_PARAM: usize = 1;
+
+pub const EXP1_INPUT: usize = 0;
+pub const LIN1_INPUT: usize = 1;
+pub const IN1_INPUT: usize = 2;
+pub const EXP2_INPUT: usize = 3;
+pub const LIN2_INPUT: usize = 4;
+pub const IN2_INPUT: usize = 5;
+
+pub const OUT1_OUTPUT: usize = 0;
+pub const OUT2_OUTPUT: usize = 1;
+
+/// Gain of the exponential input at 10V relative to 0V, before it is
+/// rescaled to 0..1.
+const EXP_BASE: f32 = 50.0;
+
+pub static DESCRIPTOR: ModuleDescriptor = ModuleDescriptor {
+    slug: "VCA",
+    name: "VCA",
+    brand: "Core",
+    tags: &["VCA", "Dual", "Polyphonic"],
+    hp: 6,
+    panel_svg: "res/VCA.svg",
+    params: &[
+        ParamInfo::knob("Level 1", 0.0, 1.0, 1.0, "", (45.6, 70.0)),
+        ParamInfo::knob("Level 2", 0.0, 1.0, 1.0, "", (45.6, 220.0)),
+    ],
+    inputs: &[
+        PortInfo::new("Exponential CV 1", (22.0, 115.0)),
+        PortInfo::new("Linear CV 1", (69.0, 115.0)),
+        PortInfo::new("In 1", (22.0, 155.0)),
+        PortInfo::new("Exponential CV 2", (22.0, 265.0)),
+        PortInfo::new("Linear CV 2", (69.0, 265.0)),
+        PortInfo::new("In 2", (22.0, 330.0)),
+    ],
+    outputs: &[
+        PortInfo::new("Out 1", (69.0, 155.0)),
+        PortInfo::new("Out 2", (69.0, 330.0)),
+    ],
+    lights: &[],
+    create,
+};
+
+fn create() -> Box<dyn Module> {
+    Box::new(Vca::new())
+}
+
+/// Gain of one VCA for its level knob and the voltages at its linear and
+/// exponential CV inputs. An unpatched CV input leaves the gain alone.
+pub fn gain(level: f32, linear: Option<f32>, exponential: Option<f32>) -> f32 {
+    let mut gain = level;
+    if let Some(cv) = linear {
+        gain *= (cv / 10.0).clamp(0.0, 1.0);
+    }
+    if let Some(cv) = exponential {
+        gain *= (EXP_BASE.powf((cv / 10.0).clamp(0.0, 1.0)) - 1.0) / (EXP_BASE - 1.0);
+    }
+    gain
+}
+
+/// Two polyphonic voltage controlled amplifiers, each with a level knob and
+/// a linear and an exponential CV input.
+pub struct Vca {
+    io: ModuleIo,
+}
+
+impl Vca {
+    pub fn new() -> Self {
+        Self {
+            io: ModuleIo::new(&DESCRIPTOR),
+        }
+    }
+}
+
+impl Default for Vca {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Voltage of `channel` at a CV input, or `None` when nothing is patched.
+fn cv(port: &Port, channel: usize) -> Option<f32> {
+    port.is_connected().then(|| port.poly_voltage(channel))
+}
+
+impl Module for Vca {
+    fn descriptor(&self) -> &'static ModuleDescriptor {
+        &DESCRIPTOR
+    }
+
+    fn io(&self) -> &ModuleIo {
+        &self.io
+    }
+
+    fn io_mut(&mut self) -> &mut ModuleIo {
+        &mut self.io
+    }
+
+    fn process(&mut self, _ctx: &ProcessContext) {
+        let sections = [
+            (LEVEL1_PARAM, EXP1_INPUT, LIN1_INPUT, IN1_INPUT, OUT1_OUTPUT),
+            (LEVEL2_PARAM, EXP2_INPUT, LIN2_INPUT, IN2_INPUT, OUT2_OUTPUT),
+        ];
+        for (level, exp, lin, input, output) in sections {
+            let inputs = &self.io.inputs;
+            let channels = inputs[input].channels.max(1);
+            for channel in 0..channels {
+                let gain = gain(self.io.params[level], cv(&inputs[lin], channel), cv(&inputs[exp], channel));
+                let voltage = inputs[input].poly_voltage(channel) * gain;
+                self.io.outputs[output].set_voltage_at(channel, voltage);
+            }
+            self.io.outputs[output].set_channels(channels);
+        }
+    }
+}
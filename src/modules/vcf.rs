@@ -0,0 +1,136 @@
+use crate::dsp::{self, FilterOutputs, LadderFilter, SvfFilter};
+use crate::models::module::{Module, ModuleDescriptor, ModuleIo, ParamInfo, PortInfo, ProcessContext, MAX_CHANNELS};
+
+pub const CUTOFF_PARAM: usize = 0;
+pub const RESONANCE_PARAM: usize = 1;
+pub const CUTOFF_CV_PARAM: usize = 2;
+pub const DRIVE_PARAM: usize = 3;
+/// 0 = 4-pole ladder, 1 = 2-pole state-variable filter.
+pub const MODE_PARAM: usize = 4;
+
+pub const CUTOFF_INPUT: usize = 0;
+pub const RESONANCE_INPUT: usize = 1;
+pub const DRIVE_INPUT: usize = 2;
+pub const IN_INPUT: usize = 3;
+
+pub const LOWPASS_OUTPUT: usize = 0;
+pub const HIGHPASS_OUTPUT: usize = 1;
+pub const BANDPASS_OUTPUT: usize = 2;
+pub const NOTCH_OUTPUT: usize = 3;
+
+/// Voltage that the filter cores see as 1.
+const SIGNAL_LEVEL: f32 = 5.0;
+/// Ladder feedback at full resonance, a little past the point where it
+/// starts to self-oscillate so it keeps ringing.
+const MAX_LADDER_RESONANCE: f32 = 1.1 * LadderFilter::SELF_OSCILLATION;
+/// Tiny offset at the ladder input that lets self-oscillation start
+/// without an input signal, and keeps denormals away.
+const LADDER_OFFSET: f32 = 1e-6;
+
+pub static DESCRIPTOR: ModuleDescriptor = ModuleDescriptor {
+    slug: "VCF",
+    name: "VCF",
+    brand: "Core",
+    tags: &["Filter", "Polyphonic"],
+    hp: 8,
+    panel_svg: "res/VCF.svg",
+    params: &[
+        ParamInfo::knob("Cutoff", 0.0, 1.0, 0.5, "", (30.0, 80.0)),
+        ParamInfo::knob("Resonance", 0.0, 1.0, 0.0, "", (91.0, 80.0)),
+        ParamInfo::knob("Cutoff CV", -1.0, 1.0, 1.0, "", (30.0, 140.0)),
+        ParamInfo::knob("Drive", 0.0, 1.0, 0.0, "", (91.0, 140.0)),
+        ParamInfo::switch("Mode", 2, 0.0, (30.0, 200.0)),
+    ],
+    inputs: &[
+        PortInfo::new("Cutoff", (17.0, 270.0)),
+        PortInfo::new("Resonance", (46.0, 270.0)),
+        PortInfo::new("Drive", (75.0, 270.0)),
+        PortInfo::new("In", (104.0, 270.0)),
+    ],
+    outputs: &[
+        PortInfo::new("Lowpass", (17.0, 330.0)),
+        PortInfo::new("Highpass", (46.0, 330.0)),
+        PortInfo::new("Bandpass", (75.0, 330.0)),
+        PortInfo::new("Notch", (104.0, 330.0)),
+    ],
+    lights: &[],
+    create,
+};
+
+fn create() -> Box<dyn Module> {
+    Box::new(Vcf::new())
+}
+
+/// Cutoff in Hz of the Cutoff knob (0..1, 10 octaves around C4) plus the
+/// 1V/octave cutoff CV.
+pub fn cutoff_freq(knob: f32, cv: f32) -> f32 {
+    dsp::pitch_to_freq(10.0 * knob - 5.0 + cv)
+}
+
+/// Polyphonic resonant filter with a 4-pole ladder and a 2-pole
+/// state-variable core, both with lowpass, highpass, bandpass and notch outputs.
+pub struct Vcf {
+    io: ModuleIo,
+    ladders: [LadderFilter; MAX_CHANNELS],
+    svfs: [SvfFilter; MAX_CHANNELS],
+}
+
+impl Vcf {
+    pub fn new() -> Self {
+        Self {
+            io: ModuleIo::new(&DESCRIPTOR),
+            ladders: [LadderFilter::default(); MAX_CHANNELS],
+            svfs: [SvfFilter::default(); MAX_CHANNELS],
+        }
+    }
+}
+
+impl Default for Vcf {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Module for Vcf {
+    fn descriptor(&self) -> &'static ModuleDescriptor {
+        &DESCRIPTOR
+    }
+
+    fn io(&self) -> &ModuleIo {
+        &self.io
+    }
+
+    fn io_mut(&mut self) -> &mut ModuleIo {
+        &mut self.io
+    }
+
+    fn process(&mut self, ctx: &ProcessContext) {
+        let params = &self.io.params;
+        let ladder = params[MODE_PARAM] < 0.5;
+        let inputs = &self.io.inputs;
+        let channels = inputs[IN_INPUT].channels.max(1);
+
+        for channel in 0..channels {
+            let cv = inputs[CUTOFF_INPUT].poly_voltage(channel) * params[CUTOFF_CV_PARAM];
+            let freq = cutoff_freq(params[CUTOFF_PARAM], cv);
+            let resonance = (params[RESONANCE_PARAM] + inputs[RESONANCE_INPUT].poly_voltage(channel) / 10.0).clamp(0.0, 1.0);
+            let drive = (params[DRIVE_PARAM] + inputs[DRIVE_INPUT].poly_voltage(channel) / 10.0).clamp(0.0, 1.0);
+            // Up to +20dB into the saturating core
+            let input = inputs[IN_INPUT].poly_voltage(channel) / SIGNAL_LEVEL * (1.0 + 9.0 * drive);
+
+            let FilterOutputs { lowpass, highpass, bandpass, notch } = if ladder {
+                self.ladders[channel].process(input + LADDER_OFFSET, freq, resonance * MAX_LADDER_RESONANCE, ctx.sample_time)
+            } else {
+                // Q from 0.5 (no peak) to 50
+                let q = 0.5 * 100f32.powf(resonance);
+                self.svfs[channel].process(input.tanh(), freq, q, ctx.sample_time)
+            };
+            for (output, value) in [(LOWPASS_OUTPUT, lowpass), (HIGHPASS_OUTPUT, highpass), (BANDPASS_OUTPUT, bandpass), (NOTCH_OUTPUT, notch)] {
+                self.io.outputs[output].set_voltage_at(channel, SIGNAL_LEVEL * value);
+            }
+        }
+        for output in &mut self.io.outputs {
+            output.set_channels(channels);
+        }
+    }
+}
@@ -0,0 +1,224 @@
+use crate::dsp::{self, PolyBlep};
+use crate::models::module::{Module, ModuleDescriptor, ModuleIo, ParamInfo, PortInfo, ProcessContext, MAX_CHANNELS};
+use std::f32::consts::TAU;
+
+pub const FREQ_PARAM: usize = 0;
+pub const FINE_PARAM: usize = 1;
+pub const FM_PARAM: usize = 2;
+pub const PW_PARAM: usize = 3;
+pub const PWM_PARAM: usize = 4;
+/// 0 = exponential FM, 1 = linear FM.
+pub const FM_MODE_PARAM: usize = 5;
+
+pub const PITCH_INPUT: usize = 0;
+pub const FM_INPUT: usize = 1;
+pub const SYNC_INPUT: usize = 2;
+pub const PWM_INPUT: usize = 3;
+
+pub const SINE_OUTPUT: usize = 0;
+pub const TRIANGLE_OUTPUT: usize = 1;
+pub const SAW_OUTPUT: usize = 2;
+pub const SQUARE_OUTPUT: usize = 3;
+
+/// Peak voltage of every output.
+const AMPLITUDE: f32 = 5.0;
+/// Highest frequency as a fraction of the sample rate, below Nyquist.
+const MAX_FREQ_RATIO: f32 = 0.45;
+
+pub static DESCRIPTOR: ModuleDescriptor = ModuleDescriptor {
+    slug: "VCO",
+    name: "VCO",
+    brand: "Core",
+    tags: &["Oscillator", "Polyphonic"],
+    hp: 10,
+    panel_svg: "res/VCO.svg",
+    params: &[
+        ParamInfo::knob("Frequency", -54.0, 54.0, 0.0, "st", (38.0, 80.0)),
+        ParamInfo::knob("Fine", -1.0, 1.0, 0.0, "st", (114.0, 80.0)),
+        ParamInfo::knob("FM", -1.0, 1.0, 0.0, "", (38.0, 140.0)),
+        ParamInfo::knob("Pulse Width", 0.01, 0.99, 0.5, "", (114.0, 140.0)),
+        ParamInfo::knob("PWM", -1.0, 1.0, 0.0, "", (114.0, 200.0)),
+        ParamInfo::switch("FM Mode", 2, 0.0, (38.0, 200.0)),
+    ],
+    inputs: &[
+        PortInfo::new("V/Oct", (19.0, 270.0)),
+        PortInfo::new("FM", (57.0, 270.0)),
+        PortInfo::new("Sync", (95.0, 270.0)),
+        PortInfo::new("PWM", (133.0, 270.0)),
+    ],
+    outputs: &[
+        PortInfo::new("Sine", (19.0, 330.0)),
+        PortInfo::new("Triangle", (57.0, 330.0)),
+        PortInfo::new("Saw", (95.0, 330.0)),
+        PortInfo::new("Square", (133.0, 330.0)),
+    ],
+    lights: &[],
+    create,
+};
+
+fn create() -> Box<dyn Module> {
+    Box::new(Vco::new())
+}
+
+/// Naive waveforms of a phase in 0..1, between -1 and 1. Every wave starts
+/// its cycle at its lowest point, except the square which starts high.
+fn sine(phase: f32) -> f32 {
+    -(TAU * phase).cos()
+}
+
+fn triangle(phase: f32) -> f32 {
+    if phase < 0.5 { 4.0 * phase - 1.0 } else { 3.0 - 4.0 * phase }
+}
+
+fn saw(phase: f32) -> f32 {
+    2.0 * phase - 1.0
+}
+
+fn square(phase: f32, pulse_width: f32) -> f32 {
+    if phase < pulse_width { 1.0 } else { -1.0 }
+}
+
+/// Oscillator state of one polyphonic channel.
+#[derive(Debug, Clone, Copy, Default)]
+struct Voice {
+    phase: f32,
+    last_sync: f32,
+    sine: PolyBlep,
+    triangle: PolyBlep,
+    saw: PolyBlep,
+    square: PolyBlep,
+}
+
+impl Voice {
+    /// Moves the phase on from `start` to `end` (fractions of this sample),
+    /// band-limiting the edges and corners it passes.
+    fn advance(&mut self, dt: f32, pulse_width: f32, start: f32, end: f32) {
+        let from = self.phase;
+        let mut to = from + dt * (end - start);
+        // Distance from a point of the cycle to the end of the sample
+        let offset = |phase: f32| 1.0 - (start + (phase - from) / dt);
+
+        if from < pulse_width && to >= pulse_width {
+            self.square.insert_step(offset(pulse_width), -2.0);
+        }
+        if from < 0.5 && to >= 0.5 {
+            self.triangle.insert_kink(offset(0.5), -8.0 * dt);
+        }
+        if to >= 1.0 {
+            let wrap = offset(1.0);
+            self.saw.insert_step(wrap, -2.0);
+            self.square.insert_step(wrap, 2.0);
+            self.triangle.insert_kink(wrap, 8.0 * dt);
+            if to - 1.0 >= pulse_width {
+                self.square.insert_step(offset(1.0 + pulse_width), -2.0);
+            }
+            to -= 1.0;
+        }
+        self.phase = to;
+    }
+
+    /// Restarts the cycle `offset` samples before the end of this sample.
+    fn reset(&mut self, dt: f32, pulse_width: f32, offset: f32) {
+        let phase = self.phase;
+        self.sine.insert_step(offset, sine(0.0) - sine(phase));
+        self.sine.insert_kink(offset, -TAU * dt * (TAU * phase).sin());
+        self.triangle.insert_step(offset, triangle(0.0) - triangle(phase));
+        let slope = if phase < 0.5 { 4.0 * dt } else { -4.0 * dt };
+        self.triangle.insert_kink(offset, 4.0 * dt - slope);
+        self.saw.insert_step(offset, saw(0.0) - saw(phase));
+        self.square.insert_step(offset, square(0.0, pulse_width) - square(phase, pulse_width));
+        self.phase = 0.0;
+    }
+
+    /// Runs one sample. A rising zero crossing of `sync` restarts the cycle
+    /// where it happened between the samples.
+    fn process(&mut self, dt: f32, pulse_width: f32, sync: f32) -> [f32; 4] {
+        let synced = self.last_sync <= 0.0 && sync > 0.0;
+        if synced {
+            let crossing = self.last_sync / (self.last_sync - sync);
+            self.advance(dt, pulse_width, 0.0, crossing);
+            self.reset(dt, pulse_width, 1.0 - crossing);
+            self.advance(dt, pulse_width, crossing, 1.0);
+        } else {
+            self.advance(dt, pulse_width, 0.0, 1.0);
+        }
+        self.last_sync = sync;
+
+        let phase = self.phase;
+        [
+            self.sine.process(sine(phase)),
+            self.triangle.process(triangle(phase)),
+            self.saw.process(saw(phase)),
+            self.square.process(square(phase, pulse_width)),
+        ]
+    }
+}
+
+/// Polyphonic oscillator with 1V/octave pitch, exponential or linear FM,
+/// hard sync and pulse width modulation. The outputs are band-limited with
+/// PolyBLEP, so they are one sample late.
+pub struct Vco {
+    io: ModuleIo,
+    voices: [Voice; MAX_CHANNELS],
+}
+
+impl Vco {
+    pub fn new() -> Self {
+        Self {
+            io: ModuleIo::new(&DESCRIPTOR),
+            voices: [Voice::default(); MAX_CHANNELS],
+        }
+    }
+}
+
+impl Default for Vco {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Module for Vco {
+    fn descriptor(&self) -> &'static ModuleDescriptor {
+        &DESCRIPTOR
+    }
+
+    fn io(&self) -> &ModuleIo {
+        &self.io
+    }
+
+    fn io_mut(&mut self) -> &mut ModuleIo {
+        &mut self.io
+    }
+
+    fn process(&mut self, ctx: &ProcessContext) {
+        let params = &self.io.params;
+        let base_pitch = (params[FREQ_PARAM] + params[FINE_PARAM]) / 12.0;
+        let fm_amount = params[FM_PARAM];
+        let linear_fm = params[FM_MODE_PARAM] >= 0.5;
+        let (pulse_width, pwm_amount) = (params[PW_PARAM], params[PWM_PARAM]);
+        let inputs = &self.io.inputs;
+        let channels = inputs[PITCH_INPUT].channels.max(1);
+
+        for (channel, voice) in self.voices.iter_mut().enumerate().take(channels) {
+            let fm = inputs[FM_INPUT].poly_voltage(channel) * fm_amount;
+            let mut pitch = base_pitch + inputs[PITCH_INPUT].poly_voltage(channel);
+            if !linear_fm {
+                pitch += fm;
+            }
+            let mut freq = dsp::pitch_to_freq(pitch);
+            if linear_fm {
+                freq += dsp::FREQ_C4 * fm;
+            }
+            let dt = freq.clamp(0.0, MAX_FREQ_RATIO * ctx.sample_rate) * ctx.sample_time;
+            let pulse_width = (pulse_width + pwm_amount * inputs[PWM_INPUT].poly_voltage(channel) / 10.0).clamp(0.01, 0.99);
+
+            let waves = voice.process(dt, pulse_width, inputs[SYNC_INPUT].poly_voltage(channel));
+            for (output, wave) in self.io.outputs.iter_mut().zip(waves) {
+                output.set_voltage_at(channel, AMPLITUDE * wave);
+            }
+        }
+        for output in &mut self.io.outputs {
+            output.set_channels(channels);
+        }
+    }
+}
@@ -0,0 +1,226 @@
+#[cfg(test)]
+mod adsr_tests {
+    use crate::engine::{AudioBlock, ProcessGraph};
+    use crate::models::cable::PortRef;
+    use crate::models::geometry::GRID_UNIT;
+    use crate::models::module::{Module, ProcessContext};
+    use crate::models::plugin::PluginManager;
+    use crate::modules::{adsr, vco};
+    use crate::tests::module_support::{assert_mapped, create, SAMPLE_RATE};
+    use eframe::egui;
+
+    /// Knob position of a 10ms stage, 480 frames at 48kHz.
+    const TEN_MS: f32 = 0.25;
+    const STAGE_FRAMES: usize = 480;
+
+    /// One frame of the rendered envelope.
+    struct Frame {
+        envelope: f32,
+        eoc: f32,
+        lights: Vec<f32>,
+    }
+
+    /// ADSR with 10ms stages, sustain at half level and the given curve.
+    fn create_adsr(exponential: bool) -> Box<dyn Module> {
+        let mut envelope = create("ADSR");
+        for param in [adsr::ATTACK_PARAM, adsr::DECAY_PARAM, adsr::RELEASE_PARAM] {
+            envelope.set_param(param, TEN_MS);
+        }
+        envelope.set_param(adsr::SUSTAIN_PARAM, 0.5);
+        envelope.set_param(adsr::CURVE_PARAM, if exponential { 1.0 } else { 0.0 });
+        envelope.io_mut().inputs[adsr::GATE_INPUT].set_channels(1);
+        envelope.io_mut().inputs[adsr::RETRIGGER_INPUT].set_channels(1);
+        envelope
+    }
+
+    /// Runs `frames` samples of a gate and retrigger pattern.
+    fn render(envelope: &mut dyn Module, frames: usize, gate: impl Fn(usize) -> bool, retrigger: impl Fn(usize) -> bool) -> Vec<Frame> {
+        let volts = |high: bool| if high { 10.0 } else { 0.0 };
+        (0..frames)
+            .map(|frame| {
+                envelope.io_mut().inputs[adsr::GATE_INPUT].set_voltage(volts(gate(frame)));
+                envelope.io_mut().inputs[adsr::RETRIGGER_INPUT].set_voltage(volts(retrigger(frame)));
+                envelope.process(&ProcessContext::new(SAMPLE_RATE, frame as u64));
+                Frame {
+                    envelope: envelope.outputs()[adsr::ENVELOPE_OUTPUT].voltage(),
+                    eoc: envelope.outputs()[adsr::EOC_OUTPUT].voltage(),
+                    lights: envelope.lights().to_vec(),
+                }
+            })
+            .collect()
+    }
+
+    /// First frame from `from` on where `condition` holds.
+    fn first(frames: &[Frame], from: usize, condition: impl Fn(&Frame) -> bool) -> usize {
+        from + frames[from..].iter().position(condition).expect("Condition never met")
+    }
+
+    #[test]
+    fn test_adsr_is_registered_and_mapped() {
+        let envelope = create("ADSR");
+        assert_eq!(envelope.lights().len(), 4);
+        assert_eq!(envelope.outputs().len(), 2);
+        let model = assert_mapped("ADSR", "Fundamental", "ADSR");
+        assert_eq!(model.param_index(2), Some(adsr::SUSTAIN_PARAM));
+        assert_eq!(model.vcv_param_id(adsr::CURVE_PARAM), 9);
+        assert_eq!(model.input_index(4), Some(adsr::GATE_INPUT));
+        assert_eq!(model.vcv_output_id(adsr::EOC_OUTPUT), None, "VCV Rack's ADSR has no EOC output");
+    }
+
+    #[test]
+    fn test_stage_time_range() {
+        assert!((adsr::stage_time(0.0) - adsr::MIN_TIME).abs() < 1e-6);
+        assert!((adsr::stage_time(1.0) - adsr::MAX_TIME).abs() < 1e-3);
+        assert!((adsr::stage_time(TEN_MS) - 0.01).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_stage_timing_is_sample_accurate() {
+        let mut envelope = create_adsr(false);
+        let gate_off = 2000;
+        let frames = render(envelope.as_mut(), 3000, |frame| frame < gate_off, |_| false);
+
+        // Attack rises for exactly 480 samples
+        assert!((frames[0].envelope - 10.0 / STAGE_FRAMES as f32).abs() < 1e-4);
+        assert_eq!(first(&frames, 0, |f| f.envelope == 10.0), STAGE_FRAMES - 1);
+        // Decay falls to the sustain level in another 480
+        assert!(frames[STAGE_FRAMES].envelope < 10.0);
+        assert_eq!(first(&frames, STAGE_FRAMES, |f| f.envelope == 5.0), 2 * STAGE_FRAMES - 1);
+        assert!(frames[2 * STAGE_FRAMES - 2].envelope > 5.0);
+        assert_eq!(frames[gate_off - 1].envelope, 5.0, "Holds the sustain level");
+        // Release reaches zero 480 samples after the gate falls
+        let silent = first(&frames, gate_off, |f| f.envelope == 0.0);
+        assert_eq!(silent, gate_off + STAGE_FRAMES - 1);
+        assert!(frames[silent - 1].envelope > 0.0);
+    }
+
+    #[test]
+    fn test_end_of_cycle_trigger() {
+        let mut envelope = create_adsr(false);
+        let frames = render(envelope.as_mut(), 4000, |frame| frame < 1000, |_| false);
+        let release_end = 1000 + STAGE_FRAMES - 1;
+        assert!(frames[..release_end].iter().all(|f| f.eoc == 0.0));
+        assert_eq!(frames[release_end].eoc, 10.0, "Fires on the sample the release ends");
+        let width = frames[release_end..].iter().take_while(|f| f.eoc == 10.0).count();
+        assert!((47..=49).contains(&width), "1ms trigger, {} samples", width);
+        assert!(frames[release_end + 100..].iter().all(|f| f.eoc == 0.0), "Fires once");
+    }
+
+    #[test]
+    fn test_stage_lights() {
+        let mut envelope = create_adsr(false);
+        let frames = render(envelope.as_mut(), 3000, |frame| frame < 2000, |_| false);
+        let lit = |frame: usize| frames[frame].lights.iter().position(|&b| b > 0.0);
+        assert_eq!(lit(100), Some(adsr::ATTACK_LIGHT));
+        assert_eq!(lit(600), Some(adsr::DECAY_LIGHT));
+        assert_eq!(lit(1500), Some(adsr::SUSTAIN_LIGHT));
+        assert_eq!(lit(2100), Some(adsr::RELEASE_LIGHT));
+        assert_eq!(lit(2900), None);
+    }
+
+    #[test]
+    fn test_exponential_curve_bends_but_keeps_its_timing() {
+        let half = STAGE_FRAMES / 2 - 1;
+        let linear = render(create_adsr(false).as_mut(), STAGE_FRAMES, |_| true, |_| false);
+        let exponential = render(create_adsr(true).as_mut(), STAGE_FRAMES, |_| true, |_| false);
+        assert!((linear[half].envelope - 5.0).abs() < 0.05);
+        assert!(exponential[half].envelope > 8.0, "Fast start, slow finish: {} V", exponential[half].envelope);
+        assert_eq!(exponential[STAGE_FRAMES - 1].envelope, 10.0);
+        assert!(exponential[STAGE_FRAMES - 2].envelope < 10.0);
+    }
+
+    #[test]
+    fn test_gate_off_during_attack_releases_from_the_current_level() {
+        let mut envelope = create_adsr(false);
+        let frames = render(envelope.as_mut(), 1000, |frame| frame < 240, |_| false);
+        let peak = frames[239].envelope;
+        assert!((peak - 5.0).abs() < 0.05);
+        assert!(frames[240].envelope < peak);
+        assert_eq!(first(&frames, 240, |f| f.envelope == 0.0), 240 + STAGE_FRAMES - 1);
+    }
+
+    #[test]
+    fn test_retrigger_restarts_the_attack() {
+        let mut envelope = create_adsr(false);
+        let retrigger_at = 1500;
+        let frames = render(envelope.as_mut(), 3000, |frame| frame < 2000, |frame| (retrigger_at..retrigger_at + 10).contains(&frame));
+        assert_eq!(frames[retrigger_at - 1].envelope, 5.0);
+        assert!(frames[retrigger_at].lights[adsr::ATTACK_LIGHT] > 0.0);
+        // Rising from the sustain level takes half of the attack time
+        assert_eq!(first(&frames, retrigger_at, |f| f.envelope == 10.0), retrigger_at + STAGE_FRAMES / 2 - 1);
+
+        // Without a gate a retrigger does nothing
+        let mut envelope = create_adsr(false);
+        let frames = render(envelope.as_mut(), 100, |_| false, |frame| frame > 50);
+        assert!(frames.iter().all(|f| f.envelope == 0.0));
+    }
+
+    #[test]
+    fn test_stage_cv_adds_to_the_knobs() {
+        let mut envelope = create_adsr(false);
+        envelope.set_param(adsr::ATTACK_PARAM, 0.0);
+        envelope.set_param(adsr::SUSTAIN_PARAM, 0.0);
+        let attack = &mut envelope.io_mut().inputs[adsr::ATTACK_INPUT];
+        attack.set_channels(1);
+        attack.set_voltage(10.0 * TEN_MS);
+        let sustain = &mut envelope.io_mut().inputs[adsr::SUSTAIN_INPUT];
+        sustain.set_channels(1);
+        sustain.set_voltage(2.5);
+        let frames = render(envelope.as_mut(), 2000, |_| true, |_| false);
+        assert_eq!(first(&frames, 0, |f| f.envelope == 10.0), STAGE_FRAMES - 1);
+        assert_eq!(frames[1999].envelope, 2.5);
+    }
+
+    #[test]
+    fn test_polyphony_follows_the_gate() {
+        let mut envelope = create_adsr(false);
+        let gate = &mut envelope.io_mut().inputs[adsr::GATE_INPUT];
+        gate.set_channels(2);
+        gate.set_voltage_at(1, 10.0);
+        envelope.process(&ProcessContext::new(SAMPLE_RATE, 0));
+        let output = &envelope.outputs()[adsr::ENVELOPE_OUTPUT];
+        assert_eq!(output.channels, 2);
+        assert_eq!(output.voltage_at(0), 0.0);
+        assert!(output.voltage_at(1) > 0.0);
+        assert!(envelope.lights()[adsr::ATTACK_LIGHT] > 0.0, "Lit while any channel is in the stage");
+    }
+
+    #[test]
+    fn test_gate_pattern_through_the_engine() {
+        let mut manager = PluginManager::new();
+        let oscillator = manager.add_module(&vco::DESCRIPTOR, egui::pos2(100.0, 100.0), None).unwrap();
+        let envelope = manager.add_module(&adsr::DESCRIPTOR, egui::pos2(100.0 + 10.0 * GRID_UNIT, 100.0), None).unwrap();
+        assert_eq!(manager.get_plugin(envelope).unwrap().get_width(), 9.0 * GRID_UNIT);
+        // A square at about 11.6Hz as the gate
+        manager.set_param(oscillator, vco::FREQ_PARAM, -54.0);
+        for param in [adsr::ATTACK_PARAM, adsr::DECAY_PARAM, adsr::RELEASE_PARAM] {
+            manager.set_param(envelope, param, TEN_MS);
+        }
+        manager.add_cable(PortRef::new(oscillator, vco::SQUARE_OUTPUT), PortRef::new(envelope, adsr::GATE_INPUT)).unwrap();
+
+        let mut graph = ProcessGraph::new();
+        graph.sync(&manager.graph_spec());
+        let mut block = AudioBlock::new(1, 2);
+        let (mut gate, mut peak, mut cycles, mut eoc_high, mut lit) = (Vec::new(), 0.0f32, 0, false, false);
+        for _ in 0..48000 {
+            graph.process_block(&mut block, SAMPLE_RATE as u32);
+            gate.push(graph.module(oscillator).unwrap().outputs()[vco::SQUARE_OUTPUT].voltage() > 0.0);
+            let outputs = graph.module(envelope).unwrap().outputs();
+            peak = peak.max(outputs[adsr::ENVELOPE_OUTPUT].voltage());
+            let eoc = outputs[adsr::EOC_OUTPUT].voltage() > 0.0;
+            if eoc && !eoc_high {
+                cycles += 1;
+            }
+            eoc_high = eoc;
+            if gate.len() == 1000 {
+                let lights = graph.lights();
+                manager.set_lights(&lights);
+                lit = manager.get_plugin(envelope).unwrap().lights.iter().any(|&b| b > 0.0);
+            }
+        }
+        let gate_cycles = gate.windows(2).filter(|w| !w[0] && w[1]).count() + usize::from(gate[0]);
+        assert!(gate_cycles > 5 && peak == 10.0);
+        assert!(lit, "The engine lights reach the plugin");
+        assert!(cycles >= gate_cycles - 1 && cycles <= gate_cycles, "{} envelopes for {} gates", cycles, gate_cycles);
+    }
+}
@@ -0,0 +1,65 @@
+#[cfg(test)]
+mod audio_tests {
+    use crate::engine::{AudioEngine, EngineConfig, NullBackend};
+    use crate::models::cable::PortRef;
+    use crate::models::geometry::GRID_UNIT;
+    use crate::models::module::Module;
+    use crate::models::plugin::PluginManager;
+    use crate::modules::{audio, vco};
+    use crate::tests::module_support::{assert_mapped, create, process, set_input, set_voices};
+    use eframe::egui;
+
+    /// Frame sent to the engine after one sample.
+    fn play(module: &mut dyn Module) -> [f32; 2] {
+        process(module);
+        module.audio_output().expect("Audio sends to the engine")
+    }
+
+    #[test]
+    fn test_audio_is_registered_and_mapped() {
+        let audio = create("Audio");
+        assert_eq!(audio.inputs().len(), 2);
+        let model = assert_mapped("Audio", "Core", "AudioInterface2");
+        assert_eq!(model.param_index(0), Some(audio::LEVEL_PARAM));
+        assert_eq!(model.input_index(1), Some(audio::RIGHT_INPUT));
+        assert_eq!(model.output_index(0), None, "audio coming in from the device has no counterpart");
+    }
+
+    #[test]
+    fn test_inputs_are_scaled_to_full_scale() {
+        let mut audio = create("Audio");
+        assert_eq!(play(audio.as_mut()), [0.0, 0.0]);
+        set_input(audio.as_mut(), audio::LEFT_INPUT, 5.0);
+        set_input(audio.as_mut(), audio::RIGHT_INPUT, -2.0);
+        assert_eq!(play(audio.as_mut()), [0.5, -0.2]);
+        audio.set_param(audio::LEVEL_PARAM, 2.0);
+        assert_eq!(play(audio.as_mut()), [1.0, -0.4]);
+        set_input(audio.as_mut(), audio::LEFT_INPUT, 8.0);
+        assert_eq!(play(audio.as_mut())[0], 1.0, "clipped at full scale");
+    }
+
+    #[test]
+    fn test_right_is_normalled_to_left_and_voices_are_mixed() {
+        let mut audio = create("Audio");
+        set_voices(audio.as_mut(), audio::LEFT_INPUT, &[1.0, 2.0, 3.0]);
+        assert_eq!(play(audio.as_mut()), [0.6, 0.6]);
+        assert_eq!(audio.lights()[audio::RIGHT_LIGHT], 0.6);
+    }
+
+    #[test]
+    fn test_patched_oscillator_renders_sound() {
+        let mut manager = PluginManager::new();
+        let oscillator = manager.add_module(&vco::DESCRIPTOR, egui::pos2(100.0, 100.0), None).unwrap();
+        let output = manager.add_module(&audio::DESCRIPTOR, egui::pos2(100.0 + 10.0 * GRID_UNIT, 100.0), None).unwrap();
+        manager.add_cable(PortRef::new(oscillator, vco::SINE_OUTPUT), PortRef::new(output, audio::LEFT_INPUT)).unwrap();
+
+        let mut engine = AudioEngine::new(EngineConfig::default(), Box::new(NullBackend::new()));
+        engine.update_graph(&manager.graph_spec());
+        let block = engine.render_offline(4800);
+        let peak = block.samples.iter().fold(0.0f32, |peak, sample| peak.max(sample.abs()));
+        assert!((peak - 0.5).abs() < 0.01, "the 5V sine peaks at half scale: {}", peak);
+        for frame in block.samples.chunks(2) {
+            assert_eq!(frame[0], frame[1], "mono input plays on both sides");
+        }
+    }
+}
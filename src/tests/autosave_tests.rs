@@ -0,0 +1,166 @@
+#[cfg(test)]
+mod autosave_tests {
+    use crate::app::autosave::{autosave_is_newer, autosave_source, AUTOSAVE_INTERVAL};
+    use crate::app::vcvrack_app::VcvRackApp;
+    use crate::models::geometry::{GridPos, RackGeometry};
+    use crate::models::patch::Patch;
+    use eframe::egui;
+    use std::path::{Path, PathBuf};
+    use std::time::{Duration, SystemTime};
+
+    /// Empty folder outside the save directory.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("vcvrack_autosave_{}_{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn edited_app(ctx: &egui::Context) -> VcvRackApp {
+        let mut app = VcvRackApp::new_test(ctx);
+        app.plugin_manager = Default::default();
+        app.plugin_manager.add_plugin(RackGeometry::grid_to_rack(GridPos::new(2, 4)), None);
+        app.has_unsaved_changes = true;
+        app
+    }
+
+    fn set_modified(path: &Path, time: SystemTime) {
+        std::fs::File::options().write(true).open(path).unwrap().set_modified(time).unwrap();
+    }
+
+    #[test]
+    fn test_autosave_waits_for_interval() {
+        let ctx = egui::Context::default();
+        let mut app = edited_app(&ctx);
+        let path = temp_dir("interval").join("autosave.json");
+        let start = app.last_autosave;
+
+        assert!(!app.autosave_to(&path, start + AUTOSAVE_INTERVAL / 2));
+        assert!(!path.exists());
+        assert!(app.autosave_to(&path, start + AUTOSAVE_INTERVAL));
+        assert!(path.exists());
+        assert!(!app.autosave_to(&path, start + AUTOSAVE_INTERVAL + Duration::from_secs(1)),
+                "The interval starts again after an autosave");
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_nothing_to_autosave_without_changes() {
+        let ctx = egui::Context::default();
+        let mut app = edited_app(&ctx);
+        app.has_unsaved_changes = false;
+        let path = temp_dir("clean").join("autosave.json");
+        assert!(!app.autosave_to(&path, app.last_autosave + AUTOSAVE_INTERVAL * 2));
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_autosave_is_written_atomically() {
+        let ctx = egui::Context::default();
+        let mut app = edited_app(&ctx);
+        let dir = temp_dir("atomic");
+        let path = dir.join("autosave.json");
+        std::fs::write(&path, "old contents").unwrap();
+
+        assert!(app.autosave_to(&path, app.last_autosave + AUTOSAVE_INTERVAL));
+        let loaded = Patch::load(&path).unwrap();
+        assert_eq!(loaded.patch.rack.plugins.len(), 1);
+        let leftovers: Vec<_> = std::fs::read_dir(&dir).unwrap().flatten()
+            .filter(|entry| entry.path() != path)
+            .collect();
+        assert!(leftovers.is_empty(), "No temporary file is left behind");
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_autosave_newer_than_last_save() {
+        let dir = temp_dir("newer");
+        let autosave = dir.join("autosave.json");
+        let saved = dir.join("default.json");
+        std::fs::write(&autosave, "{}").unwrap();
+        std::fs::write(&saved, "{}").unwrap();
+        let now = SystemTime::now();
+
+        set_modified(&saved, now - Duration::from_secs(60));
+        set_modified(&autosave, now);
+        assert!(autosave_is_newer(&autosave, Some(&saved)), "Crashed after the last save");
+
+        set_modified(&saved, now + Duration::from_secs(60));
+        assert!(!autosave_is_newer(&autosave, Some(&saved)), "Saved after the autosave");
+
+        assert!(autosave_is_newer(&autosave, Some(&dir.join("missing.json"))), "Never saved");
+        assert!(autosave_is_newer(&autosave, None));
+        assert!(!autosave_is_newer(&dir.join("none.json"), Some(&saved)), "No autosave");
+        std::fs::remove_file(&autosave).ok();
+        std::fs::remove_file(&saved).ok();
+    }
+
+    #[test]
+    fn test_restore_autosave() {
+        let ctx = egui::Context::default();
+        let mut app = edited_app(&ctx);
+        let path = temp_dir("restore").join("autosave.json");
+        assert!(app.autosave_to(&path, app.last_autosave + AUTOSAVE_INTERVAL));
+
+        let id = app.plugin_manager.save_state().plugins[0].id;
+
+        let mut restarted = VcvRackApp::new_test(&ctx);
+        restarted.restore_autosave(&path).unwrap();
+        assert_eq!(restarted.plugin_manager.plugin_count(), 1);
+        assert_eq!(restarted.plugin_manager.get_plugin(id).unwrap().grid(), GridPos::new(2, 4));
+        assert!(restarted.has_unsaved_changes, "Restored work still has to be saved");
+        assert!(restarted.current_file.is_none(), "The rack had never been saved");
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_restored_autosave_keeps_its_patch_file() {
+        let ctx = egui::Context::default();
+        let dir = temp_dir("source");
+        let source = dir.join("song.json");
+        let mut app = edited_app(&ctx);
+        app.current_file = Some(source.clone());
+        let path = dir.join("autosave.json");
+        assert!(app.autosave_to(&path, app.last_autosave + AUTOSAVE_INTERVAL));
+        assert_eq!(autosave_source(&path), Some(source.clone()));
+
+        let mut restarted = VcvRackApp::new_test(&ctx);
+        restarted.restore_autosave(&path).unwrap();
+        assert_eq!(restarted.current_file, Some(source));
+        assert!(restarted.has_unsaved_changes);
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_autosave_is_offered_against_its_own_patch_file() {
+        let ctx = egui::Context::default();
+        let dir = temp_dir("offer");
+        let source = dir.join("song.json");
+        std::fs::write(&source, "{}").unwrap();
+        let mut app = edited_app(&ctx);
+        app.settings_dir = Some(dir.clone());
+        app.current_file = Some(source.clone());
+        assert!(app.tick_autosave(app.last_autosave + AUTOSAVE_INTERVAL));
+        let autosave = app.autosave_path().unwrap();
+        let now = SystemTime::now();
+
+        set_modified(&source, now - Duration::from_secs(60));
+        set_modified(&autosave, now);
+        assert_eq!(app.recoverable_autosave(), Some(autosave.clone()), "Crashed after the last save of song.json");
+        set_modified(&source, now + Duration::from_secs(60));
+        assert_eq!(app.recoverable_autosave(), None, "song.json was saved after the autosave");
+        std::fs::remove_file(&source).ok();
+        std::fs::remove_file(&autosave).ok();
+    }
+
+    #[test]
+    fn test_discard_autosave_removes_it_from_the_settings_folder() {
+        let ctx = egui::Context::default();
+        let mut app = edited_app(&ctx);
+        app.settings_dir = Some(temp_dir("discard"));
+        assert!(app.tick_autosave(app.last_autosave + AUTOSAVE_INTERVAL));
+        let path = app.autosave_path().unwrap();
+        assert!(path.exists());
+        app.discard_autosave();
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,220 @@
+#[cfg(test)]
+mod cable_tests {
+    use crate::models::cable::{Cable, CableColor, PortKind, PortRef, CABLE_COLORS};
+    use crate::models::module::{Module, ModuleDescriptor, ModuleIo, PortInfo, ProcessContext};
+    use crate::models::module_id::ModuleId;
+    use crate::models::plugin::{PluginManager, RackState};
+    use eframe::egui;
+
+    const TEST_ZOOM: f32 = 1.0;
+
+    static PATCH_DESCRIPTOR: ModuleDescriptor = ModuleDescriptor {
+        slug: "PatchTest",
+        name: "Patch Test",
+        brand: "Test",
+        tags: &[],
+        hp: 4,
+        panel_svg: "res/BlankPlatePlugin.svg",
+        params: &[],
+        inputs: &[PortInfo::new("In", (20.0, 300.0))],
+        outputs: &[PortInfo::new("Out", (20.0, 340.0))],
+        lights: &[],
+        create: create_patch_module,
+    };
+
+    struct PatchModule {
+        io: ModuleIo,
+    }
+
+    fn create_patch_module() -> Box<dyn Module> {
+        Box::new(PatchModule { io: ModuleIo::new(&PATCH_DESCRIPTOR) })
+    }
+
+    impl Module for PatchModule {
+        fn descriptor(&self) -> &'static ModuleDescriptor {
+            &PATCH_DESCRIPTOR
+        }
+
+        fn io(&self) -> &ModuleIo {
+            &self.io
+        }
+
+        fn io_mut(&mut self) -> &mut ModuleIo {
+            &mut self.io
+        }
+
+        fn process(&mut self, _ctx: &ProcessContext) {}
+    }
+
+    /// Two patchable modules next to each other and their ids.
+    fn create_patchable_rack() -> (PluginManager, [ModuleId; 2]) {
+        let mut manager = PluginManager::new();
+        let a = manager.add_module(&PATCH_DESCRIPTOR, egui::pos2(100.0, 100.0), None).unwrap();
+        let b = manager.add_module(&PATCH_DESCRIPTOR, egui::pos2(200.0, 100.0), None).unwrap();
+        (manager, [a, b])
+    }
+
+    #[test]
+    fn test_add_cable() {
+        let (mut manager, [a, b]) = create_patchable_rack();
+        let id = manager.add_cable(PortRef::new(a, 0), PortRef::new(b, 0)).unwrap();
+
+        let cable = manager.get_cable(id).unwrap();
+        assert_eq!(cable.output, PortRef::new(a, 0));
+        assert_eq!(cable.input, PortRef::new(b, 0));
+        assert_eq!(cable.color, CABLE_COLORS[0]);
+    }
+
+    #[test]
+    fn test_cable_colors_cycle() {
+        let (mut manager, [a, b]) = create_patchable_rack();
+        manager.add_cable(PortRef::new(a, 0), PortRef::new(b, 0)).unwrap();
+        manager.add_cable(PortRef::new(b, 0), PortRef::new(a, 0)).unwrap();
+        let colors: Vec<CableColor> = manager.get_cables().iter().map(|c| c.color).collect();
+        assert_eq!(colors, vec![CABLE_COLORS[0], CABLE_COLORS[1]]);
+    }
+
+    #[test]
+    fn test_input_accepts_one_cable() {
+        let (mut manager, [a, b]) = create_patchable_rack();
+        assert!(manager.add_cable(PortRef::new(a, 0), PortRef::new(b, 0)).is_some());
+        assert!(manager.add_cable(PortRef::new(b, 0), PortRef::new(b, 0)).is_none(),
+                "A second cable into the same input should be rejected");
+        assert_eq!(manager.get_cables().len(), 1);
+    }
+
+    #[test]
+    fn test_invalid_ports_rejected() {
+        let (mut manager, [a, b]) = create_patchable_rack();
+        assert!(manager.add_cable(PortRef::new(a, 5), PortRef::new(b, 0)).is_none());
+        assert!(manager.add_cable(PortRef::new(a, 0), PortRef::new(9, 0)).is_none());
+        assert!(manager.get_cables().is_empty());
+    }
+
+    #[test]
+    fn test_deleting_plugin_removes_its_cables() {
+        let (mut manager, [a, b]) = create_patchable_rack();
+        manager.add_cable(PortRef::new(a, 0), PortRef::new(b, 0)).unwrap();
+        manager.delete_plugin(egui::pos2(210.0, 100.0), TEST_ZOOM);
+        assert!(manager.get_cables().is_empty());
+    }
+
+    #[test]
+    fn test_port_at_position() {
+        let (manager, [a, b]) = create_patchable_rack();
+        assert_eq!(manager.port_at(egui::pos2(120.0, 400.0), TEST_ZOOM), Some((PortKind::Input, PortRef::new(a, 0))));
+        assert_eq!(manager.port_at(egui::pos2(222.0, 441.0), TEST_ZOOM), Some((PortKind::Output, PortRef::new(b, 0))));
+        assert_eq!(manager.port_at(egui::pos2(150.0, 200.0), TEST_ZOOM), None);
+    }
+
+    #[test]
+    fn test_drag_from_output_to_input_creates_cable() {
+        let (mut manager, [a, b]) = create_patchable_rack();
+        manager.begin_cable_drag(PortKind::Output, PortRef::new(a, 0), false);
+        assert!(manager.is_dragging_cable());
+        assert!(manager.end_cable_drag(Some(egui::pos2(220.0, 400.0)), TEST_ZOOM));
+        assert!(!manager.is_dragging_cable());
+
+        let cable = manager.get_cables()[0];
+        assert_eq!((cable.output, cable.input), (PortRef::new(a, 0), PortRef::new(b, 0)));
+    }
+
+    #[test]
+    fn test_drag_to_empty_space_creates_nothing() {
+        let (mut manager, [a, _]) = create_patchable_rack();
+        manager.begin_cable_drag(PortKind::Output, PortRef::new(a, 0), false);
+        assert!(!manager.end_cable_drag(Some(egui::pos2(600.0, 600.0)), TEST_ZOOM));
+        assert!(manager.get_cables().is_empty());
+    }
+
+    #[test]
+    fn test_reroute_cable_from_input() {
+        let (mut manager, [a, b]) = create_patchable_rack();
+        let id = manager.add_cable(PortRef::new(a, 0), PortRef::new(b, 0)).unwrap();
+
+        // Pick up the plug in module b and move it to module a's input
+        manager.begin_cable_drag(PortKind::Input, PortRef::new(b, 0), false);
+        assert!(manager.get_cables().is_empty(), "Picked up cable is held by the drag");
+        assert!(manager.end_cable_drag(Some(egui::pos2(120.0, 400.0)), TEST_ZOOM));
+
+        let cable = manager.get_cable(id).expect("Rerouted cable keeps its id");
+        assert_eq!(cable.input, PortRef::new(a, 0));
+        assert_eq!(cable.color, CABLE_COLORS[0], "Rerouted cable keeps its colour");
+    }
+
+    #[test]
+    fn test_unplug_cable_by_dropping_it() {
+        let (mut manager, [a, b]) = create_patchable_rack();
+        manager.add_cable(PortRef::new(a, 0), PortRef::new(b, 0)).unwrap();
+        manager.begin_cable_drag(PortKind::Input, PortRef::new(b, 0), false);
+        assert!(manager.end_cable_drag(None, TEST_ZOOM));
+        assert!(manager.get_cables().is_empty());
+    }
+
+    #[test]
+    fn test_ctrl_drag_from_output_moves_existing_cable() {
+        let (mut manager, [a, b]) = create_patchable_rack();
+        let id = manager.add_cable(PortRef::new(a, 0), PortRef::new(b, 0)).unwrap();
+        manager.begin_cable_drag(PortKind::Output, PortRef::new(a, 0), true);
+        assert!(manager.end_cable_drag(Some(egui::pos2(220.0, 440.0)), TEST_ZOOM));
+        assert_eq!(manager.get_cable(id).unwrap().output, PortRef::new(b, 0));
+    }
+
+    #[test]
+    fn test_remove_cable() {
+        let (mut manager, [a, b]) = create_patchable_rack();
+        let id = manager.add_cable(PortRef::new(a, 0), PortRef::new(b, 0)).unwrap();
+        assert!(manager.remove_cable(id).is_some());
+        assert!(manager.remove_cable(id).is_none());
+    }
+
+    #[test]
+    fn test_cables_saved_in_rack_state() {
+        let (mut manager, [a, b]) = create_patchable_rack();
+        manager.add_cable(PortRef::new(a, 0), PortRef::new(b, 0)).unwrap();
+        let state = manager.save_state();
+        assert_eq!(state.cables.len(), 1);
+
+        let json = serde_json::to_string(&state).unwrap();
+        assert!(json.contains("\"color\":\"#f3374b\""));
+        let restored: RackState = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.cables, state.cables);
+    }
+
+    #[test]
+    fn test_rack_state_without_cables_loads() {
+        let state: RackState = serde_json::from_str(r#"{"plugins": []}"#).unwrap();
+        assert!(state.cables.is_empty());
+    }
+
+    #[test]
+    fn test_load_state_drops_cables_to_missing_ports() {
+        let mut manager = PluginManager::new();
+        manager.add_plugin(egui::pos2(100.0, 100.0), None);
+        let mut state = manager.save_state();
+        state.cables.push(Cable {
+            id: 0,
+            output: PortRef::new(state.plugins[0].id, 0),
+            input: PortRef::new(7, 0),
+            color: CABLE_COLORS[0],
+        });
+        manager.load_state(state, None);
+        assert!(manager.get_cables().is_empty());
+    }
+
+    #[test]
+    fn test_cable_color_hex() {
+        assert_eq!(CableColor::from_hex("#00b56e"), Some(CableColor([0x00, 0xb5, 0x6e])));
+        assert_eq!(CableColor([0x36, 0x95, 0xef]).to_hex(), "#3695ef");
+        assert_eq!(CableColor::from_hex("blue"), None);
+    }
+
+    #[test]
+    fn test_graph_spec_contains_cables() {
+        let (mut manager, [a, b]) = create_patchable_rack();
+        manager.add_cable(PortRef::new(a, 0), PortRef::new(b, 0)).unwrap();
+        let spec = manager.graph_spec();
+        assert_eq!(spec.cables.len(), 1);
+        assert_eq!(spec.cables[0].input, PortRef::new(b, 0));
+    }
+}
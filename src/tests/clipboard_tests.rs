@@ -0,0 +1,145 @@
+#[cfg(test)]
+mod clipboard_tests {
+    use crate::app::vcvrack_app::VcvRackApp;
+    use crate::models::geometry::{GridPos, RackGeometry, RAIL_HP};
+    use crate::models::module_id::ModuleId;
+    use crate::models::plugin::{PluginState, RackState};
+    use crate::models::selection::Selection;
+    use eframe::egui;
+
+    /// App with plates at HP 0 and HP 4 of rail 0 (ids 0 and 1), the first one selected.
+    fn app_with_selection(ctx: &egui::Context) -> VcvRackApp {
+        let mut app = VcvRackApp::new_test(ctx);
+        let plate = |id, column| PluginState { rail: 0, column, selected: false, id, slug: "Blank".to_string(), params: Vec::new() };
+        app.plugin_manager.load_state(RackState { plugins: vec![plate(0, 0), plate(1, 4)], cables: Vec::new() }, None);
+        app.plugin_manager.select_plugin(RackGeometry::grid_to_rack(GridPos::new(0, 0)) + egui::vec2(5.0, 5.0), 1.0);
+        app
+    }
+
+    fn grid_of(app: &VcvRackApp, id: ModuleId) -> GridPos {
+        app.plugin_manager.get_plugin(id).unwrap().grid()
+    }
+
+    fn run(ctx: &egui::Context, app: &mut VcvRackApp, events: Vec<egui::Event>) -> egui::FullOutput {
+        let input = egui::RawInput { events, ..Default::default() };
+        ctx.run(input, |ctx| app.handle_clipboard_shortcuts(ctx))
+    }
+
+    #[test]
+    fn test_copy_serializes_selected_modules() {
+        let ctx = egui::Context::default();
+        let mut app = app_with_selection(&ctx);
+        let output = run(&ctx, &mut app, vec![egui::Event::Copy]);
+
+        let selection = Selection::from_json(&output.platform_output.copied_text).unwrap();
+        assert_eq!(selection.plugins.len(), 1);
+        assert_eq!(selection.plugins[0].slug, "Blank");
+        assert_eq!(app.plugin_manager.plugin_count(), 2, "copying leaves the rack alone");
+        assert!(!app.has_unsaved_changes);
+    }
+
+    #[test]
+    fn test_copy_without_selection_keeps_clipboard() {
+        let ctx = egui::Context::default();
+        let mut app = app_with_selection(&ctx);
+        app.plugin_manager.deselect_all();
+        let output = run(&ctx, &mut app, vec![egui::Event::Copy]);
+        assert!(output.platform_output.copied_text.is_empty());
+    }
+
+    #[test]
+    fn test_cut_removes_selected_modules() {
+        let ctx = egui::Context::default();
+        let mut app = app_with_selection(&ctx);
+        let output = run(&ctx, &mut app, vec![egui::Event::Cut]);
+
+        assert!(Selection::from_json(&output.platform_output.copied_text).is_ok());
+        assert_eq!(app.plugin_manager.plugin_count(), 1);
+        assert!(app.plugin_manager.get_plugin(0).is_none());
+        assert!(app.has_unsaved_changes);
+    }
+
+    #[test]
+    fn test_paste_at_pointer_with_fresh_ids() {
+        let ctx = egui::Context::default();
+        let mut app = app_with_selection(&ctx);
+        let json = app.copy_selection().unwrap();
+        app.last_rack_pointer = Some(RackGeometry::grid_to_rack(GridPos::new(2, 10)));
+        run(&ctx, &mut app, vec![egui::Event::Paste(json)]);
+
+        assert_eq!(app.plugin_manager.plugin_count(), 3);
+        let pasted = app.plugin_manager.get_selected_plugins()[0];
+        assert!(pasted.id > 1, "pasted modules get a fresh id");
+        assert_eq!(pasted.grid(), GridPos::new(2, 10));
+        assert!(app.has_unsaved_changes);
+    }
+
+    #[test]
+    fn test_paste_moves_to_nearest_free_span() {
+        let ctx = egui::Context::default();
+        let mut app = app_with_selection(&ctx);
+        let json = app.copy_selection().unwrap();
+        let ids = app.paste(&json, GridPos::new(0, 1)).unwrap();
+        assert_eq!(grid_of(&app, ids[0]), GridPos::new(0, 2), "between the two plates");
+        let ids = app.paste(&json, GridPos::new(0, 1)).unwrap();
+        assert_eq!(grid_of(&app, ids[0]), GridPos::new(0, 6));
+    }
+
+    #[test]
+    fn test_paste_without_room_is_refused() {
+        let ctx = egui::Context::default();
+        let mut app = app_with_selection(&ctx);
+        let plate = |id, column| PluginState { rail: 0, column, selected: true, id, slug: "Blank".to_string(), params: Vec::new() };
+        let rail_long = Selection::new(vec![plate(0, 0), plate(1, RAIL_HP - 2)], &[]).to_json().unwrap();
+        assert!(app.paste(&rail_long, GridPos::new(0, 0)).is_err(), "rail 0 has plates in the way");
+        assert_eq!(app.plugin_manager.plugin_count(), 2);
+        assert!(!app.has_unsaved_changes);
+        assert!(app.plugin_manager.history().undo_name().is_none());
+    }
+
+    #[test]
+    fn test_duplicate_without_room_is_refused() {
+        let ctx = egui::Context::default();
+        let mut app = VcvRackApp::new_test(&ctx);
+        let plate = |id, column| PluginState { rail: 0, column, selected: false, id, slug: "Blank".to_string(), params: Vec::new() };
+        app.plugin_manager.load_state(RackState { plugins: vec![plate(0, 0), plate(1, RAIL_HP - 2)], cables: Vec::new() }, None);
+        for column in [0, RAIL_HP - 2] {
+            app.plugin_manager.select_plugin(RackGeometry::grid_to_rack(GridPos::new(0, column)) + egui::vec2(5.0, 5.0), 1.0);
+        }
+        assert!(app.duplicate_selection().is_err(), "the copies would span the whole rail");
+        assert_eq!(app.plugin_manager.plugin_count(), 2);
+        assert!(!app.has_unsaved_changes);
+    }
+
+    #[test]
+    fn test_pasting_other_text_does_nothing() {
+        let ctx = egui::Context::default();
+        let mut app = app_with_selection(&ctx);
+        run(&ctx, &mut app, vec![egui::Event::Paste("hello".to_string())]);
+        assert_eq!(app.plugin_manager.plugin_count(), 2);
+        assert!(!app.has_unsaved_changes);
+    }
+
+    #[test]
+    fn test_ctrl_d_duplicates_next_to_selection() {
+        let ctx = egui::Context::default();
+        let mut app = app_with_selection(&ctx);
+        let input = egui::RawInput {
+            modifiers: egui::Modifiers::CTRL,
+            events: vec![egui::Event::Key {
+                key: egui::Key::D,
+                physical_key: None,
+                pressed: true,
+                repeat: false,
+                modifiers: egui::Modifiers::CTRL,
+            }],
+            ..Default::default()
+        };
+        let _ = ctx.run(input, |ctx| app.handle_clipboard_shortcuts(ctx));
+
+        assert_eq!(app.plugin_manager.plugin_count(), 3);
+        let duplicate = app.plugin_manager.get_selected_plugins()[0];
+        assert_eq!(duplicate.grid(), GridPos::new(0, 2), "first free span next to the original");
+        assert_eq!(app.plugin_manager.history().undo_name(), Some("Add modules"));
+    }
+}
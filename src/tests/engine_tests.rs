@@ -0,0 +1,183 @@
+#[cfg(test)]
+mod engine_tests {
+    use crate::app::engine_menu::engine_status_text;
+    use crate::engine::{AudioBlock, AudioEngine, EngineConfig, NullBackend, OutputBackend, SampleQueue, WavFileBackend};
+    use crate::models::plugin::PluginManager;
+    use eframe::egui;
+    use std::io;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    /// Backend whose first `failures` writes fail, like a device that was
+    /// unplugged and plugged back in.
+    struct FlakyBackend {
+        failures: Arc<AtomicUsize>,
+    }
+
+    impl OutputBackend for FlakyBackend {
+        fn name(&self) -> &str {
+            "Flaky"
+        }
+
+        fn open(&mut self, _sample_rate: u32, _channels: usize) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn write(&mut self, _block: &AudioBlock) -> io::Result<()> {
+            match self.failures.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |left| left.checked_sub(1)) {
+                Ok(_) => Err(io::Error::other("unplugged")),
+                Err(_) => Ok(()),
+            }
+        }
+
+        fn close(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn wait_until(condition: impl Fn() -> bool) -> bool {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while Instant::now() < deadline {
+            if condition() {
+                return true;
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        false
+    }
+
+    #[test]
+    fn test_engine_starts_stopped() {
+        let engine = AudioEngine::with_null_backend();
+        let state = engine.state();
+        assert!(!state.running);
+        assert_eq!(state.sample_rate, 48000);
+        assert_eq!(state.backend, "Null");
+    }
+
+    #[test]
+    fn test_engine_runs_on_null_backend() {
+        let backend = NullBackend::new();
+        let frames_written = backend.frames_written_counter();
+        let mut engine = AudioEngine::new(EngineConfig::default(), Box::new(backend));
+
+        engine.start().unwrap();
+        assert!(engine.is_running());
+        assert!(wait_until(|| frames_written.load(Ordering::Relaxed) >= 1024),
+                "Engine thread should keep writing blocks to the backend");
+
+        engine.stop();
+        assert!(!engine.is_running());
+        let written = frames_written.load(Ordering::Relaxed);
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(frames_written.load(Ordering::Relaxed), written, "No blocks should be written after stop");
+    }
+
+    #[test]
+    fn test_engine_starts_again_after_the_backend_failed() {
+        let failures = Arc::new(AtomicUsize::new(1));
+        let mut engine = AudioEngine::new(EngineConfig::default(), Box::new(FlakyBackend { failures: failures.clone() }));
+        engine.start().unwrap();
+        assert!(wait_until(|| !engine.is_running()), "The engine stops when the backend fails");
+        assert_eq!(engine.frames_processed(), 0);
+
+        engine.start().unwrap();
+        assert!(engine.is_running());
+        assert!(wait_until(|| engine.frames_processed() >= 1024), "Start runs the engine again");
+        engine.stop();
+    }
+
+    #[test]
+    fn test_sample_rate_change_restarts_running_engine() {
+        let mut engine = AudioEngine::with_null_backend();
+        engine.start().unwrap();
+        engine.set_sample_rate(44100).unwrap();
+        assert!(engine.is_running());
+        assert_eq!(engine.state().sample_rate, 44100);
+        engine.stop();
+
+        engine.set_sample_rate(96000).unwrap();
+        assert!(!engine.is_running(), "Changing sample rate should not start a stopped engine");
+        assert_eq!(engine.sample_rate(), 96000);
+    }
+
+    #[test]
+    fn test_offline_render_length() {
+        let config = EngineConfig { sample_rate: 48000, block_size: 64 };
+        let mut engine = AudioEngine::new(config, Box::new(NullBackend::new()));
+        let block = engine.render_offline(1000);
+        assert_eq!(block.frames(), 1000);
+        assert!(block.samples.iter().all(|s| *s == 0.0), "Blank plates should render silence");
+    }
+
+    #[test]
+    fn test_graph_follows_plugin_manager() {
+        let mut manager = PluginManager::new();
+        manager.add_plugin(egui::pos2(100.0, 100.0), None);
+        manager.add_plugin(egui::pos2(130.4, 100.0), None);
+
+        let engine = AudioEngine::with_null_backend();
+        engine.update_graph(&manager.graph_spec());
+        assert_eq!(engine.graph_node_count(), 2);
+
+        manager.delete_plugin(egui::pos2(100.0, 100.0), 1.0);
+        engine.update_graph(&manager.graph_spec());
+        assert_eq!(engine.graph_node_count(), 1);
+    }
+
+    #[test]
+    fn test_wav_backend_writes_header() {
+        let path = std::env::temp_dir().join(format!("vcvrack_rs_engine_{}.wav", std::process::id()));
+        let config = EngineConfig { sample_rate: 44100, block_size: 128 };
+        let mut engine = AudioEngine::new(config, Box::new(WavFileBackend::new(&path)));
+        engine.start().unwrap();
+        assert!(wait_until(|| engine.frames_processed() >= 256));
+        engine.stop();
+
+        let bytes = std::fs::read(&path).unwrap();
+        assert_eq!(&bytes[0..4], b"RIFF");
+        assert_eq!(&bytes[8..12], b"WAVE");
+        let data_bytes = u32::from_le_bytes([bytes[40], bytes[41], bytes[42], bytes[43]]);
+        assert_eq!(data_bytes as usize, bytes.len() - 44);
+        assert_eq!(data_bytes % (2 * 4), 0, "Data should contain whole stereo float frames");
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_engine_status_text() {
+        let mut engine = AudioEngine::with_null_backend();
+        assert_eq!(engine_status_text(&engine.state()), "Stopped | 48000 Hz");
+        engine.start().unwrap();
+        assert!(engine_status_text(&engine.state()).starts_with("Running | 48000 Hz | CPU"));
+    }
+
+    #[test]
+    fn test_sample_queue_plays_silence_when_it_runs_dry() {
+        let queue = SampleQueue::new(8);
+        assert!(queue.push(&[1.0, 2.0, 3.0], Duration::ZERO));
+        let mut out = [9.0; 4];
+        assert_eq!(queue.pop_into(&mut out), 3);
+        assert_eq!(out, [1.0, 2.0, 3.0, 0.0]);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_sample_queue_waits_for_room() {
+        let queue = std::sync::Arc::new(SampleQueue::new(4));
+        assert!(queue.push(&[1.0; 4], Duration::ZERO));
+        assert!(!queue.push(&[2.0; 2], Duration::from_millis(10)), "a full queue times out");
+
+        let reader = queue.clone();
+        let drain = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(20));
+            reader.pop_into(&mut [0.0; 2])
+        });
+        assert!(queue.push(&[2.0; 2], Duration::from_secs(5)), "room made by the device is used");
+        assert_eq!(drain.join().unwrap(), 2);
+        assert_eq!(queue.len(), 4);
+        assert!(!queue.push(&[3.0; 9], Duration::from_millis(10)), "too big to fit next to queued samples");
+        queue.pop_into(&mut [0.0; 4]);
+        assert!(queue.push(&[3.0; 9], Duration::ZERO), "a block bigger than the queue goes into an empty one");
+    }
+}
@@ -0,0 +1,133 @@
+#[cfg(test)]
+mod file_menu_tests {
+    use crate::app::vcvrack_app::VcvRackApp;
+    use crate::models::geometry::{GridPos, RackGeometry};
+    use crate::models::recent_files::{RecentFiles, MAX_RECENT_FILES};
+    use eframe::egui;
+    use std::path::{Path, PathBuf};
+
+    /// Empty folder outside the save directory.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("vcvrack_files_{}_{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn app_with_plugin(ctx: &egui::Context) -> VcvRackApp {
+        let mut app = VcvRackApp::new_test(ctx);
+        app.plugin_manager = Default::default();
+        app.plugin_manager.add_plugin(RackGeometry::grid_to_rack(GridPos::new(1, 3)), None);
+        app.has_unsaved_changes = true;
+        app
+    }
+
+    #[test]
+    fn test_recent_files_order() {
+        let mut recent = RecentFiles::default();
+        recent.add(Path::new("a.json"));
+        recent.add(Path::new("b.json"));
+        recent.add(Path::new("a.json"));
+        assert_eq!(recent.files, vec![PathBuf::from("a.json"), PathBuf::from("b.json")],
+                   "Reopening a file moves it to the top without duplicating it");
+
+        for i in 0..MAX_RECENT_FILES + 2 {
+            recent.add(Path::new(&format!("{}.json", i)));
+        }
+        assert_eq!(recent.files.len(), MAX_RECENT_FILES);
+        assert_eq!(recent.files[0], PathBuf::from(format!("{}.json", MAX_RECENT_FILES + 1)));
+
+        recent.remove(Path::new(&format!("{}.json", MAX_RECENT_FILES + 1)));
+        assert_eq!(recent.files.len(), MAX_RECENT_FILES - 1);
+    }
+
+    #[test]
+    fn test_recent_files_round_trip() {
+        let path = temp_dir("recent").join("recent_files.json");
+        let mut recent = RecentFiles::default();
+        recent.add(Path::new("/patches/bass.json"));
+        recent.save(&path).unwrap();
+        assert_eq!(RecentFiles::load(&path).unwrap(), recent);
+        std::fs::remove_file(&path).ok();
+
+        let old: RecentFiles = serde_json::from_str("{}").unwrap();
+        assert!(old.files.is_empty());
+    }
+
+    #[test]
+    fn test_save_patch_to_any_folder() {
+        let ctx = egui::Context::default();
+        let mut app = app_with_plugin(&ctx);
+        let path = temp_dir("save").join("My patch.json");
+
+        app.save_patch(&path).unwrap();
+        assert!(path.exists(), "The patch is written where the user chose, not into the save directory");
+        assert_eq!(app.current_file.as_deref(), Some(path.as_path()));
+        assert_eq!(app.recent_files.files[0], path);
+        assert!(!app.has_unsaved_changes);
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_save_writes_back_to_current_file() {
+        let ctx = egui::Context::default();
+        let mut app = app_with_plugin(&ctx);
+        let path = temp_dir("resave").join("current.json");
+        app.save_patch(&path).unwrap();
+
+        app.plugin_manager.add_plugin(RackGeometry::grid_to_rack(GridPos::new(2, 0)), None);
+        app.has_unsaved_changes = true;
+        app.save();
+        assert!(!app.has_unsaved_changes);
+
+        let mut reopened = VcvRackApp::new_test(&ctx);
+        reopened.load_patch(&path).unwrap();
+        assert_eq!(reopened.plugin_manager.plugin_count(), 2);
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_open_file_from_any_folder() {
+        let ctx = egui::Context::default();
+        let mut app = app_with_plugin(&ctx);
+        let path = temp_dir("open").join("elsewhere.json");
+        app.save_patch(&path).unwrap();
+
+        let mut other = VcvRackApp::new_test(&ctx);
+        assert!(other.open_file(&path));
+        assert_eq!(other.current_file.as_deref(), Some(path.as_path()));
+        assert_eq!(other.plugin_manager.plugin_count(), 1);
+        assert_eq!(other.recent_files.files[0], path);
+        assert!(!other.has_unsaved_changes);
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_missing_recent_file_is_forgotten() {
+        let ctx = egui::Context::default();
+        let mut app = VcvRackApp::new_test(&ctx);
+        let path = temp_dir("missing").join("gone.json");
+        app.recent_files.add(&path);
+        let current = app.current_file.clone();
+
+        assert!(!app.open_file(&path));
+        assert!(app.recent_files.files.is_empty());
+        assert_eq!(app.current_file, current, "A failed open keeps the current patch");
+    }
+
+    #[test]
+    fn test_recent_files_are_kept_in_the_settings_folder() {
+        let ctx = egui::Context::default();
+        let settings = temp_dir("settings");
+        let mut app = app_with_plugin(&ctx);
+        app.settings_dir = Some(settings.clone());
+        let path = settings.join("song.json");
+        app.save_patch(&path).unwrap();
+        assert!(settings.join("recent_files.json").exists());
+
+        let mut restarted = VcvRackApp::new_test(&ctx);
+        assert!(restarted.load_recent_files().files.is_empty(), "No settings folder, no recent files");
+        restarted.settings_dir = Some(settings);
+        assert_eq!(restarted.load_recent_files().files, vec![path.clone()]);
+        std::fs::remove_file(&path).ok();
+    }
+}
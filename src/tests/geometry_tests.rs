@@ -0,0 +1,69 @@
+#[cfg(test)]
+mod geometry_tests {
+    use crate::app::rail_view::{RailLayout, RAIL_ROWS, RAIL_TILE_SIZE};
+    use crate::models::geometry::{GridPos, RackGeometry, GRID_ORIGIN, GRID_UNIT, RAIL_COUNT, RAIL_HEIGHT, RAIL_TILE_HP};
+    use crate::models::plugin::PluginManager;
+    use crate::modules;
+    use eframe::egui;
+
+    #[test]
+    fn test_grid_round_trip() {
+        for grid in [GridPos::new(0, 0), GridPos::new(3, 17), GridPos::new(RAIL_COUNT - 1, 1000)] {
+            let pos = RackGeometry::grid_to_rack(grid);
+            assert_eq!(RackGeometry::snap_to_grid(pos), grid);
+            assert_eq!(RackGeometry::grid_cell_at(pos + egui::vec2(1.0, 1.0)), grid);
+        }
+        assert_eq!(RackGeometry::grid_to_rack(GridPos::default()), GRID_ORIGIN);
+    }
+
+    #[test]
+    fn test_snap_rounds_and_cell_floors() {
+        let pos = GRID_ORIGIN + egui::vec2(GRID_UNIT * 4.8, RAIL_HEIGHT * 1.2);
+        assert_eq!(RackGeometry::snap_to_grid(pos), GridPos::new(1, 5));
+        assert_eq!(RackGeometry::grid_cell_at(pos), GridPos::new(1, 4));
+    }
+
+    #[test]
+    fn test_rails_line_up_with_the_grid() {
+        assert_eq!(RAIL_TILE_SIZE, egui::vec2(RAIL_TILE_HP as f32 * GRID_UNIT, RAIL_HEIGHT));
+        assert_eq!(RAIL_ROWS, RAIL_COUNT as usize);
+
+        let geometry = RackGeometry::new(egui::pos2(-500.0, -20.0), 1.5);
+        let layout = RailLayout {
+            origin: geometry.to_screen(GRID_ORIGIN),
+            tile_size: RAIL_TILE_SIZE * geometry.zoom,
+            ..RailLayout::default()
+        };
+        let tile = layout.tile_rect(2, 3);
+        let corner = geometry.grid_to_screen(GridPos::new(3, 2 * RAIL_TILE_HP));
+        assert!((tile.min - corner).length() < 1e-3);
+    }
+
+    #[test]
+    fn test_module_lands_where_clicked() {
+        for zoom in [0.4, 1.0, 1.3, 2.6] {
+            let geometry = RackGeometry::new(egui::pos2(8.0, 30.0), zoom);
+            let mut manager = PluginManager::new();
+            let click = geometry.grid_to_screen(GridPos::new(2, 37)) + egui::vec2(GRID_UNIT * zoom * 0.7, 200.0 * zoom);
+
+            let cell = geometry.screen_to_grid(click);
+            assert_eq!(cell, GridPos::new(2, 37));
+            let id = manager.add_module(modules::blank_plate(), RackGeometry::grid_to_rack(cell), None).unwrap();
+
+            let plugin = manager.get_plugin(id).unwrap();
+            assert_eq!(plugin.grid(), cell, "Module moved away from the click at zoom {}", zoom);
+            assert!(manager.get_plugin_at_position(geometry.to_rack(click), zoom).is_some());
+        }
+    }
+
+    #[test]
+    fn test_state_stores_grid_coordinates() {
+        let mut manager = PluginManager::new();
+        manager.add_plugin(RackGeometry::grid_to_rack(GridPos::new(4, 11)), None);
+        let json = serde_json::to_value(manager.save_state()).unwrap();
+        let plugin = &json["plugins"][0];
+        assert_eq!(plugin["rail"], 4);
+        assert_eq!(plugin["column"], 11);
+        assert!(plugin.get("x").is_none() && plugin.get("y").is_none());
+    }
+}
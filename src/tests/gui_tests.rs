@@ -48,6 +48,7 @@ mod gui_tests {
         }
     }
 
+    #[allow(dead_code)]
     struct Rail {
         position: egui::Pos2,
         width: u32,
@@ -133,6 +134,7 @@ mod gui_tests {
         }
     }
 
+    #[allow(dead_code)]
     struct Plugin {
         position: egui::Pos2,
         texture: egui::TextureHandle,
@@ -270,7 +272,7 @@ mod gui_tests {
 
         println!("\nStarting grid positioning test...");
         // Test cases with input click positions and expected grid-snapped positions
-        let test_cases = vec![
+        let test_cases = [
             // Exact grid positions
             (RAIL_START_X, RAIL_START_X),                    // Grid 0
             (RAIL_START_X + GRID_UNIT, RAIL_START_X + GRID_UNIT),      // Grid 1
@@ -372,9 +374,9 @@ mod gui_tests {
     fn test_plugin_deletion() {
         let (_ctx, mut plugin_manager, mock_textures) = create_test_context();
 
-        // Add two plugins next to each other
+        // Add two plugins next to each other (blank plates are 2HP wide)
         let plugin1_pos = egui::pos2(RAIL_START_X, RAIL_Y);
-        let plugin2_pos = egui::pos2(RAIL_START_X + GRID_UNIT, RAIL_Y);
+        let plugin2_pos = egui::pos2(RAIL_START_X + 2.0 * GRID_UNIT, RAIL_Y);
         
         plugin_manager.add_plugin(plugin1_pos, Some(mock_textures.blank_plate.clone()));
         plugin_manager.add_plugin(plugin2_pos, Some(mock_textures.blank_plate));
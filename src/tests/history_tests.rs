@@ -0,0 +1,319 @@
+#[cfg(test)]
+mod history_tests {
+    use crate::app::edit_menu::history_label;
+    use crate::app::vcvrack_app::VcvRackApp;
+    use crate::models::cable::{PortKind, PortRef};
+    use crate::models::geometry::GRID_UNIT;
+    use crate::models::history::{Command, History};
+    use crate::models::module::{Module, ModuleDescriptor, ModuleIo, ParamInfo, PortInfo, ProcessContext};
+    use crate::models::module_id::ModuleId;
+    use crate::models::plugin::PluginManager;
+    use crate::modules;
+    use eframe::egui;
+
+    const TEST_ZOOM: f32 = 1.0;
+
+    static KNOB_DESCRIPTOR: ModuleDescriptor = ModuleDescriptor {
+        slug: "HistoryTest",
+        name: "History Test",
+        brand: "Test",
+        tags: &[],
+        hp: 4,
+        panel_svg: "res/BlankPlatePlugin.svg",
+        params: &[ParamInfo::knob("Level", 0.0, 10.0, 5.0, "V", (30.0, 60.0))],
+        inputs: &[PortInfo::new("In", (20.0, 300.0))],
+        outputs: &[PortInfo::new("Out", (20.0, 340.0))],
+        lights: &[],
+        create: create_knob_module,
+    };
+
+    struct KnobModule {
+        io: ModuleIo,
+    }
+
+    fn create_knob_module() -> Box<dyn Module> {
+        Box::new(KnobModule { io: ModuleIo::new(&KNOB_DESCRIPTOR) })
+    }
+
+    impl Module for KnobModule {
+        fn descriptor(&self) -> &'static ModuleDescriptor {
+            &KNOB_DESCRIPTOR
+        }
+
+        fn io(&self) -> &ModuleIo {
+            &self.io
+        }
+
+        fn io_mut(&mut self) -> &mut ModuleIo {
+            &mut self.io
+        }
+
+        fn process(&mut self, _ctx: &ProcessContext) {}
+    }
+
+    /// Two patchable modules, left to right, with their ids.
+    fn create_rack() -> (PluginManager, [ModuleId; 2]) {
+        let mut manager = PluginManager::new();
+        let a = manager.add_module(&KNOB_DESCRIPTOR, egui::pos2(100.0, 100.0), None).unwrap();
+        let b = manager.add_module(&KNOB_DESCRIPTOR, egui::pos2(200.0, 100.0), None).unwrap();
+        (manager, [a, b])
+    }
+
+    fn set_param_command(to: f32) -> Command {
+        Command::SetParam { id: 0, index: 0, from: 0.0, to }
+    }
+
+    /// Target value of an undone `SetParam`.
+    fn undone_value(history: &mut History) -> Option<f32> {
+        match history.take_undo()? {
+            Command::SetParam { to, .. } => Some(to),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_undo_redo_add_plugin() {
+        let mut manager = PluginManager::new();
+        let id = manager.add_module(modules::blank_plate(), egui::pos2(100.0, 100.0), None).unwrap();
+        assert_eq!(manager.history().undo_name(), Some("Add module"));
+
+        assert!(manager.undo());
+        assert!(manager.is_empty());
+        assert_eq!(manager.history().redo_name(), Some("Add module"));
+
+        assert!(manager.redo());
+        assert_eq!(manager.plugin_count(), 1);
+        assert_eq!(manager.get_plugin(id).unwrap().slug(), "Blank", "Redo brings back the same id");
+    }
+
+    #[test]
+    fn test_undo_delete_restores_plugin_and_cables() {
+        let (mut manager, [a, b]) = create_rack();
+        manager.add_cable(PortRef::new(a, 0), PortRef::new(b, 0)).unwrap();
+        manager.set_param(b, 0, 8.0);
+        manager.delete_plugin(egui::pos2(210.0, 100.0), TEST_ZOOM);
+        assert_eq!(manager.plugin_count(), 1);
+        assert!(manager.get_cables().is_empty());
+
+        assert!(manager.undo());
+        assert_eq!(manager.plugin_count(), 2);
+        assert_eq!(manager.get_plugin(b).unwrap().params, vec![8.0]);
+        assert_eq!(manager.get_cables().len(), 1);
+
+        assert!(manager.redo());
+        assert_eq!(manager.plugin_count(), 1);
+        assert!(manager.get_cables().is_empty());
+    }
+
+    #[test]
+    fn test_delete_selected_is_one_step() {
+        let (mut manager, _) = create_rack();
+        manager.select_plugin(egui::pos2(110.0, 100.0), TEST_ZOOM);
+        manager.select_plugin(egui::pos2(210.0, 100.0), TEST_ZOOM);
+        let steps = manager.history().undo_len();
+        manager.delete_selected_plugins();
+        assert_eq!(manager.history().undo_name(), Some("Delete modules"));
+
+        manager.undo();
+        assert_eq!(manager.plugin_count(), 2);
+        assert_eq!(manager.history().undo_len(), steps);
+    }
+
+    #[test]
+    fn test_undo_move() {
+        let (mut manager, [a, _]) = create_rack();
+        let start = egui::pos2(110.0, 150.0);
+        manager.begin_module_drag(a, start);
+        manager.drag_modules(start + egui::vec2(20.0 * GRID_UNIT, 0.0));
+        manager.end_module_drag().unwrap();
+        let moved = manager.get_plugin(a).unwrap().grid_column();
+
+        assert!(manager.undo());
+        assert_eq!(manager.get_plugin(a).unwrap().grid_column(), 0);
+        assert!(manager.redo());
+        assert_eq!(manager.get_plugin(a).unwrap().grid_column(), moved);
+    }
+
+    #[test]
+    fn test_undo_cable_changes() {
+        let (mut manager, [a, b]) = create_rack();
+        let id = manager.add_cable(PortRef::new(a, 0), PortRef::new(b, 0)).unwrap();
+
+        // Reroute to module a's own input
+        manager.begin_cable_drag(PortKind::Input, PortRef::new(b, 0), false);
+        assert!(manager.end_cable_drag(Some(egui::pos2(120.0, 400.0)), TEST_ZOOM));
+        assert_eq!(manager.history().undo_name(), Some("Move cable"));
+        manager.undo();
+        assert_eq!(manager.get_cable(id).unwrap().input, PortRef::new(b, 0));
+
+        manager.remove_cable(id);
+        manager.undo();
+        assert!(manager.get_cable(id).is_some());
+
+        manager.undo();
+        assert!(manager.get_cables().is_empty(), "Undoing the add removes the cable");
+    }
+
+    #[test]
+    fn test_undo_unplugging_by_drag() {
+        let (mut manager, [a, b]) = create_rack();
+        manager.add_cable(PortRef::new(a, 0), PortRef::new(b, 0)).unwrap();
+        manager.begin_cable_drag(PortKind::Input, PortRef::new(b, 0), false);
+        assert!(!manager.undo(), "No undo in the middle of a drag");
+        manager.end_cable_drag(None, TEST_ZOOM);
+        assert_eq!(manager.history().undo_name(), Some("Remove cable"));
+
+        manager.undo();
+        assert_eq!(manager.get_cables().len(), 1);
+    }
+
+    #[test]
+    fn test_undo_param_change() {
+        let (mut manager, [a, _]) = create_rack();
+        manager.set_param(a, 0, 9.0);
+        manager.set_param(a, 0, 2.0);
+        manager.undo();
+        assert_eq!(manager.get_plugin(a).unwrap().params[0], 9.0);
+        manager.undo();
+        assert_eq!(manager.get_plugin(a).unwrap().params[0], 5.0);
+    }
+
+    #[test]
+    fn test_new_edit_clears_redo() {
+        let (mut manager, [a, _]) = create_rack();
+        manager.set_param(a, 0, 9.0);
+        manager.undo();
+        assert!(manager.history().can_redo());
+        manager.set_param(a, 0, 1.0);
+        assert!(!manager.history().can_redo());
+    }
+
+    #[test]
+    fn test_load_state_clears_history() {
+        let (mut manager, [a, _]) = create_rack();
+        manager.set_param(a, 0, 9.0);
+        let state = manager.save_state();
+        manager.load_state(state, None);
+        assert!(!manager.history().can_undo());
+        assert!(!manager.history().is_modified());
+    }
+
+    #[test]
+    fn test_history_is_bounded() {
+        let mut history = History::with_limit(3);
+        for value in 0..5 {
+            history.push(set_param_command(value as f32));
+        }
+        assert_eq!(history.undo_len(), 3);
+        assert_eq!(undone_value(&mut history), Some(4.0));
+    }
+
+    #[test]
+    fn test_knob_gesture_merges_into_one_step() {
+        let mut history = History::new();
+        history.push_mergeable(set_param_command(1.0));
+        history.push_mergeable(set_param_command(2.0));
+        history.push_mergeable(set_param_command(3.0));
+        history.seal();
+        history.push_mergeable(set_param_command(4.0));
+
+        assert_eq!(history.undo_len(), 2);
+        assert_eq!(undone_value(&mut history), Some(4.0));
+        assert_eq!(undone_value(&mut history), Some(3.0));
+    }
+
+    #[test]
+    fn test_saved_point_tracking() {
+        let mut history = History::new();
+        assert!(!history.is_modified());
+        history.push(set_param_command(1.0));
+        history.mark_saved();
+        history.push(set_param_command(2.0));
+        assert!(history.is_modified());
+
+        history.take_undo();
+        assert!(!history.is_modified(), "Back at the saved point");
+        history.take_undo();
+        assert!(history.is_modified(), "Before the saved point");
+        history.take_redo();
+        assert!(!history.is_modified());
+
+        // Branching off discards the saved state
+        history.take_undo();
+        history.push(set_param_command(5.0));
+        assert!(history.is_modified());
+    }
+
+    #[test]
+    fn test_saved_point_survives_trimming() {
+        let mut history = History::with_limit(2);
+        history.push(set_param_command(1.0));
+        history.mark_saved();
+        history.push(set_param_command(2.0));
+        history.push(set_param_command(3.0));
+        history.take_undo();
+        history.take_undo();
+        assert!(!history.is_modified(), "Oldest kept state is the saved one");
+    }
+
+    #[test]
+    fn test_menu_labels() {
+        assert_eq!(history_label("Undo", Some("Move module")), "Undo Move module");
+        assert_eq!(history_label("Redo", None), "Redo");
+    }
+
+    #[test]
+    fn test_app_undo_to_saved_point_clears_indicator() {
+        let ctx = egui::Context::default();
+        let mut app = VcvRackApp::new_test(&ctx);
+        let state = app.plugin_manager.save_state();
+        app.plugin_manager.load_state(state, None);
+        app.has_unsaved_changes = false;
+
+        app.plugin_manager.add_plugin(egui::pos2(100.0, 100.0), None);
+        app.has_unsaved_changes = true;
+        app.undo();
+        assert!(!app.has_unsaved_changes, "Undoing back to the saved rack clears the *");
+        app.redo();
+        assert!(app.has_unsaved_changes);
+    }
+
+    #[test]
+    fn test_ctrl_z_in_a_text_field_leaves_the_rack_alone() {
+        let ctx = egui::Context::default();
+        let mut app = VcvRackApp::new_test(&ctx);
+        app.plugin_manager = PluginManager::new();
+        app.plugin_manager.add_plugin(egui::pos2(100.0, 100.0), None);
+        let ctrl_z = || egui::RawInput {
+            modifiers: egui::Modifiers::CTRL,
+            events: vec![egui::Event::Key {
+                key: egui::Key::Z,
+                physical_key: None,
+                pressed: true,
+                repeat: false,
+                modifiers: egui::Modifiers::CTRL,
+            }],
+            ..Default::default()
+        };
+        let mut text = String::new();
+        let mut search = |ctx: &egui::Context, focus: bool| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                let response = ui.text_edit_singleline(&mut text);
+                if focus {
+                    response.request_focus();
+                }
+            });
+        };
+
+        let _ = ctx.run(egui::RawInput::default(), |ctx| search(ctx, true));
+        let _ = ctx.run(ctrl_z(), |ctx| {
+            app.handle_history_shortcuts(ctx);
+            search(ctx, false);
+        });
+        assert_eq!(app.plugin_manager.plugin_count(), 1, "The text field has the keyboard");
+
+        let _ = ctx.run(egui::RawInput::default(), |ctx| ctx.memory_mut(|memory| memory.stop_text_input()));
+        let _ = ctx.run(ctrl_z(), |ctx| app.handle_history_shortcuts(ctx));
+        assert_eq!(app.plugin_manager.plugin_count(), 0);
+    }
+}
@@ -0,0 +1,202 @@
+#[cfg(test)]
+mod lfo_tests {
+    use crate::engine::{AudioBlock, ProcessGraph};
+    use crate::models::cable::PortRef;
+    use crate::models::module::{Module, ProcessContext};
+    use crate::models::plugin::PluginManager;
+    use crate::modules::{lfo, vco};
+    use crate::tests::module_support::{assert_mapped, create, render, SAMPLE_RATE};
+    use crate::vcv::{export_patch, import_patch, VcvPatch};
+    use eframe::egui;
+
+    fn create_lfo(shape: usize) -> Box<dyn Module> {
+        let mut lfo = create("LFO");
+        lfo.set_param(lfo::SHAPE_PARAM, shape as f32);
+        lfo
+    }
+
+    /// Runs `frames` samples and records `output`. `trigger(frame)` tells
+    /// whether the input `trigger_input` is high on that frame.
+    fn render_with(lfo: &mut dyn Module, output: usize, frames: usize, trigger_input: usize, trigger: impl Fn(usize) -> bool) -> Vec<f32> {
+        lfo.io_mut().inputs[trigger_input].set_channels(1);
+        (0..frames)
+            .map(|frame| {
+                lfo.io_mut().inputs[trigger_input].set_voltage(if trigger(frame) { 10.0 } else { 0.0 });
+                lfo.process(&ProcessContext::new(SAMPLE_RATE, frame as u64));
+                lfo.outputs()[output].voltage()
+            })
+            .collect()
+    }
+
+    /// Frames where a saw restarts its cycle.
+    fn wraps(saw: &[f32]) -> Vec<usize> {
+        saw.windows(2).enumerate().filter(|(_, w)| w[1] < w[0] - 1.0).map(|(i, _)| i + 1).collect()
+    }
+
+    #[test]
+    fn test_lfo_is_registered_and_mapped() {
+        let lfo = create_lfo(lfo::SINE_SHAPE);
+        assert_eq!(lfo.outputs().len(), 4);
+        let model = assert_mapped("LFO", "Fundamental", "LFO");
+        assert_eq!(model.param_index(2), Some(lfo::RATE_PARAM));
+        assert_eq!(model.param_index(0), Some(lfo::POLARITY_PARAM));
+        assert_eq!(model.input_index(2), Some(lfo::RESET_INPUT));
+        assert_eq!(model.input_index(4), Some(lfo::CLOCK_INPUT));
+        assert_eq!(model.vcv_output_id(lfo::PHASE_180_OUTPUT), None);
+    }
+
+    #[test]
+    fn test_vcv_lfo_cables_land_on_the_right_jacks() {
+        // Reset (2) and clock (4) cables into VCV Rack's LFO, and one from
+        // its saw output (2) into a VCO
+        let json = r#"{"modules": [
+            {"id": 1, "plugin": "Fundamental", "model": "VCO", "pos": [0, 0]},
+            {"id": 2, "plugin": "Fundamental", "model": "LFO", "pos": [10, 0]}
+        ], "cables": [
+            {"id": 0, "outputModuleId": 1, "outputId": 3, "inputModuleId": 2, "inputId": 2},
+            {"id": 1, "outputModuleId": 1, "outputId": 3, "inputModuleId": 2, "inputId": 4},
+            {"id": 2, "outputModuleId": 2, "outputId": 2, "inputModuleId": 1, "inputId": 0}
+        ]}"#;
+        let (rack, report) = import_patch(&VcvPatch::from_bytes(json.as_bytes()).unwrap());
+        let inputs: Vec<PortRef> = rack.cables.iter().map(|c| c.input).collect();
+        assert_eq!(inputs, vec![PortRef::new(2, lfo::RESET_INPUT), PortRef::new(2, lfo::CLOCK_INPUT)]);
+        assert_eq!(report.dropped_cables, vec!["from output 2 of Fundamental/LFO to input 0 of Fundamental/VCO".to_string()]);
+        let exported = export_patch(&rack);
+        assert_eq!(exported.cables.iter().map(|c| c.input_id).collect::<Vec<_>>(), vec![2, 4]);
+    }
+
+    #[test]
+    fn test_waveforms() {
+        for (phase, sine, triangle, saw, square) in [(0.0, 0.0, 0.0, -1.0, 1.0), (0.25, 1.0, 1.0, -0.5, 1.0), (0.75, -1.0, -1.0, 0.5, -1.0)] {
+            assert!((lfo::waveform(lfo::SINE_SHAPE, phase) - sine).abs() < 1e-6);
+            assert!((lfo::waveform(lfo::TRIANGLE_SHAPE, phase) - triangle).abs() < 1e-6);
+            assert_eq!(lfo::waveform(lfo::SAW_SHAPE, phase), saw);
+            assert_eq!(lfo::waveform(lfo::SQUARE_SHAPE, phase), square);
+        }
+    }
+
+    #[test]
+    fn test_rate_knob_and_cv() {
+        assert_eq!(lfo::rate_freq(1.0, 0.0), 2.0, "Defaults to 2Hz");
+        assert_eq!(lfo::rate_freq(1.0, -2.0), 0.5, "1V/octave");
+
+        let mut lfo = create_lfo(lfo::SAW_SHAPE);
+        lfo.set_param(lfo::RATE_PARAM, 3.0);
+        let saw = render(lfo.as_mut(), lfo::PHASE_0_OUTPUT, 48000);
+        let wraps = wraps(&saw);
+        assert_eq!(wraps.len(), 7, "8Hz from phase 0");
+        let period = (wraps[6] - wraps[0]) as f32 / 6.0;
+        assert!((period - 6000.0).abs() <= lfo::CONTROL_RATE_DIVIDER as f32, "Period of {} samples", period);
+    }
+
+    #[test]
+    fn test_bipolar_and_unipolar() {
+        let mut lfo = create_lfo(lfo::SINE_SHAPE);
+        let sine = render(lfo.as_mut(), lfo::PHASE_0_OUTPUT, 24000);
+        let (min, max) = sine.iter().fold((0.0f32, 0.0f32), |(lo, hi), &v| (lo.min(v), hi.max(v)));
+        assert!(min < -4.99 && max > 4.99 && min >= -5.0 && max <= 5.0);
+
+        lfo.set_param(lfo::POLARITY_PARAM, 1.0);
+        let sine = render(lfo.as_mut(), lfo::PHASE_0_OUTPUT, 24000);
+        let (min, max) = sine.iter().fold((10.0f32, 0.0f32), |(lo, hi), &v| (lo.min(v), hi.max(v)));
+        assert!(min < 0.01 && max > 9.99 && min >= 0.0 && max <= 10.0);
+    }
+
+    #[test]
+    fn test_outputs_are_a_quarter_cycle_apart() {
+        let mut lfo = create_lfo(lfo::TRIANGLE_SHAPE);
+        let mut outputs = vec![Vec::new(); 4];
+        for frame in 0..24000 {
+            lfo.process(&ProcessContext::new(SAMPLE_RATE, frame));
+            for (i, output) in outputs.iter_mut().enumerate() {
+                output.push(lfo.outputs()[lfo::PHASE_0_OUTPUT + i].voltage());
+            }
+        }
+        // At 2Hz a quarter cycle is 6000 samples
+        for i in 1..4 {
+            for frame in (0..24000 - 6000 * i).step_by(500) {
+                let ahead = outputs[0][frame + 6000 * i];
+                assert!((outputs[i][frame] - ahead).abs() < 0.05, "{}°: {} vs {}", 90 * i, outputs[i][frame], ahead);
+            }
+        }
+    }
+
+    #[test]
+    fn test_runs_at_control_rate() {
+        let mut lfo = create_lfo(lfo::SINE_SHAPE);
+        lfo.set_param(lfo::RATE_PARAM, 5.0);
+        let sine = render(lfo.as_mut(), lfo::PHASE_0_OUTPUT, 4800);
+        let changes = sine.windows(2).filter(|w| w[0] != w[1]).count();
+        assert_eq!(changes, 4800 / lfo::CONTROL_RATE_DIVIDER as usize - 1);
+    }
+
+    #[test]
+    fn test_reset_restarts_the_cycle() {
+        let mut lfo = create_lfo(lfo::SAW_SHAPE);
+        let saw = render_with(lfo.as_mut(), lfo::PHASE_0_OUTPUT, 20000, lfo::RESET_INPUT, |frame| (15007..15050).contains(&frame));
+        assert!(saw[15006] > 1.0);
+        assert_eq!(saw[15007], -5.0, "Restarts on the sample of the trigger");
+        assert_eq!(wraps(&saw), vec![15007]);
+    }
+
+    #[test]
+    fn test_clock_locks_the_period() {
+        let mut lfo = create_lfo(lfo::SAW_SHAPE);
+        // 5Hz clock with 1ms pulses, while the knob says 2Hz
+        let clock = |frame: usize| frame % 9600 < 48;
+        let saw = render_with(lfo.as_mut(), lfo::PHASE_0_OUTPUT, 96000, lfo::CLOCK_INPUT, clock);
+        let wraps = wraps(&saw);
+        assert_eq!(wraps, (1..10).map(|n| n * 9600).collect::<Vec<_>>(), "One cycle per clock");
+        // From the second clock on the saw is near its top just before the
+        // next one, so the period matches
+        for &frame in &wraps[1..] {
+            assert!(saw[frame - 1] > 4.9, "Saw at {} V before the clock", saw[frame - 1]);
+        }
+    }
+
+    #[test]
+    fn test_unpatching_the_clock_returns_to_the_knob() {
+        let mut lfo = create_lfo(lfo::SAW_SHAPE);
+        render_with(lfo.as_mut(), lfo::PHASE_0_OUTPUT, 20000, lfo::CLOCK_INPUT, |frame| frame % 9600 < 48);
+        lfo.io_mut().inputs[lfo::CLOCK_INPUT].set_channels(0);
+        let saw = render(lfo.as_mut(), lfo::PHASE_0_OUTPUT, 48000);
+        assert_eq!(wraps(&saw).len(), 2, "2Hz again");
+    }
+
+    #[test]
+    fn test_random_shape_is_smooth_and_bounded() {
+        let mut lfo = create_lfo(lfo::RANDOM_SHAPE);
+        lfo.set_param(lfo::RATE_PARAM, 4.0);
+        let random = render(lfo.as_mut(), lfo::PHASE_0_OUTPUT, 96000);
+        assert!(random.iter().all(|v| v.abs() <= 5.0));
+        let max_step = random.windows(2).map(|w| (w[1] - w[0]).abs()).fold(0.0, f32::max);
+        // 16Hz between random levels at most 10V apart, updated at 3kHz
+        assert!(max_step < 0.2, "Jumps by {} V", max_step);
+        let (min, max) = random.iter().fold((0.0f32, 0.0f32), |(lo, hi), &v| (lo.min(v), hi.max(v)));
+        assert!(max - min > 3.0, "Wanders between {} and {} V", min, max);
+    }
+
+    #[test]
+    fn test_light_follows_the_output() {
+        let mut lfo = create_lfo(lfo::SQUARE_SHAPE);
+        render(lfo.as_mut(), lfo::PHASE_0_OUTPUT, 100);
+        assert_eq!(lfo.lights()[lfo::RATE_LIGHT], 1.0);
+        render(lfo.as_mut(), lfo::PHASE_0_OUTPUT, 14000);
+        assert_eq!(lfo.lights()[lfo::RATE_LIGHT], 0.0);
+    }
+
+    #[test]
+    fn test_lfo_modulates_through_the_engine() {
+        let mut manager = PluginManager::new();
+        let modulator = manager.add_module(&lfo::DESCRIPTOR, egui::pos2(100.0, 100.0), None).unwrap();
+        let oscillator = manager.add_module(&vco::DESCRIPTOR, egui::pos2(300.0, 100.0), None).unwrap();
+        manager.add_cable(PortRef::new(modulator, lfo::PHASE_0_OUTPUT), PortRef::new(oscillator, vco::PITCH_INPUT)).unwrap();
+        manager.set_param(modulator, lfo::POLARITY_PARAM, 1.0);
+
+        let mut graph = ProcessGraph::new();
+        graph.sync(&manager.graph_spec());
+        graph.process_block(&mut AudioBlock::new(6000, 2), SAMPLE_RATE as u32);
+        let pitch = graph.module(oscillator).unwrap().inputs()[vco::PITCH_INPUT].voltage();
+        assert!(pitch > 9.9, "A quarter cycle in the unipolar sine is at its top: {} V", pitch);
+    }
+}
@@ -0,0 +1,128 @@
+#[cfg(test)]
+mod mixer_tests {
+    use crate::engine::AudioEngine;
+    use crate::models::cable::PortRef;
+    use crate::models::geometry::GRID_UNIT;
+    use crate::models::module::Module;
+    use crate::models::plugin::PluginManager;
+    use crate::modules::{audio, mixer, vca, vco};
+    use crate::tests::module_support::{assert_mapped, create, process, set_input};
+    use eframe::egui;
+
+    /// Left and right mix after one sample.
+    fn mix(module: &mut dyn Module) -> (f32, f32) {
+        process(module);
+        (module.outputs()[mixer::LEFT_OUTPUT].voltage(), module.outputs()[mixer::RIGHT_OUTPUT].voltage())
+    }
+
+    #[test]
+    fn test_mixer_is_registered_and_mapped() {
+        let mixer = create("Mixer");
+        assert_eq!(mixer.params().len(), 13);
+        let model = assert_mapped("Mixer", "Fundamental", "VCMixer");
+        assert_eq!(model.param_index(0), Some(mixer::MASTER_PARAM));
+        assert_eq!(model.param_index(1), Some(mixer::LEVEL_PARAM));
+        assert_eq!(model.input_index(5), Some(mixer::LEVEL_CV_INPUT));
+        assert_eq!(model.vcv_output_id(mixer::RIGHT_OUTPUT), None, "VCV Rack's mixer has a mono mix");
+    }
+
+    #[test]
+    fn test_sums_channels_at_unity() {
+        let mut mixer = create("Mixer");
+        set_input(mixer.as_mut(), mixer::IN_INPUT, 1.0);
+        set_input(mixer.as_mut(), mixer::IN_INPUT + 3, 2.0);
+        assert_eq!(mix(mixer.as_mut()), (3.0, 3.0));
+        assert_eq!(mixer.outputs()[mixer::CHANNEL_OUTPUT + 3].voltage(), 2.0);
+        assert_eq!(mixer.outputs()[mixer::CHANNEL_OUTPUT + 1].voltage(), 0.0, "Unpatched channels are silent");
+    }
+
+    #[test]
+    fn test_level_and_master() {
+        let mut mixer = create("Mixer");
+        set_input(mixer.as_mut(), mixer::IN_INPUT + 1, 4.0);
+        mixer.set_param(mixer::LEVEL_PARAM + 1, 0.5);
+        assert_eq!(mix(mixer.as_mut()), (2.0, 2.0));
+        assert_eq!(mixer.outputs()[mixer::CHANNEL_OUTPUT + 1].voltage(), 2.0, "Channel outputs are post-fader");
+        mixer.set_param(mixer::MASTER_PARAM, 0.5);
+        assert_eq!(mix(mixer.as_mut()), (1.0, 1.0));
+        assert_eq!(mixer.outputs()[mixer::CHANNEL_OUTPUT + 1].voltage(), 2.0, "Master only affects the mix");
+    }
+
+    #[test]
+    fn test_level_cv() {
+        let mut mixer = create("Mixer");
+        set_input(mixer.as_mut(), mixer::IN_INPUT, 4.0);
+        set_input(mixer.as_mut(), mixer::LEVEL_CV_INPUT, 5.0);
+        assert_eq!(mix(mixer.as_mut()), (2.0, 2.0));
+        set_input(mixer.as_mut(), mixer::MASTER_CV_INPUT, 2.5);
+        assert_eq!(mix(mixer.as_mut()), (0.5, 0.5));
+    }
+
+    #[test]
+    fn test_mute() {
+        let mut mixer = create("Mixer");
+        set_input(mixer.as_mut(), mixer::IN_INPUT, 1.0);
+        set_input(mixer.as_mut(), mixer::IN_INPUT + 2, 2.0);
+        mixer.set_param(mixer::MUTE_PARAM + 2, 1.0);
+        assert_eq!(mix(mixer.as_mut()), (1.0, 1.0));
+        assert_eq!(mixer.outputs()[mixer::CHANNEL_OUTPUT + 2].voltage(), 0.0);
+    }
+
+    #[test]
+    fn test_pan() {
+        assert_eq!(mixer::pan_gains(0.0), (1.0, 1.0));
+        assert_eq!(mixer::pan_gains(-1.0), (1.0, 0.0));
+        assert_eq!(mixer::pan_gains(0.5), (0.5, 1.0));
+
+        let mut mixer = create("Mixer");
+        set_input(mixer.as_mut(), mixer::IN_INPUT, 2.0);
+        set_input(mixer.as_mut(), mixer::IN_INPUT + 1, 4.0);
+        mixer.set_param(mixer::PAN_PARAM, -1.0);
+        mixer.set_param(mixer::PAN_PARAM + 1, 1.0);
+        assert_eq!(mix(mixer.as_mut()), (2.0, 4.0));
+    }
+
+    #[test]
+    fn test_polyphonic_inputs_stay_polyphonic() {
+        let mut mixer = create("Mixer");
+        let input = &mut mixer.io_mut().inputs[mixer::IN_INPUT];
+        input.set_channels(3);
+        for channel in 0..3 {
+            input.set_voltage_at(channel, channel as f32 + 1.0);
+        }
+        set_input(mixer.as_mut(), mixer::IN_INPUT + 1, 10.0);
+        mix(mixer.as_mut());
+        let left = &mixer.outputs()[mixer::LEFT_OUTPUT];
+        assert_eq!(left.channels, 3);
+        assert_eq!([left.voltage_at(0), left.voltage_at(1), left.voltage_at(2)], [11.0, 2.0, 3.0], "A mono input goes into the first voice");
+        assert_eq!(mixer.outputs()[mixer::CHANNEL_OUTPUT].channels, 3);
+    }
+
+    #[test]
+    fn test_stereo_mix_through_the_engine() {
+        let mut manager = PluginManager::new();
+        let oscillator = manager.add_module(&vco::DESCRIPTOR, egui::pos2(100.0, 100.0), None).unwrap();
+        let amplifier = manager.add_module(&vca::DESCRIPTOR, egui::pos2(100.0 + 10.0 * GRID_UNIT, 100.0), None).unwrap();
+        let mixer = manager.add_module(&mixer::DESCRIPTOR, egui::pos2(100.0 + 16.0 * GRID_UNIT, 100.0), None).unwrap();
+        let output = manager.add_module(&audio::DESCRIPTOR, egui::pos2(100.0 + 28.0 * GRID_UNIT, 100.0), None).unwrap();
+        assert_eq!(manager.get_plugin(amplifier).unwrap().get_width(), 6.0 * GRID_UNIT);
+        assert_eq!(manager.get_plugin(mixer).unwrap().get_width(), 12.0 * GRID_UNIT);
+        manager.add_cable(PortRef::new(oscillator, vco::SQUARE_OUTPUT), PortRef::new(amplifier, vca::IN1_INPUT)).unwrap();
+        manager.add_cable(PortRef::new(amplifier, vca::OUT1_OUTPUT), PortRef::new(mixer, mixer::IN_INPUT)).unwrap();
+        manager.add_cable(PortRef::new(mixer, mixer::LEFT_OUTPUT), PortRef::new(output, audio::LEFT_INPUT)).unwrap();
+        manager.add_cable(PortRef::new(mixer, mixer::RIGHT_OUTPUT), PortRef::new(output, audio::RIGHT_INPUT)).unwrap();
+        manager.set_param(amplifier, vca::LEVEL1_PARAM, 0.5);
+        manager.set_param(mixer, mixer::PAN_PARAM, -0.5);
+
+        let mut engine = AudioEngine::with_null_backend();
+        engine.update_graph(&manager.graph_spec());
+        let block = engine.render_offline(10);
+        // Every cable delays by a sample, so the mix arrives a few frames in
+        assert_eq!(&block.samples[..2], &[0.0; 2]);
+        for frame in block.samples[10..].chunks(2) {
+            let (left, right) = (frame[0], frame[1]);
+            assert!((left.abs() - 0.25).abs() < 1e-3, "Half of the 5V square on the left, at 10V full scale: {}", left);
+            assert!((right - left / 2.0).abs() < 1e-6, "Panned halfway left: {}", right);
+        }
+    }
+}
@@ -0,0 +1,227 @@
+#[cfg(test)]
+mod module_browser_tests {
+    use crate::app::vcvrack_app::VcvRackApp;
+    use crate::models::browser::{self, fuzzy_score, BrowserPrefs, ModuleBrowser, MAX_RECENT};
+    use crate::models::module::{Module, ModuleDescriptor, ModuleIo, ProcessContext};
+    use crate::modules;
+    use eframe::egui;
+
+    struct NoopModule {
+        io: ModuleIo,
+    }
+
+    impl Module for NoopModule {
+        fn descriptor(&self) -> &'static ModuleDescriptor {
+            &OSCILLATOR
+        }
+
+        fn io(&self) -> &ModuleIo {
+            &self.io
+        }
+
+        fn io_mut(&mut self) -> &mut ModuleIo {
+            &mut self.io
+        }
+
+        fn process(&mut self, _ctx: &ProcessContext) {}
+    }
+
+    fn create_noop() -> Box<dyn Module> {
+        Box::new(NoopModule { io: ModuleIo::new(&OSCILLATOR) })
+    }
+
+    const fn descriptor(slug: &'static str, name: &'static str, brand: &'static str, tags: &'static [&'static str]) -> ModuleDescriptor {
+        ModuleDescriptor {
+            slug,
+            name,
+            brand,
+            tags,
+            hp: 4,
+            panel_svg: "res/BlankPlatePlugin.svg",
+            params: &[],
+            inputs: &[],
+            outputs: &[],
+            lights: &[],
+            create: create_noop,
+        }
+    }
+
+    static OSCILLATOR: ModuleDescriptor = descriptor("VCO", "Voltage Controlled Oscillator", "Fundamental", &["Oscillator"]);
+    static FILTER: ModuleDescriptor = descriptor("VCF", "Filter", "Fundamental", &["Filter"]);
+    static SCOPE: ModuleDescriptor = descriptor("Scope", "Scope", "Other", &["Visual"]);
+    static CATALOG: &[&ModuleDescriptor] = &[&SCOPE, &OSCILLATOR, &FILTER];
+
+    fn slugs(results: Vec<&'static ModuleDescriptor>) -> Vec<&'static str> {
+        results.iter().map(|m| m.slug).collect()
+    }
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert!(fuzzy_score("vco", "VCO").is_some(), "Matching ignores case");
+        assert!(fuzzy_score("vlt osc", "Voltage Controlled Oscillator").is_some());
+        assert!(fuzzy_score("ocv", "VCO").is_none(), "Characters must appear in order");
+        assert!(fuzzy_score("fil", "Filter") > fuzzy_score("fil", "Fail"),
+                "Consecutive matches rank higher");
+        assert!(fuzzy_score("o", "Oscillator") > fuzzy_score("o", "Scope"),
+                "Matches at the start of a word rank higher");
+    }
+
+    #[test]
+    fn test_empty_query_lists_everything_by_name() {
+        let browser = ModuleBrowser::default();
+        assert_eq!(slugs(browser.search(CATALOG)), vec!["VCF", "Scope", "VCO"]);
+    }
+
+    #[test]
+    fn test_query_ranks_best_match_first() {
+        let browser = ModuleBrowser { query: "osc".to_string(), ..ModuleBrowser::default() };
+        assert_eq!(slugs(browser.search(CATALOG))[0], "VCO");
+
+        let browser = ModuleBrowser { query: "xyz".to_string(), ..ModuleBrowser::default() };
+        assert!(browser.search(CATALOG).is_empty());
+    }
+
+    #[test]
+    fn test_query_matches_tags_and_brand() {
+        let browser = ModuleBrowser { query: "visual".to_string(), ..ModuleBrowser::default() };
+        assert_eq!(slugs(browser.search(CATALOG)), vec!["Scope"]);
+    }
+
+    #[test]
+    fn test_tag_and_brand_filters() {
+        let browser = ModuleBrowser { tag: Some("Filter"), ..ModuleBrowser::default() };
+        assert_eq!(slugs(browser.search(CATALOG)), vec!["VCF"]);
+
+        let browser = ModuleBrowser { brand: Some("Fundamental"), ..ModuleBrowser::default() };
+        assert_eq!(slugs(browser.search(CATALOG)), vec!["VCF", "VCO"]);
+
+        assert_eq!(browser::all_tags(CATALOG), vec!["Filter", "Oscillator", "Visual"]);
+        assert_eq!(browser::all_brands(CATALOG), vec!["Fundamental", "Other"]);
+    }
+
+    #[test]
+    fn test_favourites() {
+        let mut browser = ModuleBrowser::default();
+        browser.toggle_favorite("Scope");
+        assert!(browser.is_favorite("Scope"));
+        browser.favorites_only = true;
+        assert_eq!(slugs(browser.search(CATALOG)), vec!["Scope"]);
+
+        browser.toggle_favorite("Scope");
+        assert!(browser.search(CATALOG).is_empty());
+    }
+
+    #[test]
+    fn test_recently_used_come_first() {
+        let mut browser = ModuleBrowser::default();
+        browser.record_used("VCO");
+        browser.record_used("Scope");
+        assert_eq!(slugs(browser.search(CATALOG)), vec!["Scope", "VCO", "VCF"]);
+
+        for i in 0..MAX_RECENT + 3 {
+            browser.record_used(&format!("Module{}", i));
+        }
+        assert_eq!(browser.prefs.recent.len(), MAX_RECENT);
+        assert_eq!(browser.prefs.recent[0], format!("Module{}", MAX_RECENT + 2));
+    }
+
+    #[test]
+    fn test_prefs_round_trip() {
+        let path = std::env::temp_dir().join(format!("vcvrack_browser_prefs_{}.json", std::process::id()));
+        let prefs = BrowserPrefs {
+            favorites: vec!["VCO".to_string()],
+            recent: vec!["Scope".to_string(), "VCF".to_string()],
+        };
+        prefs.save(&path).unwrap();
+        assert_eq!(BrowserPrefs::load(&path).unwrap(), prefs);
+        std::fs::remove_file(&path).ok();
+
+        let old: BrowserPrefs = serde_json::from_str("{}").unwrap();
+        assert_eq!(old, BrowserPrefs::default());
+    }
+
+    #[test]
+    fn test_registered_modules_have_brand_and_tags() {
+        for descriptor in modules::MODULES {
+            assert!(!descriptor.brand.is_empty(), "{} has no brand", descriptor.slug);
+            assert!(!descriptor.tags.is_empty(), "{} has no tags", descriptor.slug);
+        }
+    }
+
+    #[test]
+    fn test_browser_places_module_at_target() {
+        let ctx = egui::Context::default();
+        let mut app = VcvRackApp::new_test(&ctx);
+        let state = app.plugin_manager.save_state();
+        let count = state.plugins.len();
+        let target = egui::pos2(100.0 + 200.0 * 15.2, 100.0 + 380.0 * 20.0);
+
+        app.open_module_browser(target);
+        assert!(app.is_module_browser_open());
+        let id = app.place_module(modules::blank_plate()).expect("Target spot is free");
+
+        assert!(!app.is_module_browser_open());
+        assert!(app.has_unsaved_changes);
+        assert_eq!(app.plugin_manager.plugin_count(), count + 1);
+        assert_eq!(app.plugin_manager.get_plugin(id).unwrap().position, target);
+        assert_eq!(app.module_browser.prefs.recent[0], "Blank");
+    }
+
+    #[test]
+    fn test_closed_browser_places_nothing() {
+        let ctx = egui::Context::default();
+        let mut app = VcvRackApp::new_test(&ctx);
+        app.open_module_browser(egui::pos2(100.0, 100.0));
+        app.close_module_browser();
+        assert!(app.place_module(modules::blank_plate()).is_none());
+    }
+
+    #[test]
+    fn test_placed_module_is_remembered_in_the_settings_folder() {
+        let ctx = egui::Context::default();
+        let settings = std::env::temp_dir().join(format!("vcvrack_browser_settings_{}", std::process::id()));
+        std::fs::create_dir_all(&settings).unwrap();
+        let mut app = VcvRackApp::new_test(&ctx);
+        app.settings_dir = Some(settings.clone());
+        app.open_module_browser(egui::pos2(100.0, 100.0));
+        app.place_module(&modules::vco::DESCRIPTOR).expect("Target spot is free");
+
+        let mut restarted = VcvRackApp::new_test(&ctx);
+        restarted.settings_dir = Some(settings.clone());
+        assert_eq!(restarted.load_module_browser().prefs.recent, vec!["VCO".to_string()]);
+        std::fs::remove_dir_all(&settings).ok();
+    }
+
+    #[test]
+    fn test_delete_in_the_search_field_keeps_selected_modules() {
+        let ctx = egui::Context::default();
+        let mut app = VcvRackApp::new_test(&ctx);
+        app.plugin_manager.add_plugin(egui::pos2(100.0, 100.0), None);
+        app.plugin_manager.select_plugin(egui::pos2(105.0, 105.0), 1.0);
+        let count = app.plugin_manager.plugin_count();
+        let delete = || egui::RawInput {
+            events: vec![egui::Event::Key {
+                key: egui::Key::Delete,
+                physical_key: None,
+                pressed: true,
+                repeat: false,
+                modifiers: egui::Modifiers::NONE,
+            }],
+            ..Default::default()
+        };
+
+        app.open_module_browser(egui::pos2(300.0, 100.0));
+        let _ = ctx.run(egui::RawInput::default(), |ctx| app.module_browser_window(ctx));
+        let _ = ctx.run(delete(), |ctx| {
+            app.handle_delete_key(ctx);
+            app.module_browser_window(ctx);
+        });
+        assert_eq!(app.plugin_manager.plugin_count(), count, "Delete edits the search text");
+        assert!(!app.has_unsaved_changes);
+
+        app.close_module_browser();
+        let _ = ctx.run(egui::RawInput::default(), |_| {});
+        let _ = ctx.run(delete(), |ctx| app.handle_delete_key(ctx));
+        assert_eq!(app.plugin_manager.plugin_count(), count - 1);
+    }
+}
@@ -0,0 +1,204 @@
+#[cfg(test)]
+mod module_drag_tests {
+    use crate::models::geometry::{GRID_UNIT, RAIL_COUNT, RAIL_HEIGHT, RAIL_HP};
+    use crate::models::module_id::ModuleId;
+    use crate::models::placement::{self, Span};
+    use crate::models::plugin::{PluginManager, PluginState, RackState};
+    use eframe::egui;
+
+    const RAIL_START_X: f32 = 100.0;
+    const RAIL_Y: f32 = 100.0;
+
+    fn column_x(column: i32) -> f32 {
+        RAIL_START_X + column as f32 * GRID_UNIT
+    }
+
+    /// Blank plates (2HP) at the given columns of the first rail, with ids
+    /// 0, 1, 2... in order.
+    fn create_rack(columns: &[i32]) -> PluginManager {
+        let plugins = columns.iter().zip(0..).map(|(&column, id)| PluginState {
+            rail: 0, column, selected: false, id, slug: "Blank".to_string(), params: Vec::new(),
+        }).collect();
+        let mut manager = PluginManager::new();
+        manager.load_state(RackState { plugins, cables: Vec::new() }, None);
+        manager
+    }
+
+    fn column_of(manager: &PluginManager, id: ModuleId) -> i32 {
+        manager.get_plugin(id).unwrap().grid_column()
+    }
+
+    /// Drags plugin `id` by `columns` HP and `rails` rails and drops it.
+    fn drag(manager: &mut PluginManager, id: ModuleId, columns: f32, rails: f32) -> bool {
+        let start = manager.get_plugin(id).unwrap().position + egui::vec2(5.0, 50.0);
+        manager.begin_module_drag(id, start);
+        manager.drag_modules(start + egui::vec2(columns * GRID_UNIT, rails * RAIL_HEIGHT));
+        manager.end_module_drag().is_some()
+    }
+
+    #[test]
+    fn test_drag_to_free_space() {
+        let mut manager = create_rack(&[0]);
+        assert!(drag(&mut manager, 0, 5.0, 0.0));
+        assert_eq!(column_of(&manager, 0), 5);
+        assert!(!manager.is_dragging_modules());
+    }
+
+    #[test]
+    fn test_drag_snaps_to_hp_grid() {
+        let mut manager = create_rack(&[0]);
+        drag(&mut manager, 0, 3.4, 0.0);
+        let plugin = manager.get_plugin(0).unwrap();
+        assert_eq!(plugin.grid_column(), 3);
+        assert_eq!(plugin.position.x, column_x(3));
+    }
+
+    #[test]
+    fn test_drag_between_rails() {
+        let mut manager = create_rack(&[0]);
+        drag(&mut manager, 0, 0.0, 1.3);
+        let plugin = manager.get_plugin(0).unwrap();
+        assert_eq!(plugin.rail_index(), 1);
+        assert_eq!(plugin.position.y, RAIL_Y + RAIL_HEIGHT, "Plugins snap to the top of the rail");
+    }
+
+    #[test]
+    fn test_small_drag_changes_nothing() {
+        let mut manager = create_rack(&[0]);
+        assert!(!drag(&mut manager, 0, 0.3, 0.2));
+        assert_eq!(column_of(&manager, 0), 0);
+    }
+
+    #[test]
+    fn test_cannot_drag_past_rack_edges() {
+        let mut manager = create_rack(&[4]);
+        drag(&mut manager, 0, -20.0, -3.0);
+        assert_eq!(column_of(&manager, 0), 0);
+        assert_eq!(manager.get_plugin(0).unwrap().rail_index(), 0);
+
+        drag(&mut manager, 0, 0.0, 100.0);
+        assert_eq!(manager.get_plugin(0).unwrap().rail_index(), RAIL_COUNT - 1);
+
+        drag(&mut manager, 0, RAIL_HP as f32 + 50.0, 0.0);
+        assert_eq!(column_of(&manager, 0), RAIL_HP - 2, "Stops with its right edge at the end of the rail");
+    }
+
+    #[test]
+    fn test_neighbour_at_rail_end_is_pushed_left() {
+        let mut manager = create_rack(&[RAIL_HP - 6, RAIL_HP - 2]);
+        assert!(drag(&mut manager, 0, 3.0, 0.0));
+        assert_eq!(column_of(&manager, 0), RAIL_HP - 3);
+        assert_eq!(column_of(&manager, 1), RAIL_HP - 5, "No room right of it, so it goes left");
+    }
+
+    #[test]
+    fn test_drop_on_neighbour_pushes_it_right() {
+        let mut manager = create_rack(&[0, 2]);
+        assert!(drag(&mut manager, 0, 1.0, 0.0));
+        assert_eq!(column_of(&manager, 0), 1);
+        assert_eq!(column_of(&manager, 1), 3);
+    }
+
+    #[test]
+    fn test_drop_on_neighbour_pushes_it_left() {
+        let mut manager = create_rack(&[4, 8]);
+        drag(&mut manager, 1, -3.0, 0.0);
+        assert_eq!(column_of(&manager, 1), 5);
+        assert_eq!(column_of(&manager, 0), 3);
+    }
+
+    #[test]
+    fn test_push_cascades_through_neighbours() {
+        let mut manager = create_rack(&[0, 2, 4, 10]);
+        drag(&mut manager, 0, 2.0, 0.0);
+        assert_eq!(column_of(&manager, 0), 2);
+        assert_eq!(column_of(&manager, 1), 4);
+        assert_eq!(column_of(&manager, 2), 6);
+        assert_eq!(column_of(&manager, 3), 10, "Plugins out of the way stay put");
+    }
+
+    #[test]
+    fn test_layout_follows_pointer_during_drag() {
+        let mut manager = create_rack(&[0, 2]);
+        let start = egui::pos2(column_x(0) + 5.0, RAIL_Y + 50.0);
+        manager.begin_module_drag(0, start);
+        manager.drag_modules(start + egui::vec2(GRID_UNIT, 0.0));
+        assert_eq!(column_of(&manager, 1), 3);
+
+        // Dragging back lets the neighbour return to where it was
+        manager.drag_modules(start);
+        assert_eq!(column_of(&manager, 1), 2);
+        assert!(manager.end_module_drag().is_none());
+    }
+
+    #[test]
+    fn test_selected_plugins_move_together() {
+        let mut manager = create_rack(&[0, 3, 10]);
+        manager.select_plugin(egui::pos2(column_x(0), RAIL_Y), 1.0);
+        manager.select_plugin(egui::pos2(column_x(3), RAIL_Y), 1.0);
+        drag(&mut manager, 0, 2.0, 1.0);
+
+        for (id, column) in [(0, 2), (1, 5)] {
+            let plugin = manager.get_plugin(id).unwrap();
+            assert_eq!((plugin.rail_index(), plugin.grid_column()), (1, column));
+        }
+        assert_eq!(column_of(&manager, 2), 10);
+        assert_eq!(manager.get_plugin(2).unwrap().rail_index(), 0);
+    }
+
+    #[test]
+    fn test_dragging_unselected_plugin_selects_only_it() {
+        let mut manager = create_rack(&[0, 4]);
+        manager.select_plugin(egui::pos2(column_x(0), RAIL_Y), 1.0);
+        drag(&mut manager, 1, 3.0, 0.0);
+
+        assert!(!manager.get_plugin(0).unwrap().is_selected());
+        assert!(manager.get_plugin(1).unwrap().is_selected());
+        assert_eq!(column_of(&manager, 0), 0);
+    }
+
+    #[test]
+    fn test_moves_can_be_reverted() {
+        let mut manager = create_rack(&[0, 2]);
+        let start = egui::pos2(column_x(0) + 5.0, RAIL_Y + 50.0);
+        manager.begin_module_drag(0, start);
+        manager.drag_modules(start + egui::vec2(GRID_UNIT, 0.0));
+        let moves = manager.end_module_drag().unwrap();
+        assert_eq!(moves.len(), 2, "The pushed neighbour is part of the move");
+
+        manager.revert_moves(&moves);
+        assert_eq!((column_of(&manager, 0), column_of(&manager, 1)), (0, 2));
+        manager.apply_moves(&moves);
+        assert_eq!((column_of(&manager, 0), column_of(&manager, 1)), (1, 3));
+    }
+
+    #[test]
+    fn test_push_aside_falls_back_right_at_rail_start() {
+        let moving = [Span::new(1, 1, 2)];
+        let others = [Span::new(0, 0, 2)];
+        assert_eq!(placement::push_aside(&moving, &others), Some(vec![Span::new(0, 3, 2)]));
+    }
+
+    #[test]
+    fn test_push_aside_needs_room_on_the_rail() {
+        let moving = [Span::new(1, RAIL_HP - 3, 2)];
+        let others = [Span::new(0, RAIL_HP - 2, 2)];
+        assert_eq!(placement::push_aside(&moving, &others), Some(vec![Span::new(0, RAIL_HP - 5, 2)]));
+        let others = [Span::new(0, 0, RAIL_HP as u32 - 1)];
+        assert_eq!(placement::push_aside(&moving, &others), None, "The rest of the rail is taken");
+    }
+
+    #[test]
+    fn test_push_aside_never_overlaps() {
+        let moving = [Span::new(9, 4, 4), Span::new(8, 10, 2)];
+        let others = [Span::new(0, 2, 3), Span::new(1, 6, 2), Span::new(2, 9, 4), Span::new(3, 13, 1)];
+        let placed = placement::push_aside(&moving, &others).unwrap();
+        let all: Vec<Span> = moving.iter().chain(placed.iter()).copied().collect();
+        for (i, a) in all.iter().enumerate() {
+            assert!(a.column >= 0);
+            for b in &all[i + 1..] {
+                assert!(a.end() <= b.column || b.end() <= a.column, "{:?} overlaps {:?}", a, b);
+            }
+        }
+    }
+}
@@ -0,0 +1,145 @@
+#[cfg(test)]
+mod module_id_tests {
+    use crate::app::vcvrack_app::VcvRackApp;
+    use crate::models::module_id::{repair_ids, IdAllocator, IdRepair, ModuleId, MAX_MODULE_ID};
+    use crate::models::patch::{Patch, PatchMetadata};
+    use crate::models::plugin::{PluginManager, PluginState, RackState};
+    use crate::modules;
+    use eframe::egui;
+    use std::collections::HashSet;
+
+    fn plate(id: ModuleId, column: i32) -> PluginState {
+        PluginState { rail: 0, column, selected: false, id, slug: "Blank".to_string(), params: Vec::new() }
+    }
+
+    fn ids_of(manager: &PluginManager) -> Vec<ModuleId> {
+        manager.save_state().plugins.iter().map(|p| p.id).collect()
+    }
+
+    fn add_plate(manager: &mut PluginManager, column: i32) -> ModuleId {
+        manager.add_module(modules::blank_plate(), egui::pos2(100.0 + column as f32 * 15.2, 100.0), None).unwrap()
+    }
+
+    fn assert_unique(ids: &[ModuleId]) {
+        assert_eq!(ids.iter().collect::<HashSet<_>>().len(), ids.len(), "Ids {:?} are not unique", ids);
+    }
+
+    #[test]
+    fn test_allocator_skips_ids_in_use() {
+        let mut allocator = IdAllocator::with_seed(7);
+        let mut taken = HashSet::new();
+        for _ in 0..1000 {
+            let id = allocator.allocate(|id| taken.contains(&id));
+            assert!((1..=MAX_MODULE_ID).contains(&id));
+            assert!(taken.insert(id));
+        }
+
+        let first = IdAllocator::with_seed(3).allocate(|_| false);
+        let next = IdAllocator::with_seed(3).allocate(|id| id == first);
+        assert_ne!(first, next);
+    }
+
+    #[test]
+    fn test_repair_keeps_valid_ids() {
+        let ids = [0, 42, MAX_MODULE_ID];
+        let (repaired, repairs) = repair_ids(&ids, &mut IdAllocator::with_seed(1));
+        assert_eq!(repaired, ids.to_vec());
+        assert!(repairs.is_empty());
+    }
+
+    #[test]
+    fn test_repair_replaces_duplicate_and_out_of_range_ids() {
+        let ids = [5, 5, MAX_MODULE_ID + 1, 9];
+        let (repaired, repairs) = repair_ids(&ids, &mut IdAllocator::with_seed(1));
+        assert_eq!((repaired[0], repaired[3]), (5, 9), "The first module with an id keeps it");
+        assert_unique(&repaired);
+        assert_eq!(repairs, vec![
+            IdRepair { index: 1, from: 5, to: repaired[1] },
+            IdRepair { index: 2, from: MAX_MODULE_ID + 1, to: repaired[2] },
+        ]);
+    }
+
+    #[test]
+    fn test_load_state_repairs_ids() {
+        let mut manager = PluginManager::new();
+        let plugins = vec![plate(7, 0), plate(7, 2), plate(u64::MAX, 4)];
+        let repairs = manager.load_state(RackState { plugins, cables: Vec::new() }, None);
+        assert_eq!(repairs.iter().map(|r| r.index).collect::<Vec<_>>(), vec![1, 2]);
+
+        let ids = ids_of(&manager);
+        assert_eq!(ids[0], 7);
+        assert_unique(&ids);
+        assert!(ids.iter().all(|&id| id <= MAX_MODULE_ID));
+    }
+
+    #[test]
+    fn test_opened_patch_reports_repaired_ids() {
+        let ctx = egui::Context::default();
+        let path = std::env::temp_dir().join(format!("vcvrack_module_id_repair_{}.json", std::process::id()));
+        let rack = RackState { plugins: vec![plate(3, 0), plate(3, 2)], cables: Vec::new() };
+        Patch::new(rack, PatchMetadata::default()).save(&path).unwrap();
+
+        let mut app = VcvRackApp::new_test(&ctx);
+        app.load_patch(&path).unwrap();
+        let repaired = ids_of(&app.plugin_manager)[1];
+        let report = app.load_report().expect("The repairs are shown to the user");
+        assert_eq!(report, [format!("Module 1 had the invalid or duplicate id 3, it now has id {}", repaired)]);
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_clean_patch_has_no_load_report() {
+        let ctx = egui::Context::default();
+        let path = std::env::temp_dir().join(format!("vcvrack_module_id_clean_{}.json", std::process::id()));
+        let rack = RackState { plugins: vec![plate(3, 0), plate(4, 2)], cables: Vec::new() };
+        Patch::new(rack, PatchMetadata::default()).save(&path).unwrap();
+
+        let mut app = VcvRackApp::new_test(&ctx);
+        app.load_patch(&path).unwrap();
+        assert!(app.load_report().is_none());
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_loaded_ids_are_never_handed_out_again() {
+        // Fewer plugins than the largest id used to make the next id clash
+        let mut manager = PluginManager::new();
+        manager.load_state(RackState { plugins: vec![plate(1, 0), plate(2, 2)], cables: Vec::new() }, None);
+        for column in 0..20 {
+            add_plate(&mut manager, 4 + column * 2);
+        }
+        let ids = ids_of(&manager);
+        assert_eq!(&ids[..2], &[1, 2]);
+        assert_unique(&ids);
+    }
+
+    #[test]
+    fn test_save_delete_load_add_keeps_identity() {
+        let mut manager = PluginManager::new();
+        let first = add_plate(&mut manager, 0);
+        let deleted = add_plate(&mut manager, 2);
+        let last = add_plate(&mut manager, 4);
+        assert!(manager.delete_plugins(&[deleted]));
+
+        let json = serde_json::to_string(&manager.save_state()).unwrap();
+        let mut reopened = PluginManager::new();
+        assert!(reopened.load_state(serde_json::from_str(&json).unwrap(), None).is_empty());
+        assert_eq!(ids_of(&reopened), vec![first, last], "Modules keep their ids across save and load");
+
+        let added = add_plate(&mut reopened, 2);
+        assert!(![first, last].contains(&added));
+        assert!(reopened.undo());
+        assert!(reopened.redo());
+        assert_eq!(reopened.get_plugin(added).unwrap().grid_column(), 2);
+        assert_unique(&ids_of(&reopened));
+    }
+
+    #[test]
+    fn test_large_ids_survive_json() {
+        let state = RackState { plugins: vec![plate(MAX_MODULE_ID, 0)], cables: Vec::new() };
+        let json = serde_json::to_string(&state).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["plugins"][0]["id"].as_f64(), Some(MAX_MODULE_ID as f64));
+        assert_eq!(value["plugins"][0]["id"].as_f64().unwrap() as ModuleId, MAX_MODULE_ID, "Exact as a double");
+    }
+}
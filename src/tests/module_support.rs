@@ -0,0 +1,66 @@
+//! Fixtures shared by the tests of the modules in `src/modules/`.
+use crate::models::module::{Module, ProcessContext};
+use crate::modules;
+use crate::vcv::mapping::{vcv_model, VcvModel};
+
+pub const SAMPLE_RATE: f32 = 48000.0;
+
+/// Module of type `slug`, created through the registry like the module
+/// browser does.
+pub fn create(slug: &str) -> Box<dyn Module> {
+    modules::create(slug).unwrap_or_else(|| panic!("{} should be registered", slug))
+}
+
+/// Patches a mono cable carrying `voltage` into `input`.
+pub fn set_input(module: &mut dyn Module, input: usize, voltage: f32) {
+    let port = &mut module.io_mut().inputs[input];
+    port.set_channels(1);
+    port.set_voltage(voltage);
+}
+
+/// Patches a polyphonic cable with one voice per voltage into `input`.
+pub fn set_voices(module: &mut dyn Module, input: usize, voltages: &[f32]) {
+    let port = &mut module.io_mut().inputs[input];
+    for (channel, &voltage) in voltages.iter().enumerate() {
+        port.set_voltage_at(channel, voltage);
+    }
+    port.set_channels(voltages.len());
+}
+
+/// Runs the module for one sample.
+pub fn process(module: &mut dyn Module) {
+    module.process(&ProcessContext::new(SAMPLE_RATE, 0));
+}
+
+/// Runs the module for `frames` samples and records `output`.
+pub fn render(module: &mut dyn Module, output: usize, frames: usize) -> Vec<f32> {
+    (0..frames)
+        .map(|frame| {
+            module.process(&ProcessContext::new(SAMPLE_RATE, frame as u64));
+            module.outputs()[output].voltage()
+        })
+        .collect()
+}
+
+/// VCV Rack model of `slug`, once it is checked to be `plugin`/`model` and
+/// to have a param id for each of our params and an entry for each of our
+/// ports, with every VCV Rack port id leading back to the same port.
+pub fn assert_mapped(slug: &str, plugin: &str, model: &str) -> &'static VcvModel {
+    let module = create(slug);
+    let vcv = vcv_model(slug).unwrap_or_else(|| panic!("{} should have a VCV Rack model", slug));
+    assert_eq!((vcv.plugin, vcv.model), (plugin, model));
+    assert!(vcv.params.is_empty() || vcv.params.len() == module.params().len(), "{}: one VCV Rack id per param", slug);
+    assert_eq!(vcv.inputs.len(), module.inputs().len(), "{}: one entry per input", slug);
+    assert_eq!(vcv.outputs.len(), module.outputs().len(), "{}: one entry per output", slug);
+    for (index, id) in vcv.inputs.iter().enumerate() {
+        if let Some(id) = *id {
+            assert_eq!(vcv.input_index(id), Some(index), "{}: VCV Rack input {}", slug, id);
+        }
+    }
+    for (index, id) in vcv.outputs.iter().enumerate() {
+        if let Some(id) = *id {
+            assert_eq!(vcv.output_index(id), Some(index), "{}: VCV Rack output {}", slug, id);
+        }
+    }
+    vcv
+}
@@ -0,0 +1,179 @@
+#[cfg(test)]
+mod module_tests {
+    use crate::engine::{GraphSpec, ModuleSpec, ProcessGraph};
+    use crate::models::module::{Module, ModuleDescriptor, ModuleIo, ParamInfo, Port, PortInfo, ProcessContext};
+    use crate::models::plugin::{Plugin, PluginManager, PluginState};
+    use crate::modules;
+    use eframe::egui;
+
+    static TEST_DESCRIPTOR: ModuleDescriptor = ModuleDescriptor {
+        slug: "TestModule",
+        name: "Test Module",
+        brand: "Test",
+        tags: &[],
+        hp: 4,
+        panel_svg: "res/BlankPlatePlugin.svg",
+        params: &[
+            ParamInfo::knob("Level", 0.0, 10.0, 5.0, "V", (30.0, 60.0)),
+            ParamInfo::switch("Mode", 3, 0.0, (30.0, 120.0)),
+        ],
+        inputs: &[PortInfo::new("In", (30.0, 300.0))],
+        outputs: &[PortInfo::new("Out", (30.0, 340.0))],
+        lights: &[],
+        create: create_test_module,
+    };
+
+    struct TestModule {
+        io: ModuleIo,
+    }
+
+    fn create_test_module() -> Box<dyn Module> {
+        Box::new(TestModule { io: ModuleIo::new(&TEST_DESCRIPTOR) })
+    }
+
+    impl Module for TestModule {
+        fn descriptor(&self) -> &'static ModuleDescriptor {
+            &TEST_DESCRIPTOR
+        }
+
+        fn io(&self) -> &ModuleIo {
+            &self.io
+        }
+
+        fn io_mut(&mut self) -> &mut ModuleIo {
+            &mut self.io
+        }
+
+        fn process(&mut self, _ctx: &ProcessContext) {
+            let out = self.io.inputs[0].voltage() + self.io.params[0];
+            self.io.outputs[0].set_voltage(out);
+        }
+    }
+
+    #[test]
+    fn test_registry_contains_blank_plate() {
+        let blank = modules::find("Blank").expect("Blank plate should be registered");
+        assert_eq!(blank.name, "Blank Plate");
+        assert!(modules::find("DoesNotExist").is_none());
+
+        let module = modules::create("Blank").unwrap();
+        assert_eq!(module.descriptor().slug, "Blank");
+        assert!(module.params().is_empty());
+    }
+
+    #[test]
+    fn test_registered_slugs_are_unique() {
+        for (i, a) in modules::MODULES.iter().enumerate() {
+            for b in &modules::MODULES[i + 1..] {
+                assert_ne!(a.slug, b.slug, "Duplicate module slug {}", a.slug);
+            }
+        }
+    }
+
+    #[test]
+    fn test_new_plugin_is_blank_plate() {
+        let plugin = Plugin::new(egui::pos2(100.0, 100.0), None, 0);
+        assert_eq!(plugin.slug(), "Blank");
+        assert!(plugin.params.is_empty());
+    }
+
+    #[test]
+    fn test_module_io_follows_descriptor() {
+        let module = create_test_module();
+        assert_eq!(module.params(), &[5.0, 0.0]);
+        assert_eq!(module.inputs().len(), 1);
+        assert_eq!(module.outputs().len(), 1);
+        assert!(!module.inputs()[0].is_connected());
+    }
+
+    #[test]
+    fn test_module_process() {
+        let mut module = create_test_module();
+        module.io_mut().inputs[0].set_channels(1);
+        module.io_mut().inputs[0].set_voltage(1.5);
+        module.set_param(0, 2.0);
+        module.process(&ProcessContext::new(48000.0, 0));
+        assert_eq!(module.outputs()[0].voltage(), 3.5);
+    }
+
+    #[test]
+    fn test_param_clamping() {
+        let mut plugin = Plugin::with_module(&TEST_DESCRIPTOR, egui::pos2(100.0, 100.0), None, 0);
+        assert_eq!(plugin.params, vec![5.0, 0.0]);
+
+        assert!(plugin.set_param(0, 42.0));
+        assert_eq!(plugin.params[0], 10.0);
+        assert!(plugin.set_param(1, 1.4));
+        assert_eq!(plugin.params[1], 1.0, "Switches snap to whole positions");
+        assert!(!plugin.set_param(5, 1.0), "Unknown param index should be rejected");
+    }
+
+    #[test]
+    fn test_port_voltages() {
+        let mut port = Port::default();
+        assert_eq!(port.normal_voltage(5.0), 5.0);
+        port.set_channels(1);
+        port.set_voltage(2.0);
+        assert_eq!(port.normal_voltage(5.0), 2.0);
+        assert_eq!(port.poly_voltage(3), 2.0, "Mono signals spread to all channels");
+
+        port.set_channels(40);
+        assert_eq!(port.channels, 16);
+    }
+
+    #[test]
+    fn test_old_state_loads_as_blank_plate() {
+        let json = r#"{"rail": 0, "column": 0, "selected": false, "id": 3}"#;
+        let state: PluginState = serde_json::from_str(json).unwrap();
+        let plugin = Plugin::from_state(state, None);
+        assert_eq!(plugin.slug(), "Blank");
+        assert_eq!(plugin.id, 3);
+    }
+
+    #[test]
+    fn test_unknown_slug_falls_back_to_blank_plate() {
+        let json = r#"{"rail": 0, "column": 0, "selected": false, "id": 1, "slug": "Missing", "params": [1.0]}"#;
+        let state: PluginState = serde_json::from_str(json).unwrap();
+        let plugin = Plugin::from_state(state, None);
+        assert_eq!(plugin.slug(), "Blank");
+        assert!(plugin.params.is_empty());
+    }
+
+    #[test]
+    fn test_state_round_trip_keeps_module_type() {
+        let mut manager = PluginManager::new();
+        manager.add_plugin(egui::pos2(100.0, 100.0), None);
+        let json = serde_json::to_string(&manager.save_state()).unwrap();
+        assert!(json.contains("\"slug\":\"Blank\""));
+
+        let mut restored = PluginManager::new();
+        restored.load_state(serde_json::from_str(&json).unwrap(), None);
+        let plugin = restored.get_plugin_at_position(egui::pos2(100.0, 100.0), 1.0).unwrap();
+        assert_eq!(plugin.slug(), "Blank");
+    }
+
+    #[test]
+    fn test_graph_keeps_module_instances() {
+        let mut graph = ProcessGraph::new();
+        let spec = GraphSpec {
+            modules: vec![
+                ModuleSpec { id: 1, slug: "Blank", params: vec![] },
+                ModuleSpec { id: 2, slug: "Blank", params: vec![] },
+            ],
+            cables: vec![],
+        };
+        graph.sync(&spec);
+        assert_eq!(graph.node_ids(), vec![1, 2]);
+
+        let spec = GraphSpec {
+            modules: vec![
+                ModuleSpec { id: 2, slug: "Blank", params: vec![] },
+                ModuleSpec { id: 3, slug: "Unknown", params: vec![] },
+            ],
+            cables: vec![],
+        };
+        graph.sync(&spec);
+        assert_eq!(graph.node_ids(), vec![2], "Unknown module types are skipped");
+        assert_eq!(graph.module(2).unwrap().descriptor().slug, "Blank");
+    }
+}
@@ -0,0 +1,134 @@
+#[cfg(test)]
+mod module_width_tests {
+    use crate::models::geometry::{GRID_UNIT, RAIL_HEIGHT};
+    use crate::models::module::{Module, ModuleDescriptor, ModuleIo, ProcessContext};
+    use crate::models::plugin::{Plugin, PluginManager};
+    use crate::modules;
+    use eframe::egui;
+
+    const TEST_ZOOM: f32 = 1.0;
+    const RAIL_START_X: f32 = 100.0;
+    const RAIL_Y: f32 = 100.0;
+
+    static WIDE_DESCRIPTOR: ModuleDescriptor = ModuleDescriptor {
+        slug: "Wide",
+        name: "Wide Module",
+        brand: "Test",
+        tags: &[],
+        hp: 10,
+        panel_svg: "res/BlankPlatePlugin.svg",
+        params: &[],
+        inputs: &[],
+        outputs: &[],
+        lights: &[],
+        create: create_wide_module,
+    };
+
+    struct WideModule {
+        io: ModuleIo,
+    }
+
+    fn create_wide_module() -> Box<dyn Module> {
+        Box::new(WideModule { io: ModuleIo::new(&WIDE_DESCRIPTOR) })
+    }
+
+    impl Module for WideModule {
+        fn descriptor(&self) -> &'static ModuleDescriptor {
+            &WIDE_DESCRIPTOR
+        }
+
+        fn io(&self) -> &ModuleIo {
+            &self.io
+        }
+
+        fn io_mut(&mut self) -> &mut ModuleIo {
+            &mut self.io
+        }
+
+        fn process(&mut self, _ctx: &ProcessContext) {}
+    }
+
+    fn column_x(column: i32) -> f32 {
+        RAIL_START_X + column as f32 * GRID_UNIT
+    }
+
+    #[test]
+    fn test_width_follows_descriptor() {
+        let blank = Plugin::new(egui::pos2(RAIL_START_X, RAIL_Y), None, 0);
+        assert_eq!(blank.hp(), 2);
+        assert!((blank.get_width() - 30.4).abs() < 0.001);
+
+        let wide = Plugin::with_module(&WIDE_DESCRIPTOR, egui::pos2(RAIL_START_X, RAIL_Y), None, 1);
+        assert!((wide.get_width() - 10.0 * GRID_UNIT).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_panel_svgs_match_descriptor_width() {
+        for descriptor in modules::MODULES {
+            let svg = std::fs::read_to_string(descriptor.panel_svg)
+                .unwrap_or_else(|_| panic!("Missing panel {}", descriptor.panel_svg));
+            let tree = usvg::Tree::from_str(&svg, &usvg::Options::default()).unwrap();
+            let expected_width = descriptor.hp as f32 * GRID_UNIT;
+            assert!((tree.size().width() - expected_width).abs() < 0.5,
+                    "{} panel is {}px wide, expected {}px for {}HP",
+                    descriptor.slug, tree.size().width(), expected_width, descriptor.hp);
+            assert!((tree.size().height() - RAIL_HEIGHT).abs() < 0.5);
+        }
+    }
+
+    #[test]
+    fn test_hit_test_covers_full_panel() {
+        let wide = Plugin::with_module(&WIDE_DESCRIPTOR, egui::pos2(RAIL_START_X, RAIL_Y), None, 0);
+        assert!(wide.is_at_position(egui::pos2(column_x(9) + 1.0, RAIL_Y + RAIL_HEIGHT - 1.0), TEST_ZOOM),
+                "Bottom right corner of the panel should hit");
+        assert!(!wide.is_at_position(egui::pos2(column_x(10), RAIL_Y), TEST_ZOOM),
+                "First column after the panel should miss");
+        assert!(!wide.is_at_position(egui::pos2(RAIL_START_X, RAIL_Y + RAIL_HEIGHT), TEST_ZOOM),
+                "Next rail should miss");
+    }
+
+    #[test]
+    fn test_add_rejects_overlap_anywhere_in_span() {
+        let mut manager = PluginManager::new();
+        manager.add_module(&WIDE_DESCRIPTOR, egui::pos2(column_x(4), RAIL_Y), None).unwrap();
+
+        // Blank plate whose right half would cover the wide module's first column
+        assert!(manager.add_module(modules::blank_plate(), egui::pos2(column_x(3), RAIL_Y), None).is_none());
+        // Inside the wide module
+        assert!(manager.add_module(modules::blank_plate(), egui::pos2(column_x(8), RAIL_Y), None).is_none());
+        // Wide module whose span ends inside the existing one
+        assert!(manager.add_module(&WIDE_DESCRIPTOR, egui::pos2(column_x(0), RAIL_Y), None).is_none());
+        assert_eq!(manager.plugin_count(), 1);
+
+        // Directly next to it on both sides
+        assert!(manager.add_module(modules::blank_plate(), egui::pos2(column_x(2), RAIL_Y), None).is_some());
+        assert!(manager.add_module(modules::blank_plate(), egui::pos2(column_x(14), RAIL_Y), None).is_some());
+        assert_eq!(manager.plugin_count(), 3);
+    }
+
+    #[test]
+    fn test_same_columns_on_other_rail_are_free() {
+        let mut manager = PluginManager::new();
+        manager.add_module(&WIDE_DESCRIPTOR, egui::pos2(column_x(0), RAIL_Y), None).unwrap();
+        assert!(manager.add_module(&WIDE_DESCRIPTOR, egui::pos2(column_x(0), RAIL_Y + RAIL_HEIGHT), None).is_some());
+    }
+
+    #[test]
+    fn test_is_span_free() {
+        let mut manager = PluginManager::new();
+        let id = manager.add_module(&WIDE_DESCRIPTOR, egui::pos2(column_x(4), RAIL_Y), None).unwrap();
+        assert!(!manager.is_span_free(0, 13, 2, None));
+        assert!(manager.is_span_free(0, 14, 2, None));
+        assert!(manager.is_span_free(0, 4, 10, Some(id)), "A plugin does not collide with itself");
+        assert!(!manager.is_span_free(0, -1, 1, None), "Columns left of the rack are never free");
+    }
+
+    #[test]
+    fn test_click_inside_wide_module_selects_it() {
+        let mut manager = PluginManager::new();
+        manager.add_module(&WIDE_DESCRIPTOR, egui::pos2(column_x(0), RAIL_Y), None).unwrap();
+        let inside = egui::pos2(column_x(7), RAIL_Y + 200.0);
+        manager.select_plugin(inside, TEST_ZOOM);
+        assert!(manager.get_plugin_at_position(inside, TEST_ZOOM).unwrap().is_selected());
+    }
+}
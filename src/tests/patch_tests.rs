@@ -0,0 +1,99 @@
+#[cfg(test)]
+mod patch_tests {
+    use crate::models::geometry::{GridPos, RackGeometry};
+    use crate::models::patch::{Patch, PatchMetadata, FORMAT_VERSION};
+    use crate::models::plugin::{Plugin, PluginManager};
+
+    /// Unversioned rack saved with pixel positions.
+    const PIXEL_RACK: &str = r#"{"plugins": [{"x": 130.0, "y": 485.0, "selected": false, "id": 2}]}"#;
+    /// Unversioned rack saved with grid positions.
+    const GRID_RACK: &str = r#"{"plugins": [{"rail": 3, "column": 7, "selected": false, "id": 0, "slug": "Blank"}], "cables": []}"#;
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("vcvrack_patch_{}_{}.json", name, std::process::id()))
+    }
+
+    #[test]
+    fn test_pixel_rack_runs_every_migration() {
+        let loaded = Patch::from_json(PIXEL_RACK).unwrap();
+        assert_eq!(loaded.migrations.len(), 2);
+        assert_eq!(loaded.patch.version, FORMAT_VERSION);
+        assert_eq!(loaded.patch.metadata, PatchMetadata::default());
+
+        let state = &loaded.patch.rack.plugins[0];
+        assert_eq!((state.rail, state.column), (1, 2), "Pixel positions snap to the nearest grid cell");
+        assert_eq!(state.slug, "Blank");
+
+        let plugin = Plugin::from_state(state.clone(), None);
+        assert_eq!(plugin.position, RackGeometry::grid_to_rack(GridPos::new(1, 2)));
+    }
+
+    #[test]
+    fn test_grid_rack_only_gets_wrapped() {
+        let loaded = Patch::from_json(GRID_RACK).unwrap();
+        assert_eq!(loaded.migrations, vec!["Add format version and metadata"]);
+        let state = &loaded.patch.rack.plugins[0];
+        assert_eq!((state.rail, state.column), (3, 7));
+    }
+
+    #[test]
+    fn test_current_format_needs_no_migration() {
+        let mut manager = PluginManager::new();
+        manager.add_plugin(RackGeometry::grid_to_rack(GridPos::new(2, 5)), None);
+        let metadata = PatchMetadata {
+            author: "Someone".to_string(),
+            description: "Test patch".to_string(),
+            ..PatchMetadata::default()
+        };
+        let json = serde_json::to_string(&Patch::new(manager.save_state(), metadata.clone())).unwrap();
+
+        let loaded = Patch::from_json(&json).unwrap();
+        assert!(loaded.migrations.is_empty());
+        assert_eq!(loaded.patch.metadata, metadata);
+        assert_eq!(loaded.patch.rack.plugins.len(), 1);
+    }
+
+    #[test]
+    fn test_newer_format_is_rejected() {
+        let json = format!(r#"{{"version": {}, "rack": {{"plugins": []}}}}"#, FORMAT_VERSION + 1);
+        let err = Patch::from_json(&json).unwrap_err();
+        assert!(err.to_string().contains("newer"), "{}", err);
+    }
+
+    #[test]
+    fn test_non_patch_is_rejected() {
+        assert!(Patch::from_json(r#"{"modules": []}"#).is_err());
+        assert!(Patch::from_json("not json").is_err());
+    }
+
+    #[test]
+    fn test_touched_metadata() {
+        let metadata = PatchMetadata { created: 1000, author: "Someone".to_string(), ..PatchMetadata::default() };
+        let touched = metadata.touched();
+        assert_eq!(touched.created, 1000, "Creation time is kept");
+        assert!(touched.modified > 1000);
+        assert_eq!(touched.app_version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(touched.author, "Someone");
+
+        let new = PatchMetadata::default().touched();
+        assert_eq!(new.created, new.modified);
+    }
+
+    #[test]
+    fn test_save_and_load_file() {
+        let path = temp_path("round_trip");
+        std::fs::write(&path, PIXEL_RACK).unwrap();
+        let loaded = Patch::load(&path).unwrap();
+        assert_eq!(loaded.migrations.len(), 2);
+
+        loaded.patch.save(&path).unwrap();
+        let saved: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved["version"], FORMAT_VERSION);
+        assert!(saved["metadata"].is_object());
+        assert_eq!(saved["rack"]["plugins"][0]["column"], 2);
+
+        let reloaded = Patch::load(&path).unwrap();
+        assert!(reloaded.migrations.is_empty(), "Saved files are in the current format");
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -1,6 +1,8 @@
 #[cfg(test)]
 mod tests {
-    use crate::models::plugin::{Plugin, PluginManager};
+    use crate::models::geometry::{GridPos, RackGeometry, RAIL_COUNT};
+    use crate::models::plugin::{Plugin, PluginManager, PluginState, RackState};
+    use crate::modules;
     use eframe::egui;
 
     const TEST_ZOOM: f32 = 1.0;
@@ -231,4 +233,18 @@ mod tests {
         assert_eq!(plugin1.position, pos1, "First plugin should be at original position");
         assert_eq!(plugin2.position, pos2, "Second plugin should be at original position");
     }
+
+    #[test]
+    fn test_modules_stay_on_the_rails() {
+        let mut manager = PluginManager::new();
+        let below = RackGeometry::grid_to_rack(GridPos::new(RAIL_COUNT + 3, 4));
+        let id = manager.add_module(modules::blank_plate(), below, None).unwrap();
+        assert_eq!(manager.get_plugin(id).unwrap().grid(), GridPos::new(RAIL_COUNT - 1, 4), "Moved up to the last rail");
+        assert!(manager.add_module(modules::blank_plate(), below, None).is_none(), "That spot on the last rail is taken now");
+
+        let plate = |id, rail| PluginState { rail, column: 2, selected: false, id, slug: "Blank".to_string(), params: Vec::new() };
+        manager.load_state(RackState { plugins: vec![plate(1, RAIL_COUNT + 10), plate(2, -3)], cables: Vec::new() }, None);
+        assert_eq!(manager.get_plugin(1).unwrap().grid(), GridPos::new(RAIL_COUNT - 1, 2));
+        assert_eq!(manager.get_plugin(2).unwrap().grid(), GridPos::new(0, 2));
+    }
 }
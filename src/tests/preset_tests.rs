@@ -0,0 +1,183 @@
+#[cfg(test)]
+mod preset_tests {
+    use crate::app::vcvrack_app::VcvRackApp;
+    use crate::models::geometry::{GridPos, RackGeometry};
+    use crate::models::module::{Module, ModuleDescriptor, ModuleIo, ParamInfo, ProcessContext};
+    use crate::models::module_id::ModuleId;
+    use crate::models::plugin::PluginManager;
+    use crate::models::preset::{list_presets, preset_directory, preset_name, random_params, Preset, PresetAction};
+    use eframe::egui;
+    use std::path::{Path, PathBuf};
+
+    static KNOB_DESCRIPTOR: ModuleDescriptor = ModuleDescriptor {
+        slug: "PresetTest",
+        name: "Preset Test",
+        brand: "Test",
+        tags: &[],
+        hp: 4,
+        panel_svg: "res/BlankPlatePlugin.svg",
+        params: &[
+            ParamInfo::knob("Level", 0.0, 10.0, 5.0, "V", (30.0, 100.0)),
+            ParamInfo::switch("Range", 3, 1.0, (30.0, 200.0)),
+        ],
+        inputs: &[],
+        outputs: &[],
+        lights: &[],
+        create: create_knob_module,
+    };
+
+    struct KnobModule {
+        io: ModuleIo,
+    }
+
+    fn create_knob_module() -> Box<dyn Module> {
+        Box::new(KnobModule { io: ModuleIo::new(&KNOB_DESCRIPTOR) })
+    }
+
+    impl Module for KnobModule {
+        fn descriptor(&self) -> &'static ModuleDescriptor {
+            &KNOB_DESCRIPTOR
+        }
+
+        fn io(&self) -> &ModuleIo {
+            &self.io
+        }
+
+        fn io_mut(&mut self) -> &mut ModuleIo {
+            &mut self.io
+        }
+
+        fn process(&mut self, _ctx: &ProcessContext) {}
+    }
+
+    fn manager_with_knobs() -> (PluginManager, ModuleId) {
+        let mut manager = PluginManager::new();
+        let id = manager.add_module(&KNOB_DESCRIPTOR, RackGeometry::grid_to_rack(GridPos::new(0, 2)), None).unwrap();
+        (manager, id)
+    }
+
+    fn params(manager: &PluginManager, id: ModuleId) -> Vec<f32> {
+        manager.get_plugin(id).unwrap().params.clone()
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("vcvrack_presets_{}_{}", name, std::process::id()));
+        std::fs::remove_dir_all(&dir).ok();
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_preset_params_are_fitted_to_the_module() {
+        let preset = Preset { slug: "PresetTest".to_string(), params: vec![12.0] };
+        assert_eq!(preset.params_for(&KNOB_DESCRIPTOR).unwrap(), vec![10.0, 1.0], "clamped, missing params default");
+
+        let other = Preset { slug: "Blank".to_string(), params: vec![] };
+        assert!(other.params_for(&KNOB_DESCRIPTOR).is_err());
+    }
+
+    #[test]
+    fn test_load_preset_is_one_undo_step() {
+        let (mut manager, id) = manager_with_knobs();
+        let preset = Preset { slug: "PresetTest".to_string(), params: vec![2.5, 2.0] };
+
+        assert!(manager.load_preset(id, &preset).unwrap());
+        assert_eq!(params(&manager, id), vec![2.5, 2.0]);
+        assert_eq!(manager.history().undo_name(), Some("Load preset"));
+        assert!(!manager.load_preset(id, &preset).unwrap(), "loading the same values changes nothing");
+
+        assert!(manager.undo());
+        assert_eq!(params(&manager, id), vec![5.0, 1.0]);
+        assert!(manager.redo());
+        assert_eq!(params(&manager, id), vec![2.5, 2.0]);
+    }
+
+    #[test]
+    fn test_initialize_restores_defaults() {
+        let (mut manager, id) = manager_with_knobs();
+        manager.set_param(id, 0, 8.0);
+        manager.set_param(id, 1, 0.0);
+
+        assert!(manager.apply_preset_action(id, PresetAction::Initialize));
+        assert_eq!(params(&manager, id), vec![5.0, 1.0]);
+        assert_eq!(manager.history().undo_name(), Some("Initialize module"));
+        assert!(!manager.initialize_params(id), "already at the defaults");
+    }
+
+    #[test]
+    fn test_randomize_stays_in_range() {
+        let mut rng = fastrand::Rng::with_seed(7);
+        for _ in 0..100 {
+            let values = random_params(&KNOB_DESCRIPTOR, &mut rng);
+            assert!((0.0..=10.0).contains(&values[0]));
+            assert!([0.0, 1.0, 2.0].contains(&values[1]), "switches land on a position: {}", values[1]);
+        }
+
+        let (mut manager, id) = manager_with_knobs();
+        assert!(manager.randomize_params(id, &mut fastrand::Rng::with_seed(3)));
+        assert_eq!(manager.history().undo_name(), Some("Randomize module"));
+    }
+
+    #[test]
+    fn test_presets_are_listed_per_module_type() {
+        let save_dir = temp_dir("list");
+        let dir = preset_directory(&save_dir, "PresetTest");
+        assert_eq!(dir, save_dir.join("presets").join("PresetTest"));
+        let preset = Preset { slug: "PresetTest".to_string(), params: vec![1.0, 0.0] };
+        preset.save(&dir.join("bass.vcvm")).unwrap();
+        preset.save(&dir.join("Arp.vcvm")).unwrap();
+        std::fs::write(dir.join("notes.txt"), "not a preset").unwrap();
+
+        let presets = list_presets(&dir);
+        assert_eq!(presets.iter().map(|p| preset_name(p)).collect::<Vec<_>>(), vec!["Arp", "bass"]);
+        assert!(list_presets(Path::new("does/not/exist")).is_empty());
+        std::fs::remove_dir_all(&save_dir).ok();
+    }
+
+    #[test]
+    fn test_load_preset_action_reads_file() {
+        let dir = temp_dir("load");
+        let path = dir.join("soft.vcvm");
+        Preset { slug: "PresetTest".to_string(), params: vec![1.5, 0.0] }.save(&path).unwrap();
+
+        let (mut manager, id) = manager_with_knobs();
+        assert!(manager.apply_preset_action(id, PresetAction::Load(path)));
+        assert_eq!(params(&manager, id), vec![1.5, 0.0]);
+        assert!(!manager.apply_preset_action(id, PresetAction::Load(dir.join("missing.vcvm"))));
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_save_preset_from_app() {
+        let ctx = egui::Context::default();
+        let mut app = VcvRackApp::new_test(&ctx);
+        let (manager, id) = manager_with_knobs();
+        app.plugin_manager = manager;
+        app.plugin_manager.set_param(id, 0, 7.0);
+
+        assert!(!app.plugin_manager.apply_preset_action(id, PresetAction::Save), "saving changes no params");
+        assert_eq!(app.plugin_manager.take_preset_save_request(), Some(id));
+        assert_eq!(app.plugin_manager.take_preset_save_request(), None);
+
+        let path = temp_dir("app").join("loud.vcvm");
+        app.save_preset(id, &path).unwrap();
+        assert_eq!(Preset::load(&path).unwrap(), Preset { slug: "PresetTest".to_string(), params: vec![7.0, 1.0] });
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_presets_are_kept_in_the_settings_folder() {
+        let ctx = egui::Context::default();
+        let mut app = VcvRackApp::new_test(&ctx);
+        assert_eq!(app.presets_directory("VCO"), None);
+
+        let settings = temp_dir("settings");
+        app.settings_dir = Some(settings.clone());
+        let dir = app.presets_directory("VCO").unwrap();
+        assert_eq!(dir, preset_directory(&settings, "VCO"));
+        Preset { slug: "VCO".to_string(), params: Vec::new() }.save(&dir.join("lead.vcvm")).unwrap();
+        app.refresh_presets();
+        assert_eq!(app.plugin_manager.presets("VCO"), [dir.join("lead.vcvm")]);
+        std::fs::remove_dir_all(&settings).ok();
+    }
+}
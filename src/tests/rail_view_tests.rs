@@ -0,0 +1,88 @@
+#[cfg(test)]
+mod rail_view_tests {
+    use crate::app::rail_view::{RailLayout, RAIL_COLUMNS, RAIL_ROWS, RAIL_TILE_SIZE};
+    use eframe::egui;
+
+    fn viewport() -> egui::Rect {
+        egui::Rect::from_min_size(egui::pos2(0.0, 0.0), egui::vec2(1280.0, 720.0))
+    }
+
+    fn huge_rack() -> RailLayout {
+        RailLayout { columns: RAIL_COLUMNS * 100, rows: RAIL_ROWS * 100, ..RailLayout::default() }
+    }
+
+    /// Vertices the GPU receives for one frame that paints the rails.
+    fn frame_vertices(layout: RailLayout, viewport: egui::Rect) -> usize {
+        let ctx = egui::Context::default();
+        let input = egui::RawInput {
+            screen_rect: Some(viewport),
+            ..Default::default()
+        };
+        let output = ctx.run(input, |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                ui.painter().add(layout.mesh(egui::TextureId::default(), ui.clip_rect()));
+            });
+        });
+        ctx.tessellate(output.shapes, output.pixels_per_point)
+            .iter()
+            .map(|primitive| match &primitive.primitive {
+                egui::epaint::Primitive::Mesh(mesh) => mesh.vertices.len(),
+                egui::epaint::Primitive::Callback(_) => 0,
+            })
+            .sum()
+    }
+
+    #[test]
+    fn test_visible_tiles_cover_viewport() {
+        let layout = RailLayout::default();
+        let (columns, rows) = layout.visible_tiles(viewport());
+        // 1280 / 304 = 4.2 and 720 / 380 = 1.9 tiles
+        assert_eq!((columns, rows), (0..5, 0..2));
+    }
+
+    #[test]
+    fn test_visible_tiles_follow_scrolling() {
+        let layout = RailLayout::default();
+        let scrolled = viewport().translate(egui::vec2(RAIL_TILE_SIZE.x * 10.5, RAIL_TILE_SIZE.y * 3.0));
+        let (columns, rows) = layout.visible_tiles(scrolled);
+        assert_eq!((columns, rows), (10..15, 3..5));
+    }
+
+    #[test]
+    fn test_visible_tiles_clamped_to_rack() {
+        let layout = RailLayout::default();
+        let past_end = viewport().translate(layout.size());
+        let (columns, rows) = layout.visible_tiles(past_end);
+        assert!(columns.is_empty() && rows.is_empty());
+
+        let before_start = viewport().translate(-layout.size());
+        let (columns, rows) = layout.visible_tiles(before_start);
+        assert!(columns.is_empty() && rows.is_empty());
+    }
+
+    #[test]
+    fn test_tile_at() {
+        let layout = RailLayout::default();
+        assert_eq!(layout.tile_at(egui::pos2(10.0, 10.0)), Some((0, 0)));
+        assert_eq!(layout.tile_at(egui::pos2(RAIL_TILE_SIZE.x * 2.5, RAIL_TILE_SIZE.y * 1.5)), Some((2, 1)));
+        assert_eq!(layout.tile_at(egui::pos2(-1.0, 10.0)), None);
+        assert_eq!(layout.tile_at(layout.origin + layout.size()), None);
+    }
+
+    #[test]
+    fn test_mesh_only_contains_visible_tiles() {
+        let mesh = RailLayout::default().mesh(egui::TextureId::default(), viewport());
+        assert_eq!(mesh.vertices.len(), 5 * 2 * 4, "Four vertices per visible tile");
+    }
+
+    #[test]
+    fn test_frame_cost_independent_of_rack_size() {
+        let normal = frame_vertices(RailLayout::default(), viewport());
+        let huge = frame_vertices(huge_rack(), viewport());
+        assert!(normal > 0);
+        assert_eq!(normal, huge, "A 10000 times bigger rack paints the same number of vertices");
+
+        let full_rack_vertices = RAIL_COLUMNS * RAIL_ROWS * 4;
+        assert!(normal * 100 < full_rack_vertices);
+    }
+}
@@ -0,0 +1,191 @@
+#[cfg(test)]
+mod selection_tests {
+    use crate::app::vcvrack_app::VcvRackApp;
+    use crate::models::cable::{Cable, CableColor, PortRef};
+    use crate::models::geometry::{GridPos, RackGeometry, RAIL_COUNT, RAIL_HP};
+    use crate::models::module_id::ModuleId;
+    use crate::models::plugin::{PluginManager, PluginState, RackState};
+    use crate::models::selection::Selection;
+    use eframe::egui;
+    use std::path::PathBuf;
+
+    fn plate(id: ModuleId, rail: i32, column: i32) -> PluginState {
+        PluginState { rail, column, selected: true, id, slug: "Blank".to_string(), params: Vec::new() }
+    }
+
+    fn cable(id: usize, output: ModuleId, input: ModuleId) -> Cable {
+        Cable { id, output: PortRef::new(output, 0), input: PortRef::new(input, 1), color: CableColor([1, 2, 3]) }
+    }
+
+    /// Plates at (rail 0, HP 0) and (0, 2) with ids 0 and 1, and (1, 2) with id 2.
+    fn manager_with_plates() -> PluginManager {
+        let mut manager = PluginManager::new();
+        let plugins = vec![plate(0, 0, 0), plate(1, 0, 2), plate(2, 1, 2)];
+        manager.load_state(RackState { plugins, cables: Vec::new() }, None);
+        manager
+    }
+
+    fn grid_of(manager: &PluginManager, id: ModuleId) -> GridPos {
+        manager.get_plugin(id).unwrap().grid()
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("vcvrack_selection_{}_{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_selection_is_relative_and_keeps_internal_cables() {
+        let plugins = vec![plate(4, 3, 10), plate(7, 2, 14)];
+        let cables = [cable(0, 4, 7), cable(1, 7, 9), cable(2, 9, 4)];
+        let selection = Selection::new(plugins, &cables);
+
+        assert_eq!((selection.plugins[0].rail, selection.plugins[0].column), (1, 0));
+        assert_eq!((selection.plugins[1].rail, selection.plugins[1].column), (0, 4));
+        assert!(selection.plugins.iter().all(|p| !p.selected));
+        assert_eq!(selection.cables, vec![cable(0, 4, 7)], "cables leaving the selection are dropped");
+        assert_eq!(selection.height(), 2);
+    }
+
+    #[test]
+    fn test_placed_remaps_ids() {
+        let selection = Selection::new(vec![plate(4, 0, 0), plate(7, 1, 6)], &[cable(3, 4, 7), cable(5, 7, 4)]);
+        let placed = selection.placed(GridPos::new(2, 10), &[20, 21], 8);
+
+        assert_eq!(placed.plugins.iter().map(|p| p.id).collect::<Vec<_>>(), vec![20, 21]);
+        assert_eq!((placed.plugins[1].rail, placed.plugins[1].column), (3, 16));
+        assert_eq!(placed.cables[0], Cable { id: 8, output: PortRef::new(20, 0), input: PortRef::new(21, 1), color: CableColor([1, 2, 3]) });
+        assert_eq!(placed.cables[1].id, 9);
+        assert_eq!((placed.cables[1].output.module_id, placed.cables[1].input.module_id), (21, 20));
+    }
+
+    #[test]
+    fn test_insert_selection_keeps_layout() {
+        let mut manager = manager_with_plates();
+        let selection = Selection::new(vec![plate(0, 0, 0), plate(2, 1, 2)], &[]);
+
+        let ids = manager.insert_selection(&selection, GridPos::new(5, 40));
+        assert_eq!(ids.len(), 2);
+        assert!(ids.iter().all(|id| *id > 2) && ids[0] != ids[1], "fresh ids: {:?}", ids);
+        assert_eq!(grid_of(&manager, ids[0]), GridPos::new(5, 40));
+        assert_eq!(grid_of(&manager, ids[1]), GridPos::new(6, 42));
+        let selected: Vec<ModuleId> = manager.get_selected_plugins().iter().map(|p| p.id).collect();
+        assert_eq!(selected, ids, "only the new plugins are selected");
+    }
+
+    #[test]
+    fn test_insert_selection_avoids_occupied_spans() {
+        let mut manager = manager_with_plates();
+        let selection = Selection::new(vec![plate(0, 0, 0), plate(1, 1, 0)], &[]);
+
+        // Rail 0 is taken up to HP 4 and rail 1 from HP 2 to 4
+        let ids = manager.insert_selection(&selection, GridPos::new(0, 1));
+        assert_eq!(grid_of(&manager, ids[0]), GridPos::new(0, 4));
+        assert_eq!(grid_of(&manager, ids[1]), GridPos::new(1, 4));
+    }
+
+    #[test]
+    fn test_free_position_prefers_nearest_side() {
+        let selection = Selection::new(vec![plate(0, 0, 0)], &[]);
+        // Columns 10 to 13 are taken on every rail
+        let is_free = |_rail: i32, column: i32, hp: u32| column + hp as i32 <= 10 || column >= 14;
+        assert_eq!(selection.free_position(GridPos::new(0, 10), is_free), Some(GridPos::new(0, 8)));
+        assert_eq!(selection.free_position(GridPos::new(0, 12), is_free), Some(GridPos::new(0, 14)));
+        assert_eq!(selection.free_position(GridPos::new(0, 11), is_free), Some(GridPos::new(0, 14)), "right wins a tie");
+
+        let tall = Selection::new(vec![plate(0, 0, 0), plate(1, 2, 0)], &[]);
+        assert_eq!(tall.free_position(GridPos::new(RAIL_COUNT + 5, 0), |_, _, _| true).unwrap().rail, RAIL_COUNT - 3);
+        assert_eq!(tall.free_position(GridPos::new(-4, 0), |_, _, _| true).unwrap().rail, 0);
+    }
+
+    #[test]
+    fn test_selection_taller_than_the_rack_is_rejected() {
+        let mut manager = manager_with_plates();
+        let too_tall = Selection::new(vec![plate(0, 0, 0), plate(1, RAIL_COUNT, 0)], &[]);
+        assert!(!too_tall.fits_rack());
+        assert_eq!(too_tall.free_position(GridPos::new(0, 0), |_, _, _| true), None);
+        assert!(manager.insert_selection(&too_tall, GridPos::new(0, 10)).is_empty());
+        assert_eq!(manager.plugin_count(), 3, "nothing is placed off the rack");
+        assert!(Selection::from_json(&too_tall.to_json().unwrap()).is_err());
+
+        // Hand edited files can hold positions above the top left
+        let json = r#"{"plugins": [{"rail": -2, "column": 0, "selected": false, "id": 1, "slug": "Blank"}]}"#;
+        assert!(Selection::from_json(json).is_err());
+        let negative: Selection = serde_json::from_str(json).unwrap();
+        assert!(manager.insert_selection(&negative, GridPos::new(0, 10)).is_empty());
+        assert_eq!(manager.plugin_count(), 3);
+    }
+
+    #[test]
+    fn test_selection_stays_left_of_the_rail_end() {
+        let mut manager = manager_with_plates();
+        let pair = Selection::new(vec![plate(0, 0, 0), plate(1, 0, 2)], &[]);
+        assert_eq!(pair.width(), 4);
+        let ids = manager.insert_selection(&pair, GridPos::new(5, RAIL_HP + 100));
+        assert_eq!(grid_of(&manager, ids[0]), GridPos::new(5, RAIL_HP - 4));
+        assert_eq!(grid_of(&manager, ids[1]), GridPos::new(5, RAIL_HP - 2));
+
+        // Only the first 10HP of every rail are free
+        let is_free = |_rail: i32, column: i32, hp: u32| column + hp as i32 <= 10;
+        assert_eq!(pair.free_position(GridPos::new(0, RAIL_HP), is_free), Some(GridPos::new(0, 6)));
+        assert_eq!(pair.free_position(GridPos::new(0, 0), |_, _, _| false), None, "no room anywhere");
+
+        let too_wide = Selection::new(vec![plate(0, 0, 0), plate(1, 0, RAIL_HP - 1)], &[]);
+        assert!(!too_wide.fits_rack());
+        assert!(manager.insert_selection(&too_wide, GridPos::new(0, 0)).is_empty());
+    }
+
+    #[test]
+    fn test_insert_selection_is_one_undo_step() {
+        let mut manager = manager_with_plates();
+        let selection = Selection::new(vec![plate(0, 0, 0), plate(1, 0, 2)], &[]);
+        let ids = manager.insert_selection(&selection, GridPos::new(3, 0));
+        assert_eq!(manager.plugin_count(), 5);
+        assert_eq!(manager.history().undo_name(), Some("Add modules"));
+
+        assert!(manager.undo());
+        assert_eq!(manager.plugin_count(), 3);
+        assert!(manager.redo());
+        assert_eq!(manager.plugin_count(), 5);
+        assert_eq!(grid_of(&manager, ids[1]), GridPos::new(3, 2));
+    }
+
+    #[test]
+    fn test_save_and_import_selection_file() {
+        let ctx = egui::Context::default();
+        let mut app = VcvRackApp::new_test(&ctx);
+        app.plugin_manager = manager_with_plates();
+        let path = temp_dir("file").join("voice.vcvs");
+        assert!(app.save_selection(&path).is_err(), "nothing selected");
+
+        for id in [1, 2] {
+            let pos = RackGeometry::grid_to_rack(grid_of(&app.plugin_manager, id)) + egui::vec2(5.0, 5.0);
+            app.plugin_manager.select_plugin(pos, 1.0);
+        }
+        app.save_selection(&path).unwrap();
+
+        app.plugin_manager = manager_with_plates();
+        let ids = app.import_selection_file(&path, GridPos::new(10, 6)).unwrap();
+        assert_eq!(ids.len(), 2);
+        assert_eq!(grid_of(&app.plugin_manager, ids[0]), GridPos::new(10, 6));
+        assert_eq!(grid_of(&app.plugin_manager, ids[1]), GridPos::new(11, 6));
+        assert!(app.has_unsaved_changes);
+        assert!(Selection::from_json(r#"{"plugins": []}"#).is_err());
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_import_without_room_is_refused() {
+        let ctx = egui::Context::default();
+        let mut app = VcvRackApp::new_test(&ctx);
+        app.plugin_manager = manager_with_plates();
+        let path = temp_dir("full").join("rail.vcvs");
+        Selection::new(vec![plate(0, 0, 0), plate(1, 0, RAIL_HP - 2)], &[]).save(&path).unwrap();
+
+        assert!(app.import_selection_file(&path, GridPos::new(0, 0)).is_err(), "plates are in the way on rail 0");
+        assert_eq!(app.plugin_manager.plugin_count(), 3);
+        assert!(!app.has_unsaved_changes);
+        std::fs::remove_file(&path).ok();
+    }
+}
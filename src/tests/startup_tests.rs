@@ -16,7 +16,7 @@ mod startup_tests {
             
             // Create a default.json with some test data
             let default_file = save_dir.join("default.json");
-            let test_state = RackState { plugins: vec![] };
+            let test_state = RackState { plugins: vec![], cables: vec![] };
             let json = serde_json::to_string_pretty(&test_state).unwrap();
             fs::write(&default_file, json).unwrap();
             
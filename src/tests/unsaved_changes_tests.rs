@@ -0,0 +1,172 @@
+#[cfg(test)]
+mod unsaved_changes_tests {
+    use crate::app::unsaved_changes::{PendingAction, SaveChoice};
+    use crate::app::vcvrack_app::VcvRackApp;
+    use crate::models::geometry::{GridPos, RackGeometry};
+    use eframe::egui;
+    use std::path::{Path, PathBuf};
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("vcvrack_unsaved_{}_{}.json", name, std::process::id()))
+    }
+
+    /// App with one plugin, saved to `name` and then edited.
+    fn edited_app(ctx: &egui::Context, name: &str) -> (VcvRackApp, PathBuf) {
+        let mut app = VcvRackApp::new_test(ctx);
+        app.plugin_manager = Default::default();
+        app.plugin_manager.add_plugin(RackGeometry::grid_to_rack(GridPos::new(0, 0)), None);
+        let path = temp_path(name);
+        app.save_patch(&path).unwrap();
+
+        app.plugin_manager.add_plugin(RackGeometry::grid_to_rack(GridPos::new(1, 0)), None);
+        app.has_unsaved_changes = true;
+        (app, path)
+    }
+
+    /// Patch with a single plugin to open over the edited rack.
+    fn other_patch(ctx: &egui::Context, name: &str) -> PathBuf {
+        let mut app = VcvRackApp::new_test(ctx);
+        app.plugin_manager = Default::default();
+        app.plugin_manager.add_plugin(RackGeometry::grid_to_rack(GridPos::new(5, 5)), None);
+        let path = temp_path(name);
+        app.save_patch(&path).unwrap();
+        path
+    }
+
+    fn saved_plugin_count(ctx: &egui::Context, path: &Path) -> usize {
+        let mut app = VcvRackApp::new_test(ctx);
+        app.load_patch(path).unwrap();
+        app.plugin_manager.plugin_count()
+    }
+
+    #[test]
+    fn test_no_prompt_without_changes() {
+        let ctx = egui::Context::default();
+        let (mut app, path) = edited_app(&ctx, "clean");
+        app.save();
+        let other = other_patch(&ctx, "clean_other");
+
+        app.request_action(PendingAction::OpenFile(other.clone()));
+        assert!(app.pending_action().is_none());
+        assert_eq!(app.current_file.as_ref(), Some(&other));
+
+        app.request_action(PendingAction::Exit);
+        assert!(app.close_confirmed && app.send_close);
+        std::fs::remove_file(path).ok();
+        std::fs::remove_file(other).ok();
+    }
+
+    #[test]
+    fn test_open_asks_first() {
+        let ctx = egui::Context::default();
+        let (mut app, path) = edited_app(&ctx, "asks");
+        let other = other_patch(&ctx, "asks_other");
+
+        app.request_action(PendingAction::OpenFile(other.clone()));
+        assert_eq!(app.pending_action(), Some(&PendingAction::OpenFile(other.clone())));
+        assert_eq!(app.current_file.as_ref(), Some(&path), "Nothing happens before the user answers");
+        assert_eq!(app.plugin_manager.plugin_count(), 2);
+        std::fs::remove_file(path).ok();
+        std::fs::remove_file(other).ok();
+    }
+
+    #[test]
+    fn test_cancel_keeps_rack() {
+        let ctx = egui::Context::default();
+        let (mut app, path) = edited_app(&ctx, "cancel");
+        let other = other_patch(&ctx, "cancel_other");
+
+        app.request_action(PendingAction::OpenFile(other.clone()));
+        app.resolve_pending_action(SaveChoice::Cancel);
+        assert!(app.pending_action().is_none());
+        assert_eq!(app.current_file.as_ref(), Some(&path));
+        assert_eq!(app.plugin_manager.plugin_count(), 2);
+        assert!(app.has_unsaved_changes);
+        assert_eq!(saved_plugin_count(&ctx, &path), 1, "Cancel doesn't save");
+        std::fs::remove_file(path).ok();
+        std::fs::remove_file(other).ok();
+    }
+
+    #[test]
+    fn test_dont_save_discards_changes() {
+        let ctx = egui::Context::default();
+        let (mut app, path) = edited_app(&ctx, "discard");
+        let other = other_patch(&ctx, "discard_other");
+
+        app.request_action(PendingAction::OpenFile(other.clone()));
+        app.resolve_pending_action(SaveChoice::DontSave);
+        assert_eq!(app.current_file.as_ref(), Some(&other));
+        assert_eq!(app.plugin_manager.plugin_count(), 1);
+        assert_eq!(saved_plugin_count(&ctx, &path), 1, "The edit was thrown away");
+        std::fs::remove_file(path).ok();
+        std::fs::remove_file(other).ok();
+    }
+
+    #[test]
+    fn test_save_then_open() {
+        let ctx = egui::Context::default();
+        let (mut app, path) = edited_app(&ctx, "save");
+        let other = other_patch(&ctx, "save_other");
+
+        app.request_action(PendingAction::OpenFile(other.clone()));
+        app.resolve_pending_action(SaveChoice::Save);
+        assert_eq!(app.current_file.as_ref(), Some(&other));
+        assert_eq!(saved_plugin_count(&ctx, &path), 2, "The edit was saved before opening");
+        std::fs::remove_file(path).ok();
+        std::fs::remove_file(other).ok();
+    }
+
+    #[test]
+    fn test_window_close_is_intercepted() {
+        let ctx = egui::Context::default();
+        let (mut app, path) = edited_app(&ctx, "close");
+
+        assert!(app.intercept_close_request(), "Closing with unsaved changes is cancelled");
+        assert_eq!(app.pending_action(), Some(&PendingAction::Exit));
+        assert!(!app.send_close);
+
+        app.resolve_pending_action(SaveChoice::Cancel);
+        assert!(!app.close_confirmed);
+
+        assert!(app.intercept_close_request());
+        app.resolve_pending_action(SaveChoice::DontSave);
+        assert!(app.close_confirmed && app.send_close);
+        assert!(!app.intercept_close_request(), "The confirmed close goes through");
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_close_without_changes_goes_through() {
+        let ctx = egui::Context::default();
+        let (mut app, path) = edited_app(&ctx, "close_clean");
+        app.save();
+        assert!(!app.intercept_close_request());
+        assert!(app.pending_action().is_none());
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_dialog_frame_without_answer_keeps_waiting() {
+        let ctx = egui::Context::default();
+        let (mut app, path) = edited_app(&ctx, "frame");
+        app.request_action(PendingAction::Exit);
+        let _ = ctx.run(egui::RawInput::default(), |ctx| app.save_changes_dialog(ctx));
+        assert_eq!(app.pending_action(), Some(&PendingAction::Exit));
+
+        // Escape closes the dialog like Cancel
+        let input = egui::RawInput {
+            events: vec![egui::Event::Key {
+                key: egui::Key::Escape,
+                physical_key: None,
+                pressed: true,
+                repeat: false,
+                modifiers: egui::Modifiers::NONE,
+            }],
+            ..Default::default()
+        };
+        let _ = ctx.run(input, |ctx| app.save_changes_dialog(ctx));
+        assert!(app.pending_action().is_none());
+        assert!(!app.close_confirmed);
+        std::fs::remove_file(path).ok();
+    }
+}
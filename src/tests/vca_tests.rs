@@ -0,0 +1,82 @@
+#[cfg(test)]
+mod vca_tests {
+    use crate::modules::vca;
+    use crate::tests::module_support::{assert_mapped, create, process, set_input};
+
+    #[test]
+    fn test_vca_is_registered_and_mapped() {
+        let amplifier = create("VCA");
+        assert_eq!(amplifier.inputs().len(), 6);
+        assert_eq!(amplifier.outputs().len(), 2);
+        let model = assert_mapped("VCA", "Fundamental", "VCA");
+        assert_eq!(model.param_index(1), Some(vca::LEVEL2_PARAM));
+        assert_eq!(model.input_index(5), Some(vca::IN2_INPUT));
+        assert_eq!(model.vcv_output_id(vca::OUT2_OUTPUT), Some(1));
+    }
+
+    #[test]
+    fn test_level_knob_without_cv() {
+        let mut amplifier = create("VCA");
+        set_input(amplifier.as_mut(), vca::IN1_INPUT, 4.0);
+        process(amplifier.as_mut());
+        assert_eq!(amplifier.outputs()[vca::OUT1_OUTPUT].voltage(), 4.0, "Full level by default");
+        amplifier.set_param(vca::LEVEL1_PARAM, 0.25);
+        process(amplifier.as_mut());
+        assert_eq!(amplifier.outputs()[vca::OUT1_OUTPUT].voltage(), 1.0);
+    }
+
+    #[test]
+    fn test_linear_response() {
+        assert_eq!(vca::gain(1.0, Some(5.0), None), 0.5);
+        assert_eq!(vca::gain(0.5, Some(10.0), None), 0.5);
+        assert_eq!(vca::gain(1.0, Some(-3.0), None), 0.0, "Negative CV closes the VCA");
+        assert_eq!(vca::gain(1.0, Some(12.0), None), 1.0, "Clamped at 10V");
+    }
+
+    #[test]
+    fn test_exponential_response() {
+        assert_eq!(vca::gain(1.0, None, Some(0.0)), 0.0);
+        assert!((vca::gain(1.0, None, Some(10.0)) - 1.0).abs() < 1e-6);
+        let half = vca::gain(1.0, None, Some(5.0));
+        assert!(half > 0.1 && half < 0.2, "Exponential curve is well below linear halfway: {}", half);
+        // Both CVs multiply
+        assert!((vca::gain(1.0, Some(5.0), Some(5.0)) - half / 2.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_sections_are_independent() {
+        let mut amplifier = create("VCA");
+        set_input(amplifier.as_mut(), vca::IN1_INPUT, 5.0);
+        set_input(amplifier.as_mut(), vca::IN2_INPUT, 5.0);
+        set_input(amplifier.as_mut(), vca::LIN2_INPUT, 2.0);
+        process(amplifier.as_mut());
+        assert_eq!(amplifier.outputs()[vca::OUT1_OUTPUT].voltage(), 5.0);
+        assert_eq!(amplifier.outputs()[vca::OUT2_OUTPUT].voltage(), 1.0);
+    }
+
+    #[test]
+    fn test_polyphonic_input_and_cv() {
+        let mut amplifier = create("VCA");
+        let input = &mut amplifier.io_mut().inputs[vca::IN1_INPUT];
+        input.set_channels(3);
+        for channel in 0..3 {
+            input.set_voltage_at(channel, 10.0);
+        }
+        // A polyphonic CV sets the gain of every voice
+        let cv = &mut amplifier.io_mut().inputs[vca::LIN1_INPUT];
+        cv.set_channels(3);
+        cv.set_voltage_at(0, 0.0);
+        cv.set_voltage_at(1, 5.0);
+        cv.set_voltage_at(2, 10.0);
+        process(amplifier.as_mut());
+        let output = &amplifier.outputs()[vca::OUT1_OUTPUT];
+        assert_eq!(output.channels, 3);
+        assert_eq!([output.voltage_at(0), output.voltage_at(1), output.voltage_at(2)], [0.0, 5.0, 10.0]);
+
+        // A mono CV applies to every voice
+        set_input(amplifier.as_mut(), vca::LIN1_INPUT, 5.0);
+        process(amplifier.as_mut());
+        let output = &amplifier.outputs()[vca::OUT1_OUTPUT];
+        assert_eq!([output.voltage_at(0), output.voltage_at(2)], [5.0, 5.0]);
+    }
+}
@@ -0,0 +1,188 @@
+#[cfg(test)]
+mod vcf_tests {
+    use crate::engine::{AudioBlock, ProcessGraph};
+    use crate::models::cable::PortRef;
+    use crate::models::geometry::GRID_UNIT;
+    use crate::models::module::{Module, ProcessContext};
+    use crate::models::plugin::PluginManager;
+    use crate::modules::{vcf, vco};
+    use crate::tests::module_support::{assert_mapped, create, process, SAMPLE_RATE};
+    use eframe::egui;
+    use std::f32::consts::TAU;
+
+    /// Small enough to keep the saturation out of the measurements.
+    const TEST_LEVEL: f32 = 0.5;
+
+    /// Filter with its cutoff at `cutoff` Hz in the given mode (0 = ladder, 1 = SVF).
+    fn create_vcf(cutoff: f32, mode: f32, resonance: f32) -> Box<dyn Module> {
+        let mut filter = create("VCF");
+        filter.set_param(vcf::CUTOFF_PARAM, ((cutoff / vcf::cutoff_freq(0.0, 0.0)).log2()) / 10.0);
+        filter.set_param(vcf::MODE_PARAM, mode);
+        filter.set_param(vcf::RESONANCE_PARAM, resonance);
+        filter.io_mut().inputs[vcf::IN_INPUT].set_channels(1);
+        filter
+    }
+
+    fn rms(signal: &[f32]) -> f32 {
+        (signal.iter().map(|x| x * x).sum::<f32>() / signal.len() as f32).sqrt()
+    }
+
+    /// Runs `frames` samples with `input` at the In jack and records `output`.
+    fn run(filter: &mut dyn Module, output: usize, frames: usize, input: impl Fn(usize) -> f32) -> Vec<f32> {
+        (0..frames)
+            .map(|frame| {
+                filter.io_mut().inputs[vcf::IN_INPUT].set_voltage(input(frame));
+                filter.process(&ProcessContext::new(SAMPLE_RATE, frame as u64));
+                filter.outputs()[output].voltage()
+            })
+            .collect()
+    }
+
+    /// Gain in dB of `output` for a sine at `freq` Hz, once the filter settled.
+    fn gain_db(filter: &mut dyn Module, output: usize, freq: f32) -> f32 {
+        let sine = |frame: usize| TEST_LEVEL * (TAU * freq * frame as f32 / SAMPLE_RATE).sin();
+        let signal = run(filter, output, 24000, sine);
+        20.0 * (rms(&signal[12000..]) / (TEST_LEVEL / 2f32.sqrt())).log10()
+    }
+
+    fn response(mode: f32, resonance: f32, output: usize, freq: f32) -> f32 {
+        gain_db(create_vcf(1000.0, mode, resonance).as_mut(), output, freq)
+    }
+
+    #[test]
+    fn test_vcf_is_registered_and_mapped() {
+        let filter = create("VCF");
+        assert_eq!(filter.outputs().len(), 4);
+        let model = assert_mapped("VCF", "Fundamental", "VCF");
+        assert_eq!(model.param_index(3), Some(vcf::CUTOFF_CV_PARAM));
+        assert_eq!(model.input_index(3), Some(vcf::IN_INPUT));
+        assert_eq!(model.vcv_output_id(vcf::HIGHPASS_OUTPUT), Some(1));
+        assert_eq!(model.vcv_output_id(vcf::BANDPASS_OUTPUT), None, "VCV Rack's VCF has no bandpass");
+    }
+
+    #[test]
+    fn test_cutoff_follows_knob_and_cv() {
+        assert!((vcf::cutoff_freq(0.5, 0.0) - 261.6).abs() < 0.1, "Centre of the knob is C4");
+        assert!((vcf::cutoff_freq(0.6, 1.0) / vcf::cutoff_freq(0.5, 0.0) - 4.0).abs() < 1e-3, "1V/oct CV");
+    }
+
+    #[test]
+    fn test_ladder_lowpass_is_24db_per_octave() {
+        let (mode, res) = (0.0, 0.0);
+        assert!(response(mode, res, vcf::LOWPASS_OUTPUT, 50.0).abs() < 0.5, "Flat passband");
+        assert!((response(mode, res, vcf::LOWPASS_OUTPUT, 1000.0) + 12.0).abs() < 1.0, "Four poles each -3dB at the cutoff");
+        let slope = response(mode, res, vcf::LOWPASS_OUTPUT, 4000.0) - response(mode, res, vcf::LOWPASS_OUTPUT, 8000.0);
+        assert!(slope > 21.0 && slope < 30.0, "Slope {} dB/oct", slope);
+    }
+
+    #[test]
+    fn test_ladder_highpass_and_bandpass() {
+        let (mode, res) = (0.0, 0.0);
+        assert!(response(mode, res, vcf::HIGHPASS_OUTPUT, 12000.0).abs() < 1.0);
+        let slope = response(mode, res, vcf::HIGHPASS_OUTPUT, 250.0) - response(mode, res, vcf::HIGHPASS_OUTPUT, 125.0);
+        assert!(slope > 21.0 && slope < 27.0, "Slope {} dB/oct", slope);
+
+        assert!(response(mode, res, vcf::BANDPASS_OUTPUT, 1000.0).abs() < 1.0, "Bandpass peaks at the cutoff");
+        assert!(response(mode, res, vcf::BANDPASS_OUTPUT, 100.0) < -24.0);
+        assert!(response(mode, res, vcf::BANDPASS_OUTPUT, 10000.0) < -24.0);
+        assert!(response(mode, res, vcf::NOTCH_OUTPUT, 1000.0) < -30.0);
+    }
+
+    #[test]
+    fn test_svf_responses() {
+        let (mode, res) = (1.0, 0.15);
+        assert!(response(mode, res, vcf::LOWPASS_OUTPUT, 50.0).abs() < 0.5);
+        let slope = response(mode, res, vcf::LOWPASS_OUTPUT, 4000.0) - response(mode, res, vcf::LOWPASS_OUTPUT, 8000.0);
+        assert!(slope > 10.0 && slope < 16.0, "Lowpass slope {} dB/oct", slope);
+
+        assert!(response(mode, res, vcf::HIGHPASS_OUTPUT, 15000.0).abs() < 1.0);
+        let slope = response(mode, res, vcf::HIGHPASS_OUTPUT, 250.0) - response(mode, res, vcf::HIGHPASS_OUTPUT, 125.0);
+        assert!(slope > 10.0 && slope < 13.0, "Highpass slope {} dB/oct", slope);
+
+        assert!(response(mode, res, vcf::BANDPASS_OUTPUT, 1000.0).abs() < 0.5, "Bandpass has unity gain at the cutoff");
+        assert!(response(mode, res, vcf::BANDPASS_OUTPUT, 100.0) < -12.0);
+        assert!(response(mode, res, vcf::NOTCH_OUTPUT, 1000.0) < -40.0, "Notch at the cutoff");
+        assert!(response(mode, res, vcf::NOTCH_OUTPUT, 100.0).abs() < 0.5);
+        assert!(response(mode, res, vcf::NOTCH_OUTPUT, 10000.0).abs() < 0.5);
+    }
+
+    #[test]
+    fn test_ladder_keeps_its_passband_with_resonance() {
+        assert!(response(0.0, 0.7, vcf::LOWPASS_OUTPUT, 50.0).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_resonance_peaks_at_the_cutoff() {
+        for (mode, output) in [(0.0, vcf::LOWPASS_OUTPUT), (1.0, vcf::LOWPASS_OUTPUT)] {
+            let flat = response(mode, 0.0, output, 1000.0);
+            let resonant = response(mode, 0.8, output, 1000.0);
+            assert!(resonant > flat + 10.0, "Mode {}: {} dB with resonance, {} dB without", mode, resonant, flat);
+        }
+    }
+
+    #[test]
+    fn test_ladder_self_oscillates_at_the_cutoff() {
+        let mut filter = create_vcf(1000.0, 0.0, 1.0);
+        let signal = run(filter.as_mut(), vcf::LOWPASS_OUTPUT, 48000, |_| 0.0);
+        let tail = &signal[24000..];
+        let peak = tail.iter().fold(0.0f32, |max, x| max.max(x.abs()));
+        assert!(peak > 2.0 && peak < 10.0, "Oscillates at {} V", peak);
+
+        let crossings = tail.windows(2).filter(|w| w[0] <= 0.0 && w[1] > 0.0).count();
+        let freq = crossings as f32 * SAMPLE_RATE / tail.len() as f32;
+        assert!((freq / 1000.0).log2().abs() < 0.1, "Oscillates at {} Hz", freq);
+    }
+
+    #[test]
+    fn test_stable_under_audio_rate_modulation() {
+        for mode in [0.0, 1.0] {
+            let mut filter = create_vcf(1000.0, mode, 1.0);
+            filter.io_mut().inputs[vcf::CUTOFF_INPUT].set_channels(1);
+            for frame in 0..48000 {
+                // Sweeps the cutoff over 10 octaves at 3kHz
+                let phase = frame as f32 / SAMPLE_RATE;
+                filter.io_mut().inputs[vcf::CUTOFF_INPUT].set_voltage(5.0 * (TAU * 3000.0 * phase).sin());
+                filter.io_mut().inputs[vcf::IN_INPUT].set_voltage(if (frame / 37) % 2 == 0 { 5.0 } else { -5.0 });
+                filter.process(&ProcessContext::new(SAMPLE_RATE, frame as u64));
+                for output in filter.outputs() {
+                    assert!(output.voltage().is_finite() && output.voltage().abs() < 50.0, "Mode {} blew up: {}", mode, output.voltage());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_drive_saturates() {
+        let mut filter = create_vcf(20000.0, 1.0, 0.0);
+        filter.set_param(vcf::DRIVE_PARAM, 1.0);
+        let square = run(filter.as_mut(), vcf::LOWPASS_OUTPUT, 4800, |frame| if (frame / 240) % 2 == 0 { 5.0 } else { -5.0 });
+        let peak = square.iter().fold(0.0f32, |max, x| max.max(x.abs()));
+        assert!(peak > 4.5 && peak < 5.5, "A driven 5V square is clipped near {} V", peak);
+    }
+
+    #[test]
+    fn test_polyphony_follows_the_input() {
+        let mut filter = create_vcf(1000.0, 0.0, 0.0);
+        filter.io_mut().inputs[vcf::IN_INPUT].set_channels(3);
+        process(filter.as_mut());
+        assert!(filter.outputs().iter().all(|output| output.channels == 3));
+    }
+
+    #[test]
+    fn test_vcf_is_placed_like_any_module() {
+        let mut manager = PluginManager::new();
+        let filter = manager.add_module(&vcf::DESCRIPTOR, egui::pos2(100.0, 100.0), None).unwrap();
+        assert_eq!(manager.get_plugin(filter).unwrap().get_width(), 8.0 * GRID_UNIT);
+        assert!(manager.add_module(&vcf::DESCRIPTOR, egui::pos2(100.0 + 7.0 * GRID_UNIT, 100.0), None).is_none(), "Its 8HP are taken");
+        let oscillator = manager.add_module(&vco::DESCRIPTOR, egui::pos2(100.0 + 8.0 * GRID_UNIT, 100.0), None).unwrap();
+        manager.add_cable(PortRef::new(oscillator, vco::SAW_OUTPUT), PortRef::new(filter, vcf::IN_INPUT)).unwrap();
+
+        // The saw comes out of the filter when the rack runs
+        let mut graph = ProcessGraph::new();
+        graph.sync(&manager.graph_spec());
+        let mut block = AudioBlock::new(4800, 2);
+        graph.process_block(&mut block, SAMPLE_RATE as u32);
+        let lowpass = graph.module(filter).unwrap().outputs()[vcf::LOWPASS_OUTPUT].voltage();
+        assert!(lowpass != 0.0);
+    }
+}
@@ -0,0 +1,226 @@
+#[cfg(test)]
+mod vco_tests {
+    use crate::dsp::{self, PolyBlep};
+    use crate::models::module::{Module, ProcessContext};
+    use crate::modules::vco;
+    use crate::tests::module_support::{assert_mapped, create, render, set_input, SAMPLE_RATE};
+    use std::f64::consts::TAU;
+
+    /// Frequency from the first and last rising zero crossings, interpolated
+    /// between samples.
+    fn measure_freq(signal: &[f32]) -> f64 {
+        let crossings: Vec<f64> = signal.windows(2).enumerate()
+            .filter(|(_, w)| w[0] <= 0.0 && w[1] > 0.0)
+            .map(|(i, w)| i as f64 + (w[0] / (w[0] - w[1])) as f64)
+            .collect();
+        assert!(crossings.len() > 2, "Signal doesn't oscillate");
+        let periods = (crossings.len() - 1) as f64;
+        SAMPLE_RATE as f64 * periods / (crossings[crossings.len() - 1] - crossings[0])
+    }
+
+    fn cents(freq: f64, expected: f64) -> f64 {
+        1200.0 * (freq / expected).log2()
+    }
+
+    /// Magnitude of `freq` in a Hann windowed `signal`, relative to a full scale sine.
+    fn magnitude(signal: &[f32], freq: f64) -> f64 {
+        let n = signal.len() as f64;
+        let (mut re, mut im) = (0.0, 0.0);
+        for (i, &x) in signal.iter().enumerate() {
+            let window = 0.5 - 0.5 * (TAU * i as f64 / n).cos();
+            let angle = TAU * freq * i as f64 / SAMPLE_RATE as f64;
+            re += window * x as f64 * angle.cos();
+            im -= window * x as f64 * angle.sin();
+        }
+        (re * re + im * im).sqrt() * 4.0 / n
+    }
+
+    /// Loudest alias of the first 60 harmonics of `f0`, in dB relative to the fundamental.
+    fn worst_alias_db(signal: &[f32], f0: f64, max_freq: f64) -> f64 {
+        let nyquist = SAMPLE_RATE as f64 / 2.0;
+        let fundamental = magnitude(signal, f0);
+        (1..=60)
+            .map(|k| k as f64 * f0)
+            .filter(|&f| f > nyquist)
+            .map(|f| {
+                let folded = f % SAMPLE_RATE as f64;
+                if folded > nyquist { SAMPLE_RATE as f64 - folded } else { folded }
+            })
+            // Aliases on top of a harmonic can't be told apart from it
+            .filter(|&f| f > 50.0 && f < max_freq && (f / f0 - (f / f0).round()).abs() * f0 > 50.0)
+            .map(|f| 20.0 * (magnitude(signal, f) / fundamental).log10())
+            .fold(f64::NEG_INFINITY, f64::max)
+    }
+
+    /// A saw without band-limiting, for comparison.
+    fn naive_saw(freq: f32, frames: usize) -> Vec<f32> {
+        let mut phase = 0.0f32;
+        (0..frames)
+            .map(|_| {
+                phase += freq / SAMPLE_RATE;
+                phase -= phase.floor();
+                5.0 * (2.0 * phase - 1.0)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_vco_is_registered_and_mapped() {
+        let vco = create("VCO");
+        assert_eq!(vco.descriptor().name, "VCO");
+        assert_eq!(vco.outputs().len(), 4);
+        let model = assert_mapped("VCO", "Fundamental", "VCO");
+        assert_eq!(model.param_index(2), Some(vco::FREQ_PARAM));
+        assert_eq!(model.vcv_param_id(vco::FM_MODE_PARAM), 7);
+        assert_eq!(model.input_index(2), Some(vco::SYNC_INPUT));
+        assert_eq!(model.vcv_output_id(vco::SQUARE_OUTPUT), Some(3));
+    }
+
+    #[test]
+    fn test_default_frequency_is_c4() {
+        let mut vco = create("VCO");
+        let freq = measure_freq(&render(vco.as_mut(), vco::SINE_OUTPUT, 48000));
+        assert!(cents(freq, dsp::FREQ_C4 as f64).abs() < 0.5, "Got {} Hz", freq);
+    }
+
+    #[test]
+    fn test_pitch_tracks_one_volt_per_octave() {
+        for pitch in [-3.0, -1.0, 0.5, 1.0, 3.0, 5.0] {
+            let mut vco = create("VCO");
+            set_input(vco.as_mut(), vco::PITCH_INPUT, pitch);
+            let expected = dsp::FREQ_C4 as f64 * 2f64.powf(pitch as f64);
+            for output in [vco::SINE_OUTPUT, vco::TRIANGLE_OUTPUT] {
+                let freq = measure_freq(&render(vco.as_mut(), output, 48000));
+                assert!(cents(freq, expected).abs() < 0.5, "{}V: got {} Hz instead of {} Hz", pitch, freq, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_frequency_and_fine_knobs() {
+        let mut vco = create("VCO");
+        vco.set_param(vco::FREQ_PARAM, 12.0);
+        vco.set_param(vco::FINE_PARAM, -0.5);
+        let freq = measure_freq(&render(vco.as_mut(), vco::SINE_OUTPUT, 48000));
+        assert!((cents(freq, dsp::FREQ_C4 as f64 * 2.0) + 50.0).abs() < 0.5, "Got {} Hz", freq);
+    }
+
+    /// Renders `output` at `freq` Hz.
+    fn render_at(freq: f32, output: usize) -> Vec<f32> {
+        let mut vco = create("VCO");
+        set_input(vco.as_mut(), vco::PITCH_INPUT, (freq / dsp::FREQ_C4).log2());
+        render(vco.as_mut(), output, 48000)
+    }
+
+    #[test]
+    fn test_saw_is_band_limited() {
+        // Its harmonics above Nyquist fold back between the harmonics
+        let f0 = 2637.0;
+        let saw = render_at(f0, vco::SAW_OUTPUT);
+        let naive = naive_saw(f0, 48000);
+        assert!(worst_alias_db(&saw, f0 as f64, 5000.0) < -60.0);
+        assert!(worst_alias_db(&saw, f0 as f64, 12000.0) < -40.0);
+        assert!(worst_alias_db(&saw, f0 as f64, 12000.0) < worst_alias_db(&naive, f0 as f64, 12000.0) - 15.0);
+        assert!(worst_alias_db(&naive, f0 as f64, 12000.0) > -30.0, "The naive saw aliases");
+    }
+
+    #[test]
+    fn test_square_and_triangle_are_band_limited() {
+        let f0 = 2637.0;
+        assert!(worst_alias_db(&render_at(f0, vco::SQUARE_OUTPUT), f0 as f64, 12000.0) < -45.0);
+        assert!(worst_alias_db(&render_at(f0, vco::TRIANGLE_OUTPUT), f0 as f64, 12000.0) < -65.0);
+    }
+
+    #[test]
+    fn test_outputs_are_5v_peak() {
+        for output in [vco::SINE_OUTPUT, vco::TRIANGLE_OUTPUT, vco::SAW_OUTPUT, vco::SQUARE_OUTPUT] {
+            let signal = render_at(dsp::FREQ_C4, output);
+            let max = signal.iter().copied().fold(f32::MIN, f32::max);
+            let min = signal.iter().copied().fold(f32::MAX, f32::min);
+            assert!(max > 4.9 && max < 5.5, "Output {} peaks at {}", output, max);
+            assert!(min < -4.9 && min > -5.5, "Output {} dips to {}", output, min);
+        }
+    }
+
+    #[test]
+    fn test_hard_sync_restarts_the_cycle() {
+        let mut vco = create("VCO");
+        set_input(vco.as_mut(), vco::SYNC_INPUT, -5.0);
+        let mut saw = Vec::new();
+        let mut edges = Vec::new();
+        for frame in 0..4800 {
+            // 100 Hz square into the sync input
+            let sync = if (frame / 240) % 2 == 1 { 5.0 } else { -5.0 };
+            if frame % 480 == 240 {
+                edges.push(frame);
+            }
+            set_input(vco.as_mut(), vco::SYNC_INPUT, sync);
+            vco.process(&ProcessContext::new(SAMPLE_RATE, frame as u64));
+            saw.push(vco.outputs()[vco::SAW_OUTPUT].voltage());
+        }
+        // The restart reaches the output one sample late, and the sample
+        // after that is past the band-limited edge
+        for edge in edges {
+            assert!(saw[edge + 2] < -4.8, "Saw at {} V two samples after the sync edge at {}", saw[edge + 2], edge);
+            assert!(saw[edge - 1] > -4.0, "The cycle was running before the edge at {}", edge);
+        }
+    }
+
+    #[test]
+    fn test_pulse_width_and_pwm() {
+        let mean = |vco: &mut dyn Module| render(vco, vco::SQUARE_OUTPUT, 48000).iter().sum::<f32>() / 48000.0;
+
+        let mut vco = create("VCO");
+        vco.set_param(vco::PW_PARAM, 0.25);
+        assert!((mean(vco.as_mut()) + 2.5).abs() < 0.1, "High for a quarter of the cycle");
+
+        vco.set_param(vco::PWM_PARAM, 1.0);
+        set_input(vco.as_mut(), vco::PWM_INPUT, 5.0);
+        assert!((mean(vco.as_mut()) - 2.5).abs() < 0.1, "+5V widens the pulse by half a cycle");
+    }
+
+    #[test]
+    fn test_exponential_and_linear_fm() {
+        let mut vco = create("VCO");
+        vco.set_param(vco::FM_PARAM, 1.0);
+        set_input(vco.as_mut(), vco::FM_INPUT, 0.5);
+        let freq = measure_freq(&render(vco.as_mut(), vco::SINE_OUTPUT, 48000));
+        assert!(cents(freq, dsp::FREQ_C4 as f64 * 2f64.sqrt()).abs() < 0.5, "Exponential FM adds to the pitch, got {} Hz", freq);
+
+        let mut vco = create("VCO");
+        vco.set_param(vco::FM_PARAM, 1.0);
+        vco.set_param(vco::FM_MODE_PARAM, 1.0);
+        set_input(vco.as_mut(), vco::FM_INPUT, 0.5);
+        let freq = measure_freq(&render(vco.as_mut(), vco::SINE_OUTPUT, 48000));
+        assert!(cents(freq, dsp::FREQ_C4 as f64 * 1.5).abs() < 0.5, "Linear FM adds to the frequency, got {} Hz", freq);
+    }
+
+    #[test]
+    fn test_polyphonic_pitch() {
+        let mut vco = create("VCO");
+        let pitch = &mut vco.io_mut().inputs[vco::PITCH_INPUT];
+        pitch.set_channels(2);
+        pitch.set_voltage_at(1, 1.0);
+
+        let signal: Vec<f32> = (0..48000)
+            .map(|frame| {
+                vco.process(&ProcessContext::new(SAMPLE_RATE, frame as u64));
+                vco.outputs()[vco::SINE_OUTPUT].voltage_at(1)
+            })
+            .collect();
+        assert_eq!(vco.outputs()[vco::SAW_OUTPUT].channels, 2);
+        assert!(cents(measure_freq(&signal), dsp::FREQ_C4 as f64 * 2.0).abs() < 0.5);
+    }
+
+    #[test]
+    fn test_poly_blep_smooths_a_step() {
+        let mut blep = PolyBlep::new();
+        blep.process(0.0);
+        // A step from 0 to 1 halfway between the last two samples
+        blep.insert_step(0.5, 1.0);
+        let before = blep.process(1.0);
+        let after = blep.process(1.0);
+        assert_eq!((before, after), (0.125, 0.875));
+        assert_eq!(blep.process(1.0), 1.0);
+    }
+}
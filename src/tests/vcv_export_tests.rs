@@ -0,0 +1,143 @@
+#[cfg(test)]
+mod vcv_export_tests {
+    use crate::app::vcvrack_app::VcvRackApp;
+    use crate::models::cable::{Cable, CableColor, PortRef};
+    use crate::models::geometry::{GridPos, RackGeometry};
+    use crate::models::module_id::ModuleId;
+    use crate::models::plugin::{PluginState, RackState};
+    use crate::modules::{self, vca, vcf, vco};
+    use crate::vcv::export::{UNMAPPED_PLUGIN, VCV_VERSION};
+    use crate::vcv::mapping::{find_vcv_model, vcv_model};
+    use crate::vcv::{export_patch, import_patch, VcvPatch};
+    use eframe::egui;
+    use std::path::PathBuf;
+
+    fn plate(id: ModuleId, rail: i32, column: i32) -> PluginState {
+        PluginState { rail, column, selected: false, id, slug: "Blank".to_string(), params: Vec::new() }
+    }
+
+    fn module(id: ModuleId, slug: &str, column: i32) -> PluginState {
+        let params = modules::find(slug).unwrap().default_params();
+        PluginState { rail: 1, column, selected: false, id, slug: slug.to_string(), params }
+    }
+
+    /// A VCO, a VCF and a VCA on the second rail.
+    fn voice() -> RackState {
+        RackState { plugins: vec![module(10, "VCO", 0), module(11, "VCF", 12), module(12, "VCA", 24)], cables: Vec::new() }
+    }
+
+    fn rack() -> RackState {
+        RackState { plugins: vec![plate(0, 0, 0), plate(1, 0, 4), plate(2, 3, 12)], cables: Vec::new() }
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("vcvrack_export_{}_{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_every_module_type_has_a_vcv_model() {
+        for descriptor in modules::MODULES {
+            let model = vcv_model(descriptor.slug)
+                .unwrap_or_else(|| panic!("{} is missing from VCV_MODELS", descriptor.slug));
+            assert_eq!(find_vcv_model(model.plugin, model.model).map(|d| d.slug), Some(descriptor.slug));
+        }
+    }
+
+    #[test]
+    fn test_export_maps_modules_and_positions() {
+        let patch = export_patch(&rack());
+        assert_eq!(patch.version, VCV_VERSION);
+        assert_eq!(patch.modules.len(), 3);
+        let module = &patch.modules[2];
+        assert_eq!((module.plugin.as_str(), module.model.as_str()), ("Core", "Blank"));
+        assert_eq!(module.id, Some(2));
+        assert_eq!(module.pos, [12, 3], "pos is [hp, row]");
+    }
+
+    #[test]
+    fn test_unmapped_module_type_is_exported_as_missing() {
+        let mut rack = rack();
+        rack.plugins[0].slug = "Unreleased".to_string();
+        let patch = export_patch(&rack);
+        assert_eq!((patch.modules[0].plugin.as_str(), patch.modules[0].model.as_str()), (UNMAPPED_PLUGIN, "Unreleased"));
+    }
+
+    #[test]
+    fn test_export_writes_cables_like_vcv_rack() {
+        let plates = serde_json::to_value(export_patch(&rack())).unwrap();
+        assert_eq!(plates["modules"][0]["params"], serde_json::json!([]));
+
+        let mut rack = voice();
+        rack.cables.push(Cable { id: 7, output: PortRef::new(10, vco::SAW_OUTPUT), input: PortRef::new(11, vcf::IN_INPUT), color: CableColor([0x36, 0x95, 0xef]) });
+        let json = serde_json::to_value(export_patch(&rack)).unwrap();
+        let cable = &json["cables"][0];
+        assert_eq!(cable["id"], 7);
+        assert_eq!(cable["outputModuleId"], 10);
+        assert_eq!(cable["outputId"], 2);
+        assert_eq!(cable["inputModuleId"], 11);
+        assert_eq!(cable["inputId"], 3);
+        assert_eq!(cable["color"], "#3695ef");
+    }
+
+    #[test]
+    fn test_cables_on_ports_vcv_rack_lacks_are_left_out() {
+        let mut rack = voice();
+        let color = CableColor([1, 2, 3]);
+        rack.cables.push(Cable { id: 0, output: PortRef::new(11, vcf::BANDPASS_OUTPUT), input: PortRef::new(12, vca::IN1_INPUT), color });
+        rack.cables.push(Cable { id: 1, output: PortRef::new(11, vcf::HIGHPASS_OUTPUT), input: PortRef::new(12, vca::IN2_INPUT), color });
+        let patch = export_patch(&rack);
+        assert_eq!(patch.cables.len(), 1, "Fundamental VCF has no bandpass output");
+        assert_eq!((patch.cables[0].output_id, patch.cables[0].input_id), (1, 5));
+    }
+
+    #[test]
+    fn test_archive_round_trip() {
+        let mut rack = voice();
+        rack.cables.push(Cable { id: 0, output: PortRef::new(10, 0), input: PortRef::new(11, 0), color: CableColor([1, 2, 3]) });
+        let patch = export_patch(&rack);
+        let bytes = patch.to_bytes().unwrap();
+        assert_eq!(&bytes[..4], &[0x28, 0xb5, 0x2f, 0xfd], "zstd compressed");
+        assert_eq!(VcvPatch::from_bytes(&bytes).unwrap(), patch);
+    }
+
+    #[test]
+    fn test_rack_round_trip() {
+        let mut original = voice();
+        original.plugins.extend(rack().plugins);
+        original.cables.push(Cable { id: 0, output: PortRef::new(10, vco::SQUARE_OUTPUT), input: PortRef::new(11, vcf::IN_INPUT), color: CableColor([1, 2, 3]) });
+        original.cables.push(Cable { id: 1, output: PortRef::new(11, vcf::LOWPASS_OUTPUT), input: PortRef::new(12, vca::IN2_INPUT), color: CableColor([4, 5, 6]) });
+        let patch = export_patch(&original);
+        assert_eq!((patch.cables[1].output_id, patch.cables[1].input_id), (0, 5), "VCV Rack port ids");
+        let bytes = patch.to_bytes().unwrap();
+        let (imported, report) = import_patch(&VcvPatch::from_bytes(&bytes).unwrap());
+        assert!(report.is_empty(), "{:?}", report.lines());
+        assert_eq!(imported.plugins.len(), original.plugins.len());
+        for (imported, original) in imported.plugins.iter().zip(&original.plugins) {
+            assert_eq!(imported.id, original.id);
+            assert_eq!(imported.slug, original.slug);
+            assert_eq!((imported.rail, imported.column), (original.rail, original.column));
+            assert_eq!(imported.params, original.params);
+        }
+        assert_eq!(imported.cables, original.cables);
+    }
+
+    #[test]
+    fn test_app_export_keeps_patch_file() {
+        let ctx = egui::Context::default();
+        let mut app = VcvRackApp::new_test(&ctx);
+        app.plugin_manager = Default::default();
+        app.plugin_manager.add_plugin(RackGeometry::grid_to_rack(GridPos::new(1, 6)), None);
+        app.current_file = Some(PathBuf::from("rack.json"));
+        app.has_unsaved_changes = true;
+        let path = temp_dir("app").join("rack.vcv");
+
+        app.export_vcv_file(&path).unwrap();
+        let patch = VcvPatch::load(&path).unwrap();
+        assert_eq!(patch.modules[0].pos, [6, 1]);
+        assert_eq!(app.current_file, Some(PathBuf::from("rack.json")));
+        assert!(app.has_unsaved_changes, "exporting doesn't save the patch");
+        std::fs::remove_file(&path).ok();
+    }
+}
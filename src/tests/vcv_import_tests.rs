@@ -0,0 +1,178 @@
+#[cfg(test)]
+mod vcv_import_tests {
+    use crate::app::vcvrack_app::VcvRackApp;
+    use crate::models::cable::PortRef;
+    use crate::models::geometry::RAIL_COUNT;
+    use crate::modules::{lfo, mixer, vcf, vco};
+    use crate::vcv::{import_patch, VcvPatch};
+    use eframe::egui;
+    use std::path::PathBuf;
+
+    const V2_PATCH: &str = r##"{
+        "version": "2.4.1",
+        "modules": [
+            {"id": 8817, "plugin": "Core", "model": "Blank", "version": "2.4.1", "params": [], "pos": [4, 1]},
+            {"id": 27, "plugin": "Bogaudio", "model": "Bogaudio-VCO", "params": [{"id": 0, "value": 0.5}], "pos": [10, 1]}
+        ],
+        "cables": [
+            {"id": 3, "outputModuleId": 27, "outputId": 0, "inputModuleId": 8817, "inputId": 0, "color": "#f3374b"}
+        ]
+    }"##;
+
+    /// VCV Rack 0.6: no module ids, "wires" and "paramId".
+    const LEGACY_PATCH: &str = r##"{
+        "version": "0.6.2",
+        "modules": [
+            {"plugin": "Core", "model": "Blank", "params": [{"paramId": 0, "value": 1.0}], "pos": [0, 0]},
+            {"plugin": "Befaco", "model": "EvenVCO", "params": [], "pos": [6, 2]}
+        ],
+        "wires": [
+            {"outputModuleId": 1, "outputId": 2, "inputModuleId": 0, "inputId": 0}
+        ]
+    }"##;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("vcvrack_import_{}_{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// A `.vcv` file like VCV Rack 2 writes: a zstd compressed tar with patch.json.
+    fn v2_archive(json: &str) -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        let mut header = tar::Header::new_ustar();
+        header.set_size(json.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append_data(&mut header, "./patch.json", json.as_bytes()).unwrap();
+        zstd::encode_all(builder.into_inner().unwrap().as_slice(), 0).unwrap()
+    }
+
+    #[test]
+    fn test_reads_v2_archive() {
+        let patch = VcvPatch::from_bytes(&v2_archive(V2_PATCH)).unwrap();
+        assert_eq!(patch.version, "2.4.1");
+        assert_eq!(patch.modules.len(), 2);
+        assert_eq!(patch.modules[0].id, Some(8817));
+        assert_eq!(patch.modules[1].pos, [10, 1]);
+        assert_eq!(patch.cables[0].output_module_id, 27);
+    }
+
+    #[test]
+    fn test_archive_without_patch_json_is_an_error() {
+        let mut builder = tar::Builder::new(Vec::new());
+        let mut header = tar::Header::new_ustar();
+        header.set_size(2);
+        header.set_cksum();
+        builder.append_data(&mut header, "modules/1.json", "{}".as_bytes()).unwrap();
+        let bytes = zstd::encode_all(builder.into_inner().unwrap().as_slice(), 0).unwrap();
+        assert!(VcvPatch::from_bytes(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_reads_legacy_json() {
+        let patch = VcvPatch::from_bytes(LEGACY_PATCH.as_bytes()).unwrap();
+        assert_eq!(patch.modules[0].id, None);
+        assert_eq!(patch.modules[0].params[0].id, Some(0));
+        assert_eq!(patch.cables.len(), 1, "wires are read as cables");
+
+        let (rack, report) = import_patch(&patch);
+        assert_eq!(rack.plugins.len(), 2);
+        assert_eq!((rack.plugins[1].rail, rack.plugins[1].column), (2, 6));
+        assert_eq!(report.unknown_modules, vec!["Befaco/EvenVCO".to_string()]);
+        assert_eq!(report.dropped_cables.len(), 1);
+        assert!(report.dropped_cables[0].contains("Befaco/EvenVCO"), "{:?}", report.dropped_cables);
+    }
+
+    #[test]
+    fn test_unknown_modules_become_blank_plates() {
+        let (rack, report) = import_patch(&VcvPatch::from_bytes(V2_PATCH.as_bytes()).unwrap());
+        assert_eq!(rack.plugins.len(), 2);
+        assert!(rack.plugins.iter().all(|p| p.slug == "Blank"));
+        assert_eq!(rack.plugins.iter().map(|p| p.id).collect::<Vec<_>>(), vec![8817, 27], "modules keep their VCV ids");
+        assert_eq!((rack.plugins[0].rail, rack.plugins[0].column), (1, 4));
+        assert_eq!(report.unknown_modules, vec!["Bogaudio/Bogaudio-VCO".to_string()]);
+        assert!(report.lines().iter().any(|line| line.contains("Bogaudio/Bogaudio-VCO")));
+    }
+
+    #[test]
+    fn test_cables_without_ports_are_dropped() {
+        let (rack, report) = import_patch(&VcvPatch::from_bytes(V2_PATCH.as_bytes()).unwrap());
+        assert!(rack.cables.is_empty(), "blank plates have no ports");
+        assert_eq!(report.dropped_cables, vec!["from output 0 of Bogaudio/Bogaudio-VCO to input 0 of Core/Blank".to_string()]);
+    }
+
+    #[test]
+    fn test_cable_ports_are_translated_from_vcv_ids() {
+        // VCV VCO saw (output 2) into the VCV VCF audio input (input 3), and
+        // a cable from output 2 of the VCV VCF, which it doesn't have
+        let json = r#"{"modules": [
+            {"id": 1, "plugin": "Fundamental", "model": "VCO", "pos": [0, 0]},
+            {"id": 2, "plugin": "Fundamental", "model": "VCF", "pos": [10, 0]},
+            {"id": 3, "plugin": "Fundamental", "model": "VCA", "pos": [20, 0]}
+        ], "cables": [
+            {"id": 0, "outputModuleId": 1, "outputId": 2, "inputModuleId": 2, "inputId": 3},
+            {"id": 1, "outputModuleId": 2, "outputId": 2, "inputModuleId": 3, "inputId": 2}
+        ]}"#;
+        let (rack, report) = import_patch(&VcvPatch::from_bytes(json.as_bytes()).unwrap());
+        assert_eq!(rack.cables.len(), 1);
+        assert_eq!(rack.cables[0].output, PortRef::new(1, vco::SAW_OUTPUT));
+        assert_eq!(rack.cables[0].input, PortRef::new(2, vcf::IN_INPUT));
+        assert_eq!(report.dropped_cables, vec!["from output 2 of Fundamental/VCF to input 2 of Fundamental/VCA".to_string()]);
+    }
+
+    #[test]
+    fn test_params_are_converted_and_clamped_values_reported() {
+        let json = r#"{"modules": [
+            {"id": 1, "plugin": "Fundamental", "model": "VCMixer", "params": [{"id": 1, "value": 0.5}], "pos": [0, 0]},
+            {"id": 2, "plugin": "Fundamental", "model": "LFO", "params": [{"id": 2, "value": 8.0}], "pos": [20, 0]}
+        ]}"#;
+        let (rack, report) = import_patch(&VcvPatch::from_bytes(json.as_bytes()).unwrap());
+        assert_eq!(rack.plugins[0].params[mixer::LEVEL_PARAM], 0.25, "VCV Rack squares its channel levels");
+        assert_eq!(rack.plugins[1].params[lfo::RATE_PARAM], 6.0);
+        assert_eq!(report.changed_params, vec!["Rate of Fundamental/LFO from 8 to 6, the end of its range".to_string()]);
+        assert!(report.lines().iter().any(|line| line.starts_with("Changed Rate")));
+    }
+
+    #[test]
+    fn test_overlapping_modules_move_right() {
+        let json = r#"{"modules": [
+            {"id": 1, "plugin": "Core", "model": "Blank", "pos": [5, 0]},
+            {"id": 2, "plugin": "Core", "model": "Blank", "pos": [6, 0]},
+            {"id": 3, "plugin": "Core", "model": "Blank", "pos": [6, 1]}
+        ]}"#;
+        let (rack, report) = import_patch(&VcvPatch::from_bytes(json.as_bytes()).unwrap());
+        assert_eq!(rack.plugins[1].column, 7, "pushed past the 2HP plate on its left");
+        assert_eq!(rack.plugins[2].column, 6, "other rows are untouched");
+        assert_eq!(report.moved_modules.len(), 1);
+    }
+
+    #[test]
+    fn test_positions_are_fitted_onto_the_rails() {
+        let json = r#"{"modules": [
+            {"id": 1, "plugin": "Core", "model": "Blank", "pos": [-4, -1]},
+            {"id": 2, "plugin": "Core", "model": "Blank", "pos": [0, 100]}
+        ]}"#;
+        let (rack, report) = import_patch(&VcvPatch::from_bytes(json.as_bytes()).unwrap());
+        assert_eq!((rack.plugins[0].rail, rack.plugins[0].column), (0, 0));
+        assert_eq!((rack.plugins[1].rail, rack.plugins[1].column), (RAIL_COUNT - 1, 4));
+        assert_eq!(report.moved_modules, vec![format!("Core/Blank from row 100 to row {}, the last rail", RAIL_COUNT - 1)],
+                   "shifting the whole patch isn't reported, putting a module on another row is");
+    }
+
+    #[test]
+    fn test_app_imports_as_unsaved_rack() {
+        let ctx = egui::Context::default();
+        let mut app = VcvRackApp::new_test(&ctx);
+        app.current_file = Some(PathBuf::from("previous.json"));
+        let path = temp_dir("app").join("patch.vcv");
+        std::fs::write(&path, v2_archive(V2_PATCH)).unwrap();
+
+        let report = app.import_vcv_file(&path).unwrap();
+        assert_eq!(app.plugin_manager.plugin_count(), 2);
+        assert_eq!(app.current_file, None, "saving must not overwrite the .vcv file");
+        assert!(app.has_unsaved_changes);
+        assert_eq!(app.import_report(), Some(&report));
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -22,7 +22,7 @@ mod window_title_tests {
         assert_eq!(file_name, "test_rack.json");
         
         // Create another test state
-        let empty_state = RackState { plugins: vec![] };
+        let empty_state = RackState { plugins: vec![], cables: vec![] };
         
         // Mock loading the other state
         app.plugin_manager.load_state(empty_state, None);
@@ -0,0 +1,137 @@
+#[cfg(test)]
+mod zoom_tests {
+    use crate::app::vcvrack_app::VcvRackApp;
+    use crate::models::cable::PortKind;
+    use crate::models::geometry::{anchored_scroll_offset, RackGeometry};
+    use crate::models::module::{Module, ModuleDescriptor, ModuleIo, PortInfo, ProcessContext};
+    use crate::models::plugin::PluginManager;
+    use crate::modules;
+    use eframe::egui;
+
+    static JACK_DESCRIPTOR: ModuleDescriptor = ModuleDescriptor {
+        slug: "ZoomTest",
+        name: "Zoom Test",
+        brand: "Test",
+        tags: &[],
+        hp: 4,
+        panel_svg: "res/BlankPlatePlugin.svg",
+        params: &[],
+        inputs: &[PortInfo::new("In", (20.0, 300.0))],
+        outputs: &[],
+        lights: &[],
+        create: create_jack_module,
+    };
+
+    struct JackModule {
+        io: ModuleIo,
+    }
+
+    fn create_jack_module() -> Box<dyn Module> {
+        Box::new(JackModule { io: ModuleIo::new(&JACK_DESCRIPTOR) })
+    }
+
+    impl Module for JackModule {
+        fn descriptor(&self) -> &'static ModuleDescriptor {
+            &JACK_DESCRIPTOR
+        }
+
+        fn io(&self) -> &ModuleIo {
+            &self.io
+        }
+
+        fn io_mut(&mut self) -> &mut ModuleIo {
+            &mut self.io
+        }
+
+        fn process(&mut self, _ctx: &ProcessContext) {}
+    }
+
+    fn zoomed() -> RackGeometry {
+        RackGeometry::new(egui::pos2(40.0, 25.0), 2.0)
+    }
+
+    #[test]
+    fn test_geometry_round_trip() {
+        let geometry = zoomed();
+        let rack_pos = egui::pos2(130.4, 480.0);
+        let screen_pos = geometry.to_screen(rack_pos);
+        assert_eq!(screen_pos, egui::pos2(40.0 + 260.8, 25.0 + 960.0));
+        assert_eq!(geometry.to_rack(screen_pos), rack_pos);
+    }
+
+    #[test]
+    fn test_rects_scale_with_zoom() {
+        let geometry = zoomed();
+        let panel = egui::Rect::from_min_size(egui::pos2(100.0, 100.0), egui::vec2(30.4, 380.0));
+        let screen = geometry.rect_to_screen(panel);
+        assert!((screen.size() - egui::vec2(60.8, 760.0)).length() < 1e-3);
+        assert_eq!(screen.min, geometry.to_screen(panel.min));
+    }
+
+    #[test]
+    fn test_anchored_zoom_keeps_point_under_cursor() {
+        let offset = egui::vec2(300.0, 200.0);
+        let anchor = egui::vec2(150.0, 90.0);
+        let before = RackGeometry::new(egui::Pos2::ZERO - offset, 1.0);
+        let rack_point = before.to_rack(anchor.to_pos2());
+
+        let new_offset = anchored_scroll_offset(offset, anchor, 1.0, 1.5);
+        let after = RackGeometry::new(egui::Pos2::ZERO - new_offset, 1.5);
+        let moved = after.to_screen(rack_point) - anchor.to_pos2();
+        assert!(moved.length() < 1e-3, "Point under the cursor moved by {:?}", moved);
+    }
+
+    #[test]
+    fn test_anchored_zoom_never_scrolls_before_start() {
+        let offset = anchored_scroll_offset(egui::Vec2::ZERO, egui::vec2(100.0, 100.0), 1.0, 0.5);
+        assert_eq!(offset, egui::Vec2::ZERO);
+    }
+
+    #[test]
+    fn test_set_zoom_clamps_and_anchors() {
+        let ctx = egui::Context::default();
+        let mut app = VcvRackApp::new_test(&ctx);
+        app.set_zoom(10.0, None);
+        assert_eq!(app.get_zoom_level(), VcvRackApp::MAX_ZOOM);
+        app.set_zoom(0.0, None);
+        assert_eq!(app.get_zoom_level(), VcvRackApp::MIN_ZOOM);
+
+        app.set_zoom(1.0, None);
+        let anchor = egui::vec2(200.0, 100.0);
+        app.set_zoom(2.0, Some(anchor));
+        assert_eq!(app.scroll_offset(), anchored_scroll_offset(egui::Vec2::ZERO, anchor, 1.0, 2.0));
+    }
+
+    #[test]
+    fn test_hit_testing_follows_zoom() {
+        let mut manager = PluginManager::new();
+        let id = manager.add_module(modules::blank_plate(), egui::pos2(100.0 + 15.2 * 10.0, 100.0), None).unwrap();
+        let plugin = manager.get_plugin(id).unwrap();
+        let inside = plugin.position + egui::vec2(plugin.get_width() - 1.0, 300.0);
+
+        for zoom in [VcvRackApp::MIN_ZOOM, 1.0, 1.7, VcvRackApp::MAX_ZOOM] {
+            let geometry = RackGeometry::new(egui::pos2(12.0, 34.0), zoom);
+            let click = geometry.to_screen(inside);
+            let rack_pos = geometry.to_rack(click);
+            assert!(manager.get_plugin_at_position(rack_pos, zoom).is_some(), "Missed plugin at zoom {}", zoom);
+
+            let outside = geometry.to_rack(geometry.to_screen(plugin.position) - egui::vec2(1.0, 0.0));
+            assert!(manager.get_plugin_at_position(outside, zoom).is_none(), "Hit empty space at zoom {}", zoom);
+        }
+    }
+
+    #[test]
+    fn test_ports_scale_with_zoom() {
+        let mut manager = PluginManager::new();
+        let id = manager.add_module(&JACK_DESCRIPTOR, egui::pos2(100.0, 100.0), None).unwrap();
+        let center = manager.get_plugin(id).unwrap().port_center(PortKind::Input, 0).unwrap();
+
+        let geometry = zoomed();
+        let on_screen = geometry.to_screen(center);
+        assert!(manager.port_at(geometry.to_rack(on_screen), geometry.zoom).is_some());
+        // The jack radius is 8 rack pixels, 16 screen pixels at 200%
+        let beside = geometry.to_rack(on_screen + egui::vec2(20.0, 0.0));
+        assert!(manager.port_at(beside, geometry.zoom).is_none());
+        assert_eq!(on_screen - geometry.to_screen(egui::pos2(100.0, 100.0)), (center - egui::pos2(100.0, 100.0)) * 2.0);
+    }
+}
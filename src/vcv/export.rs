@@ -0,0 +1,64 @@
+use crate::models::module_id::ModuleId;
+use crate::models::plugin::RackState;
+use crate::vcv::format::{VcvCable, VcvModule, VcvParam, VcvPatch};
+use crate::vcv::mapping::vcv_model;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::Path;
+
+/// VCV Rack version written into exported patches.
+pub const VCV_VERSION: &str = "2.0.0";
+
+/// Plugin name used for module types that VCV Rack has no counterpart for,
+/// so VCV Rack lists them as missing instead of loading the wrong module.
+pub const UNMAPPED_PLUGIN: &str = "VcvRackRs";
+
+/// Converts a rack into a VCV Rack 2 patch. Module and cable ids are kept.
+/// Cables on ports that VCV Rack's module doesn't have are left out.
+pub fn export_patch(rack: &RackState) -> VcvPatch {
+    let modules = rack.plugins.iter()
+        .map(|plugin| {
+            let mapping = vcv_model(&plugin.slug);
+            let (plugin_name, model) = mapping.map_or((UNMAPPED_PLUGIN, plugin.slug.as_str()), |m| (m.plugin, m.model));
+            VcvModule {
+                id: Some(plugin.id as i64),
+                plugin: plugin_name.to_string(),
+                model: model.to_string(),
+                params: plugin.params.iter().enumerate()
+                    .map(|(index, &value)| match mapping {
+                        Some(m) => VcvParam { id: Some(m.vcv_param_id(index)), value: m.param_scale(index).to_vcv(value) },
+                        None => VcvParam { id: Some(index), value },
+                    })
+                    .collect(),
+                pos: [plugin.column, plugin.rail],
+            }
+        })
+        .collect();
+    // Ports of unmapped module types keep our ids
+    let mappings: HashMap<ModuleId, _> = rack.plugins.iter().map(|plugin| (plugin.id, vcv_model(&plugin.slug))).collect();
+    let output_id = |module_id: ModuleId, port: usize| match mappings.get(&module_id) {
+        Some(Some(m)) => m.vcv_output_id(port),
+        _ => Some(port),
+    };
+    let input_id = |module_id: ModuleId, port: usize| match mappings.get(&module_id) {
+        Some(Some(m)) => m.vcv_input_id(port),
+        _ => Some(port),
+    };
+    let cables = rack.cables.iter()
+        .filter_map(|cable| Some(VcvCable {
+            id: Some(cable.id as i64),
+            output_module_id: cable.output.module_id as i64,
+            output_id: output_id(cable.output.module_id, cable.output.port)?,
+            input_module_id: cable.input.module_id as i64,
+            input_id: input_id(cable.input.module_id, cable.input.port)?,
+            color: Some(Value::String(cable.color.to_hex())),
+        }))
+        .collect();
+    VcvPatch { version: VCV_VERSION.to_string(), modules, cables }
+}
+
+/// Writes a rack as a `.vcv` file for VCV Rack 2.
+pub fn export_file(rack: &RackState, path: &Path) -> Result<(), Box<dyn Error>> {
+    export_patch(rack).save(path)
+}
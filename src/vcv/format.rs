@@ -0,0 +1,101 @@
+use crate::models::patch::unix_time;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::error::Error;
+use std::io::Read;
+use std::path::Path;
+
+/// First bytes of a zstd frame; VCV Rack 2 patches are zstd compressed tar archives.
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// `patch.json` of a VCV Rack patch. Only the parts we can map are kept.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct VcvPatch {
+    #[serde(default)]
+    pub version: String,
+    #[serde(default)]
+    pub modules: Vec<VcvModule>,
+    /// Called "wires" in VCV Rack 0.6 patches.
+    #[serde(default, alias = "wires")]
+    pub cables: Vec<VcvCable>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct VcvModule {
+    /// Missing in VCV Rack 0.6 patches, where cables refer to the module's index.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<i64>,
+    pub plugin: String,
+    pub model: String,
+    #[serde(default)]
+    pub params: Vec<VcvParam>,
+    /// Position as [HP, row].
+    #[serde(default)]
+    pub pos: [i32; 2],
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct VcvParam {
+    /// Called "paramId" in VCV Rack 0.6 patches; missing means the index in the list.
+    #[serde(default, alias = "paramId", skip_serializing_if = "Option::is_none")]
+    pub id: Option<usize>,
+    pub value: f32,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VcvCable {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<i64>,
+    pub output_module_id: i64,
+    pub output_id: usize,
+    pub input_module_id: i64,
+    pub input_id: usize,
+    /// "#rrggbb"; older patches may use other notations, which are ignored.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<Value>,
+}
+
+impl VcvPatch {
+    /// Reads a `.vcv` file: plain JSON from VCV Rack 0.6 and 1, or the zstd
+    /// compressed tar archive with `patch.json` from VCV Rack 2.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Box<dyn Error>> {
+        if bytes.starts_with(&ZSTD_MAGIC) {
+            let decoder = zstd::stream::read::Decoder::new(bytes)?;
+            let mut archive = tar::Archive::new(decoder);
+            for entry in archive.entries()? {
+                let mut entry = entry?;
+                if entry.path()?.file_name().is_some_and(|name| name == "patch.json") {
+                    let mut json = String::new();
+                    entry.read_to_string(&mut json)?;
+                    return Ok(serde_json::from_str(&json)?);
+                }
+            }
+            return Err("The patch archive contains no patch.json".into());
+        }
+        Ok(serde_json::from_slice(bytes)?)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        Self::from_bytes(&std::fs::read(path)?)
+    }
+
+    /// Packs the patch the way VCV Rack 2 saves it: `patch.json` in a zstd
+    /// compressed tar archive.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Box<dyn Error>> {
+        let json = serde_json::to_vec_pretty(self)?;
+        let mut header = tar::Header::new_ustar();
+        header.set_size(json.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(unix_time());
+        header.set_cksum();
+        let mut archive = tar::Builder::new(Vec::new());
+        archive.append_data(&mut header, "patch.json", json.as_slice())?;
+        Ok(zstd::encode_all(archive.into_inner()?.as_slice(), 0)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        std::fs::write(path, self.to_bytes()?)?;
+        Ok(())
+    }
+}
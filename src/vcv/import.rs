@@ -0,0 +1,187 @@
+use crate::models::cable::{Cable, CableColor, PortRef, CABLE_COLORS};
+use crate::models::geometry::RAIL_COUNT;
+use crate::models::module::ModuleDescriptor;
+use crate::models::module_id::{repair_ids, IdAllocator, ModuleId};
+use crate::models::plugin::{PluginState, RackState};
+use crate::modules;
+use crate::vcv::format::{VcvCable, VcvModule, VcvPatch};
+use crate::vcv::mapping::{find_vcv_model, vcv_model};
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::Path;
+
+/// What could not be carried over from a VCV Rack patch.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportReport {
+    /// "Plugin/Model" of every module that was replaced by a blank plate.
+    pub unknown_modules: Vec<String>,
+    /// Cables that had nowhere to plug in.
+    pub dropped_cables: Vec<String>,
+    /// Modules that had to move because they would overlap another module.
+    pub moved_modules: Vec<String>,
+    /// Param values outside the range of our param, which were clamped.
+    pub changed_params: Vec<String>,
+}
+
+impl ImportReport {
+    pub fn is_empty(&self) -> bool {
+        self.unknown_modules.is_empty() && self.dropped_cables.is_empty() && self.moved_modules.is_empty()
+            && self.changed_params.is_empty()
+    }
+
+    /// One line per problem, for showing to the user.
+    pub fn lines(&self) -> Vec<String> {
+        let unknown = self.unknown_modules.iter().map(|m| format!("Unknown module {} was replaced by a blank plate", m));
+        let dropped = self.dropped_cables.iter().map(|c| format!("Dropped cable {}", c));
+        let moved = self.moved_modules.iter().map(|m| format!("Moved {}", m));
+        let changed = self.changed_params.iter().map(|p| format!("Changed {}", p));
+        unknown.chain(dropped).chain(moved).chain(changed).collect()
+    }
+}
+
+/// Reads a `.vcv` file into a rack.
+pub fn import_file(path: &Path) -> Result<(RackState, ImportReport), Box<dyn Error>> {
+    Ok(import_patch(&VcvPatch::load(path)?))
+}
+
+/// Converts a VCV Rack patch into a rack. Modules keep their VCV Rack ids
+/// where those are valid and unique, unknown modules become blank plates and
+/// cables that don't fit the mapped modules are left out.
+pub fn import_patch(patch: &VcvPatch) -> (RackState, ImportReport) {
+    let mut report = ImportReport::default();
+    let mut plugins = Vec::with_capacity(patch.modules.len());
+    let mut descriptors = Vec::with_capacity(patch.modules.len());
+    let mut indices = HashMap::new();
+    // VCV Rack 0.6 cables refer to modules by their index
+    let vcv_ids: Vec<i64> = patch.modules.iter().enumerate()
+        .map(|(index, module)| module.id.unwrap_or(index as i64))
+        .collect();
+    // Negative ids are out of range too and get replaced
+    let candidates: Vec<ModuleId> = vcv_ids.iter().map(|&id| u64::try_from(id).unwrap_or(u64::MAX)).collect();
+    let (ids, _) = repair_ids(&candidates, &mut IdAllocator::new());
+
+    for (index, module) in patch.modules.iter().enumerate() {
+        let descriptor = find_vcv_model(&module.plugin, &module.model).unwrap_or_else(|| {
+            report.unknown_modules.push(module_name(module));
+            modules::blank_plate()
+        });
+        indices.entry(vcv_ids[index]).or_insert(index);
+        plugins.push(PluginState {
+            rail: module.pos[1],
+            column: module.pos[0],
+            selected: false,
+            id: ids[index],
+            slug: descriptor.slug.to_string(),
+            params: import_params(module, descriptor, &mut report),
+        });
+        descriptors.push(descriptor);
+    }
+
+    place_modules(&mut plugins, &descriptors, patch, &mut report);
+
+    let mut cables: Vec<Cable> = Vec::new();
+    for cable in &patch.cables {
+        let output = indices.get(&cable.output_module_id).copied();
+        let input = indices.get(&cable.input_module_id).copied();
+        // VCV Rack port ids only mean something through the port tables
+        let output_port = output.and_then(|i| vcv_model(descriptors[i].slug)?.output_index(cable.output_id).map(|port| (i, port)));
+        let input_port = input.and_then(|i| vcv_model(descriptors[i].slug)?.input_index(cable.input_id).map(|port| (i, port)));
+        let ports = match (output_port, input_port) {
+            (Some((output, output_port)), Some((input, input_port))) => {
+                Some((PortRef::new(ids[output], output_port), PortRef::new(ids[input], input_port)))
+            }
+            _ => None,
+        };
+        // An input takes a single cable
+        let Some((output, input)) = ports.filter(|(_, input)| !cables.iter().any(|c| c.input == *input)) else {
+            report.dropped_cables.push(cable_name(cable, patch));
+            continue;
+        };
+        let id = cables.len();
+        cables.push(Cable {
+            id,
+            output,
+            input,
+            color: import_color(cable).unwrap_or(CABLE_COLORS[id % CABLE_COLORS.len()]),
+        });
+    }
+
+    (RackState { plugins, cables }, report)
+}
+
+/// Params by id, defaulting the ones the patch doesn't mention. Values are
+/// converted to ours; the ones that don't fit the range of our param are
+/// clamped and reported.
+fn import_params(module: &VcvModule, descriptor: &ModuleDescriptor, report: &mut ImportReport) -> Vec<f32> {
+    let model = vcv_model(descriptor.slug);
+    let mut params = descriptor.default_params();
+    for (index, param) in module.params.iter().enumerate() {
+        let vcv_id = param.id.unwrap_or(index);
+        let Some(id) = model.map_or(Some(vcv_id), |m| m.param_index(vcv_id)) else {
+            continue;
+        };
+        if let (Some(value), Some(info)) = (params.get_mut(id), descriptor.params.get(id)) {
+            let converted = model.map_or(param.value, |m| m.param_scale(id).from_vcv(param.value));
+            *value = info.clamp(converted);
+            if *value != converted {
+                report.changed_params.push(format!(
+                    "{} of {} from {} to {}, the end of its range", info.name, module_name(module), converted, value
+                ));
+            }
+        }
+    }
+    params
+}
+
+/// Fits the modules onto the rails: the patch is shifted so nothing lies
+/// left of or above the rack, rows past the last rail are put on it, and
+/// modules that would overlap because ours are wider move right.
+fn place_modules(plugins: &mut [PluginState], descriptors: &[&ModuleDescriptor], patch: &VcvPatch, report: &mut ImportReport) {
+    let min_column = plugins.iter().map(|p| p.column).min().unwrap_or(0).min(0);
+    let min_rail = plugins.iter().map(|p| p.rail).min().unwrap_or(0).min(0);
+    for (i, plugin) in plugins.iter_mut().enumerate() {
+        let row = plugin.rail;
+        plugin.column -= min_column;
+        plugin.rail -= min_rail;
+        if plugin.rail >= RAIL_COUNT {
+            report.moved_modules.push(format!(
+                "{} from row {} to row {}, the last rail",
+                module_name(&patch.modules[i]), row, RAIL_COUNT - 1
+            ));
+            plugin.rail = RAIL_COUNT - 1;
+        }
+    }
+
+    let mut order: Vec<usize> = (0..plugins.len()).collect();
+    order.sort_by_key(|&i| (plugins[i].rail, plugins[i].column));
+    let mut rail_end: HashMap<i32, i32> = HashMap::new();
+    for i in order {
+        let end = rail_end.entry(plugins[i].rail).or_insert(0);
+        if plugins[i].column < *end {
+            report.moved_modules.push(format!(
+                "{} from HP {} to HP {} on row {}",
+                module_name(&patch.modules[i]), plugins[i].column, *end, plugins[i].rail
+            ));
+            plugins[i].column = *end;
+        }
+        *end = plugins[i].column + descriptors[i].hp as i32;
+    }
+}
+
+fn import_color(cable: &VcvCable) -> Option<CableColor> {
+    cable.color.as_ref()?.as_str().and_then(CableColor::from_hex)
+}
+
+fn module_name(module: &VcvModule) -> String {
+    format!("{}/{}", module.plugin, module.model)
+}
+
+fn cable_name(cable: &VcvCable, patch: &VcvPatch) -> String {
+    let module = |id: i64| patch.modules.iter().enumerate()
+        .find(|(index, m)| m.id.unwrap_or(*index as i64) == id)
+        .map_or_else(|| format!("module {}", id), |(_, m)| module_name(m));
+    format!(
+        "from output {} of {} to input {} of {}",
+        cable.output_id, module(cable.output_module_id), cable.input_id, module(cable.input_module_id)
+    )
+}
@@ -0,0 +1,163 @@
+use crate::models::module::ModuleDescriptor;
+use crate::modules;
+
+/// How a VCV Rack param value converts to the value of our param.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParamScale {
+    Same,
+    /// VCV Rack squares the knob value into a gain, ours is the gain itself.
+    Squared,
+}
+
+impl ParamScale {
+    /// Our value for a VCV Rack value.
+    pub fn from_vcv(self, value: f32) -> f32 {
+        match self {
+            ParamScale::Same => value,
+            ParamScale::Squared => value * value,
+        }
+    }
+
+    /// VCV Rack value for our value.
+    pub fn to_vcv(self, value: f32) -> f32 {
+        match self {
+            ParamScale::Same => value,
+            ParamScale::Squared => value.max(0.0).sqrt(),
+        }
+    }
+}
+
+/// A module type of ours and the VCV Rack plugin and model it corresponds to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VcvModel {
+    pub slug: &'static str,
+    pub plugin: &'static str,
+    pub model: &'static str,
+    /// VCV Rack param id of each of our params, in order. Empty when the ids
+    /// are the same as ours.
+    pub params: &'static [usize],
+    /// How the VCV Rack value of each of our params converts to ours, in
+    /// order. Params past the end have the same value in both.
+    pub scales: &'static [ParamScale],
+    /// VCV Rack input id of each of our inputs, in order, or `None` where
+    /// VCV Rack has no such input.
+    pub inputs: &'static [Option<usize>],
+    /// Likewise for the outputs.
+    pub outputs: &'static [Option<usize>],
+}
+
+impl VcvModel {
+    /// Our param index for a VCV Rack param id.
+    pub fn param_index(&self, vcv_id: usize) -> Option<usize> {
+        if self.params.is_empty() {
+            Some(vcv_id)
+        } else {
+            self.params.iter().position(|&id| id == vcv_id)
+        }
+    }
+
+    /// VCV Rack param id of our param `index`.
+    pub fn vcv_param_id(&self, index: usize) -> usize {
+        self.params.get(index).copied().unwrap_or(index)
+    }
+
+    /// How the value of our param `index` converts to VCV Rack's.
+    pub fn param_scale(&self, index: usize) -> ParamScale {
+        self.scales.get(index).copied().unwrap_or(ParamScale::Same)
+    }
+
+    /// Our input index for a VCV Rack input id.
+    pub fn input_index(&self, vcv_id: usize) -> Option<usize> {
+        self.inputs.iter().position(|&id| id == Some(vcv_id))
+    }
+
+    /// Our output index for a VCV Rack output id.
+    pub fn output_index(&self, vcv_id: usize) -> Option<usize> {
+        self.outputs.iter().position(|&id| id == Some(vcv_id))
+    }
+
+    /// VCV Rack input id of our input `index`, if VCV Rack has it.
+    pub fn vcv_input_id(&self, index: usize) -> Option<usize> {
+        self.inputs.get(index).copied().flatten()
+    }
+
+    /// VCV Rack output id of our output `index`, if VCV Rack has it.
+    pub fn vcv_output_id(&self, index: usize) -> Option<usize> {
+        self.outputs.get(index).copied().flatten()
+    }
+}
+
+/// Which VCV Rack module each of our module types stands for.
+pub static VCV_MODELS: &[VcvModel] = &[
+    VcvModel { slug: "Blank", plugin: "Core", model: "Blank", params: &[], scales: &[], inputs: &[], outputs: &[] },
+    // Frequency, fine, FM, pulse width, PWM and the FM mode switch
+    VcvModel {
+        slug: "VCO", plugin: "Fundamental", model: "VCO", params: &[2, 3, 4, 5, 6, 7],
+        scales: &[],
+        inputs: &[Some(0), Some(1), Some(2), Some(3)],
+        outputs: &[Some(0), Some(1), Some(2), Some(3)],
+    },
+    // Cutoff, resonance, cutoff CV and drive. The mode switch has no
+    // counterpart; VCV Rack skips the unused id 7. It has no bandpass and
+    // notch outputs
+    VcvModel {
+        slug: "VCF", plugin: "Fundamental", model: "VCF", params: &[0, 2, 3, 4, 7],
+        scales: &[],
+        inputs: &[Some(0), Some(1), Some(2), Some(3)],
+        outputs: &[Some(0), Some(1), None, None],
+    },
+    // Attack, decay, sustain and release. The curve switch has no
+    // counterpart; VCV Rack skips the unused id 9. It has no EOC output
+    VcvModel {
+        slug: "ADSR", plugin: "Fundamental", model: "ADSR", params: &[0, 1, 2, 3, 9],
+        scales: &[],
+        inputs: &[Some(0), Some(1), Some(2), Some(3), Some(4), Some(5)],
+        outputs: &[Some(0), None],
+    },
+    VcvModel {
+        slug: "VCA", plugin: "Fundamental", model: "VCA", params: &[],
+        scales: &[],
+        inputs: &[Some(0), Some(1), Some(2), Some(3), Some(4), Some(5)],
+        outputs: &[Some(0), Some(1)],
+    },
+    // Channel levels and master level. Pan and mute have no counterpart;
+    // VCV Rack skips the unused ids 5 to 12. Its channel level knobs are
+    // squared into a gain. Its mono mix output is our left output, and it
+    // has no right output
+    VcvModel {
+        slug: "Mixer", plugin: "Fundamental", model: "VCMixer", params: &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 0],
+        scales: &[ParamScale::Squared, ParamScale::Squared, ParamScale::Squared, ParamScale::Squared],
+        inputs: &[Some(0), Some(1), Some(2), Some(3), Some(4), Some(5), Some(6), Some(7), Some(8)],
+        outputs: &[Some(0), Some(1), Some(2), Some(3), Some(4), None],
+    },
+    // Frequency and the offset switch, which is our polarity. The shape
+    // switch has no counterpart; VCV Rack skips the unused id 7. Our rate
+    // input is its FM input, and its outputs are one per shape rather than
+    // our phase taps, so they have no counterpart
+    VcvModel {
+        slug: "LFO", plugin: "Fundamental", model: "LFO", params: &[2, 7, 0],
+        scales: &[],
+        inputs: &[Some(0), Some(2), Some(4)],
+        outputs: &[None, None, None, None],
+    },
+    // VCV Rack squares its level knob into a gain. Its outputs, which bring
+    // audio in from the device, have no counterpart
+    VcvModel {
+        slug: "Audio", plugin: "Core", model: "AudioInterface2", params: &[],
+        scales: &[ParamScale::Squared],
+        inputs: &[Some(0), Some(1)],
+        outputs: &[],
+    },
+];
+
+/// Our module type for a VCV Rack plugin and model.
+pub fn find_vcv_model(plugin: &str, model: &str) -> Option<&'static ModuleDescriptor> {
+    VCV_MODELS.iter()
+        .find(|m| m.plugin == plugin && m.model == model)
+        .and_then(|m| modules::find(m.slug))
+}
+
+/// VCV Rack plugin and model of one of our module types.
+pub fn vcv_model(slug: &str) -> Option<&'static VcvModel> {
+    VCV_MODELS.iter().find(|m| m.slug == slug)
+}
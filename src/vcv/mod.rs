@@ -0,0 +1,8 @@
+pub mod export;
+pub mod format;
+pub mod import;
+pub mod mapping;
+
+pub use export::{export_file, export_patch};
+pub use format::{VcvCable, VcvModule, VcvParam, VcvPatch};
+pub use import::{import_file, import_patch, ImportReport};